@@ -1,2 +1,3 @@
+pub mod secrets;
 pub mod settings;
 pub mod themes;
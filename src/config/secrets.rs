@@ -0,0 +1,223 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::settings::{config_dir, update_config, write_private, AppConfig};
+
+/// Environment variable checked for the API key unless `apiKeyEnv` names
+/// another one.
+pub const DEFAULT_API_KEY_ENV: &str = "MINIMAX_API_KEY";
+
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "minmax-code";
+#[cfg(feature = "keyring")]
+const KEYRING_USER: &str = "api-key";
+
+/// Where the API key is kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyStore {
+    /// `apiKey` in `~/.minmax-code/config.json`.
+    #[default]
+    Config,
+    /// `~/.minmax-code/secrets.json`, readable only by the owner.
+    SecretsFile,
+    /// The OS credential store (Keychain, Credential Manager, Secret Service).
+    Keyring,
+}
+
+impl KeyStore {
+    pub fn is_default(&self) -> bool {
+        *self == Self::Config
+    }
+
+    /// Stores this build supports, in the order they are offered.
+    pub fn available() -> Vec<KeyStore> {
+        let mut stores = vec![KeyStore::Config, KeyStore::SecretsFile];
+        if cfg!(feature = "keyring") {
+            stores.push(KeyStore::Keyring);
+        }
+        stores
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            KeyStore::Config => "config file",
+            KeyStore::SecretsFile => "secrets file (0600)",
+            KeyStore::Keyring => "OS keyring",
+        }
+    }
+}
+
+/// Returns the path to ~/.minmax-code/secrets.json
+pub fn secrets_file() -> PathBuf {
+    config_dir().join("secrets.json")
+}
+
+/// Fill `config.api_key` from, in order: the environment variable, the
+/// configured key store, the config file itself. Surrounding whitespace from
+/// pasting is removed. Problems reading a store are added to `config.warnings`.
+pub fn resolve_api_key(config: &mut AppConfig) {
+    let var = config.api_key_env.as_deref().unwrap_or(DEFAULT_API_KEY_ENV);
+    if let Some(key) = std::env::var(var).ok().filter(|k| !k.trim().is_empty()) {
+        config.api_key = key.trim().to_string();
+        return;
+    }
+    let stored = match config.api_key_store {
+        KeyStore::Config => Ok(None),
+        KeyStore::SecretsFile => read_secrets_file(&secrets_file()),
+        KeyStore::Keyring => read_keyring(),
+    };
+    match stored {
+        Ok(Some(key)) => config.api_key = key,
+        Ok(None) => {}
+        Err(e) => config.warnings.push(format!("Could not read the API key: {}", e)),
+    }
+    config.api_key = config.api_key.trim().to_string();
+}
+
+/// Save the API key in `store`, and record the choice in the global config.
+/// The key is removed from config.json unless that is the chosen store.
+pub fn store_api_key(key: &str, store: KeyStore) -> Result<()> {
+    match store {
+        KeyStore::Config => {}
+        KeyStore::SecretsFile => write_secrets_file(&secrets_file(), key)?,
+        KeyStore::Keyring => write_keyring(key)?,
+    }
+    let in_config = if store == KeyStore::Config { key } else { "" };
+    update_config(serde_json::json!({ "apiKey": in_config, "apiKeyStore": store }))?;
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SecretsFile {
+    api_key: String,
+}
+
+fn read_secrets_file(path: &Path) -> Result<Option<String>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(path)?.permissions().mode();
+        if mode & 0o077 != 0 {
+            return Err(anyhow!(
+                "{} is accessible by other users (mode {:o}); run `chmod 600` on it",
+                path.display(),
+                mode & 0o777
+            ));
+        }
+    }
+    let secrets: SecretsFile = serde_json::from_str(&fs::read_to_string(path)?)?;
+    Ok(Some(secrets.api_key).filter(|k| !k.is_empty()))
+}
+
+fn write_secrets_file(path: &Path, key: &str) -> Result<()> {
+    let json = serde_json::to_string_pretty(&SecretsFile { api_key: key.to_string() })?;
+    write_private(path, &json)
+}
+
+/// Run a keyring operation on its own thread: the Secret Service backend
+/// starts a runtime of its own, which cannot happen inside ours.
+#[cfg(feature = "keyring")]
+fn with_keyring<T: Send>(
+    op: impl FnOnce(&keyring::Entry) -> keyring::Result<T> + Send,
+) -> Result<T> {
+    std::thread::scope(|s| {
+        s.spawn(|| op(&keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?))
+            .join()
+    })
+    .map_err(|_| anyhow!("keyring access panicked"))?
+    .map_err(Into::into)
+}
+
+#[cfg(feature = "keyring")]
+fn read_keyring() -> Result<Option<String>> {
+    with_keyring(|entry| match entry.get_password() {
+        Ok(key) => Ok(Some(key)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e),
+    })
+}
+
+#[cfg(feature = "keyring")]
+fn write_keyring(key: &str) -> Result<()> {
+    with_keyring(|entry| entry.set_password(key))
+}
+
+#[cfg(not(feature = "keyring"))]
+fn read_keyring() -> Result<Option<String>> {
+    Err(anyhow!("this build has no keyring support"))
+}
+
+#[cfg(not(feature = "keyring"))]
+fn write_keyring(_key: &str) -> Result<()> {
+    Err(anyhow!("this build has no keyring support"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn secrets_file_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("secrets.json");
+        assert_eq!(read_secrets_file(&path).unwrap(), None);
+        write_secrets_file(&path, "sk-test-123456").unwrap();
+        assert_eq!(read_secrets_file(&path).unwrap().as_deref(), Some("sk-test-123456"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn secrets_file_rejects_open_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("secrets.json");
+        write_secrets_file(&path, "sk-test-123456").unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        let err = read_secrets_file(&path).unwrap_err().to_string();
+        assert!(err.contains("chmod 600"));
+    }
+
+    #[test]
+    fn env_var_overrides_config() {
+        let mut config = AppConfig {
+            api_key: "from-file".to_string(),
+            api_key_env: Some("MINMAX_CODE_TEST_API_KEY".to_string()),
+            ..AppConfig::default()
+        };
+        resolve_api_key(&mut config);
+        assert_eq!(config.api_key, "from-file");
+
+        std::env::set_var("MINMAX_CODE_TEST_API_KEY", "from-env");
+        resolve_api_key(&mut config);
+        std::env::remove_var("MINMAX_CODE_TEST_API_KEY");
+        assert_eq!(config.api_key, "from-env");
+    }
+
+    #[test]
+    fn stored_key_is_trimmed() {
+        let mut config = AppConfig {
+            api_key: "  pasted-key\n".to_string(),
+            api_key_env: Some("MINMAX_CODE_TEST_UNSET_API_KEY".to_string()),
+            api_key_store: KeyStore::Config,
+            ..AppConfig::default()
+        };
+        resolve_api_key(&mut config);
+        assert_eq!(config.api_key, "pasted-key");
+    }
+
+    #[test]
+    fn key_store_serializes_kebab_case() {
+        assert_eq!(serde_json::to_string(&KeyStore::SecretsFile).unwrap(), "\"secrets-file\"");
+        assert!(KeyStore::available().contains(&KeyStore::Config));
+    }
+}
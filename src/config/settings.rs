@@ -2,14 +2,68 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use super::secrets::{self, KeyStore};
 use super::themes::DEFAULT_THEME;
+use crate::core::project::find_git_root;
 
 pub const DEFAULT_MODEL: &str = "MiniMax-M2.5";
 
-pub const VALID_MODELS: &[&str] = &["MiniMax-M2.5", "MiniMax-M2.5-highspeed"];
+/// Context window assumed for models that do not set one.
+pub const DEFAULT_CONTEXT_WINDOW: u64 = 200_000;
 
+/// A model that can be selected with `/model`. Entries under `models` in
+/// the config override the built-in entry with the same id, or add a model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelInfo {
+    pub id: String,
+    /// Short description for model lists, such as its speed.
+    #[serde(default)]
+    pub label: String,
+    /// Tokens the model accepts, prompt and output together.
+    #[serde(default = "default_context_window")]
+    pub context_window: u64,
+    /// Sent as `max_tokens`; the API default applies when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u64>,
+    /// Whether the model returns reasoning that is passed back to it.
+    #[serde(default = "default_true")]
+    pub reasoning: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<ModelPrice>,
+}
+
+impl ModelInfo {
+    /// A model not in the registry: default context window, reasoning on.
+    pub fn with_defaults(id: &str) -> Self {
+        Self::builtin(id, "")
+    }
+
+    fn builtin(id: &str, label: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            label: label.to_string(),
+            context_window: DEFAULT_CONTEXT_WINDOW,
+            max_output_tokens: None,
+            reasoning: true,
+            price: None,
+        }
+    }
+
+    /// Label and context window, for model lists.
+    pub fn description(&self) -> String {
+        let context = format!("{}k context", self.context_window / 1000);
+        if self.label.is_empty() {
+            context
+        } else {
+            format!("{} · {}", self.label, context)
+        }
+    }
+}
+
+/// Models known without any configuration: id and label.
 pub const AVAILABLE_MODELS: &[(&str, &str)] = &[
     ("MiniMax-M2.5", "Latest, ~60 tps"),
     ("MiniMax-M2.5-highspeed", "Latest fast, ~100 tps"),
@@ -17,20 +71,172 @@ pub const AVAILABLE_MODELS: &[(&str, &str)] = &[
     ("MiniMax-M2.1-highspeed", "Previous gen fast, ~100 tps"),
 ];
 
-pub const MODEL_IDS: &[&str] = &[
-    "MiniMax-M2.5",
-    "MiniMax-M2.5-highspeed",
-    "MiniMax-M2.1",
-    "MiniMax-M2.1-highspeed",
-];
+/// `AVAILABLE_MODELS` with default context windows.
+pub fn builtin_models() -> Vec<ModelInfo> {
+    AVAILABLE_MODELS
+        .iter()
+        .map(|(id, label)| ModelInfo::builtin(id, label))
+        .collect()
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_context_window() -> u64 {
+    DEFAULT_CONTEXT_WINDOW
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct McpServerConfig {
     pub command: String,
     #[serde(default)]
     pub args: Vec<String>,
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// If non-empty, only these tools (unprefixed names) are exposed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub enabled_tools: Vec<String>,
+    /// Tools that are never exposed, even if listed in `enabled_tools`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_tools: Vec<String>,
+    /// Tools that are safe to use in PLAN mode, regardless of annotations.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub read_only_tools: Vec<String>,
+    /// Treat every tool from this server as read-only.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+}
+
+impl McpServerConfig {
+    /// Whether a tool passes this server's allow/deny lists.
+    pub fn is_tool_enabled(&self, tool: &str) -> bool {
+        if self.disabled_tools.iter().any(|t| t == tool) {
+            return false;
+        }
+        self.enabled_tools.is_empty() || self.enabled_tools.iter().any(|t| t == tool)
+    }
+
+    /// Whether the config explicitly marks a tool as read-only.
+    pub fn marks_read_only(&self, tool: &str) -> bool {
+        self.read_only || self.read_only_tools.iter().any(|t| t == tool)
+    }
+}
+
+/// Price of a model in USD per million tokens. Reasoning tokens are billed
+/// as output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+}
+
+/// Session database housekeeping. Everything is off unless configured.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageConfig {
+    /// Delete unpinned sessions inactive for more than this many days.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention_days: Option<u32>,
+    /// Keep at most this many unpinned sessions, deleting the oldest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_sessions: Option<usize>,
+    /// Store tool results larger than this many bytes compressed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compress_tool_results_over: Option<usize>,
+}
+
+impl StorageConfig {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// When conversation history is compacted to fit the model's context window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextConfig {
+    /// Summarize older history automatically before requests.
+    #[serde(default = "default_true")]
+    pub auto_compact: bool,
+    /// Share of the context window in use that triggers automatic compaction.
+    #[serde(default = "default_compact_at")]
+    pub compact_at: f64,
+    /// Model that writes the summaries; defaults to the chat model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compact_model: Option<String>,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            auto_compact: true,
+            compact_at: default_compact_at(),
+            compact_model: None,
+        }
+    }
+}
+
+impl ContextConfig {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// How copied text reaches the clipboard.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClipboardBackend {
+    /// The OSC 52 escape sequence, applied by the terminal; works over SSH.
+    #[default]
+    Osc52,
+    /// The system clipboard tool (pbcopy, wl-copy, xclip, xsel or clip),
+    /// falling back to OSC 52.
+    System,
+}
+
+impl ClipboardBackend {
+    pub fn is_default(&self) -> bool {
+        *self == Self::Osc52
+    }
+}
+
+fn default_compact_at() -> f64 {
+    0.5
+}
+
+/// Which instruction files are loaded into the system prompt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstructionsConfig {
+    /// File names looked for in each directory, in order of preference; only
+    /// the first one found in a directory is loaded.
+    #[serde(default = "default_instruction_files")]
+    pub file_names: Vec<String>,
+    /// Upper bound on the instruction text added to the system prompt, in
+    /// characters.
+    #[serde(default = "default_instruction_max_chars")]
+    pub max_chars: usize,
+}
+
+impl Default for InstructionsConfig {
+    fn default() -> Self {
+        Self {
+            file_names: default_instruction_files(),
+            max_chars: default_instruction_max_chars(),
+        }
+    }
+}
+
+impl InstructionsConfig {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn default_instruction_files() -> Vec<String> {
+    vec!["agent.md".to_string(), "AGENTS.md".to_string(), "CLAUDE.md".to_string()]
+}
+
+fn default_instruction_max_chars() -> usize {
+    40_000
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,12 +244,52 @@ pub struct McpServerConfig {
 pub struct AppConfig {
     #[serde(default)]
     pub api_key: String,
+    /// Environment variable holding the API key, instead of `MINIMAX_API_KEY`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+    /// Where the API key is kept when not in `api_key`.
+    #[serde(default, skip_serializing_if = "KeyStore::is_default")]
+    pub api_key_store: KeyStore,
     #[serde(default = "default_model")]
     pub model: String,
     #[serde(default = "default_theme")]
     pub theme: String,
     #[serde(default)]
     pub mcp_servers: HashMap<String, McpServerConfig>,
+    /// Name new sessions from their first exchange.
+    #[serde(default = "default_true")]
+    pub auto_title: bool,
+    /// Model used for session titles; defaults to `model`. A fast, cheap
+    /// model is enough.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_model: Option<String>,
+    /// Per-model prices used to show what sessions cost. These take
+    /// precedence over `price` in the model registry.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub prices: HashMap<String, ModelPrice>,
+    /// Models added to, or overriding, the built-in list.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<ModelInfo>,
+    #[serde(default, skip_serializing_if = "StorageConfig::is_default")]
+    pub storage: StorageConfig,
+    #[serde(default, skip_serializing_if = "InstructionsConfig::is_default")]
+    pub instructions: InstructionsConfig,
+    #[serde(default, skip_serializing_if = "ContextConfig::is_default")]
+    pub context: ContextConfig,
+    /// How copied messages and code blocks reach the clipboard.
+    #[serde(default, skip_serializing_if = "ClipboardBackend::is_default")]
+    pub clipboard: ClipboardBackend,
+    /// Project roots whose checked-in MCP servers may start; added with
+    /// `/trust`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted_projects: Vec<String>,
+    /// MCP servers from the checked-in project config, held back until the
+    /// project is trusted.
+    #[serde(skip)]
+    pub untrusted_mcp_servers: HashMap<String, McpServerConfig>,
+    /// Problems found while loading, shown once the app starts.
+    #[serde(skip)]
+    pub warnings: Vec<String>,
 }
 
 fn default_model() -> String {
@@ -54,17 +300,69 @@ fn default_theme() -> String {
     DEFAULT_THEME.to_string()
 }
 
+fn default_true() -> bool {
+    true
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             api_key: String::new(),
+            api_key_env: None,
+            api_key_store: KeyStore::default(),
             model: DEFAULT_MODEL.to_string(),
             theme: DEFAULT_THEME.to_string(),
             mcp_servers: HashMap::new(),
+            auto_title: true,
+            title_model: None,
+            prices: HashMap::new(),
+            models: Vec::new(),
+            storage: StorageConfig::default(),
+            instructions: InstructionsConfig::default(),
+            context: ContextConfig::default(),
+            clipboard: ClipboardBackend::default(),
+            trusted_projects: Vec::new(),
+            untrusted_mcp_servers: HashMap::new(),
+            warnings: Vec::new(),
         }
     }
 }
 
+impl AppConfig {
+    /// The model registry: built-in models with configured entries applied.
+    pub fn models(&self) -> Vec<ModelInfo> {
+        let mut models = builtin_models();
+        for model in &self.models {
+            match models.iter_mut().find(|m| m.id == model.id) {
+                Some(existing) => *existing = model.clone(),
+                None => models.push(model.clone()),
+            }
+        }
+        models
+    }
+
+    pub fn find_model(&self, id: &str) -> Option<ModelInfo> {
+        self.models().into_iter().find(|m| m.id == id)
+    }
+
+    /// The selected model, with defaults if it is not in the registry.
+    pub fn model_info(&self) -> ModelInfo {
+        self.find_model(&self.model)
+            .unwrap_or_else(|| ModelInfo::with_defaults(&self.model))
+    }
+
+    /// Prices by model id from the registry and `prices`.
+    pub fn model_prices(&self) -> HashMap<String, ModelPrice> {
+        let mut prices: HashMap<String, ModelPrice> = self
+            .models()
+            .into_iter()
+            .filter_map(|m| Some((m.id, m.price?)))
+            .collect();
+        prices.extend(self.prices.clone());
+        prices
+    }
+}
+
 /// Returns the path to ~/.minmax-code/
 pub fn config_dir() -> PathBuf {
     dirs::home_dir()
@@ -77,7 +375,27 @@ pub fn config_file() -> PathBuf {
     config_dir().join("config.json")
 }
 
+/// Shared project settings, checked into the repository.
+pub const PROJECT_CONFIG_FILE: &str = ".minmax-code/config.json";
+/// Personal project settings, kept out of version control.
+pub const LOCAL_CONFIG_FILE: &str = ".minmax-code/config.local.json";
+
+/// Settings in effect for the current directory: the global config, then
+/// the project's `.minmax-code/config.json`, then `config.local.json`, each
+/// overriding the one before. The API key is then taken from the
+/// environment or key store when set there.
 pub fn load_config() -> AppConfig {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let global = load_global_config();
+    let root = find_git_root(&cwd).unwrap_or_else(|| cwd.clone());
+    let trusted = global.trusted_projects.iter().any(|p| Path::new(p) == root);
+    let mut config = layer_config(&global, &project_config_files(&cwd), trusted);
+    secrets::resolve_api_key(&mut config);
+    config
+}
+
+/// Only the global `~/.minmax-code/config.json`.
+pub fn load_global_config() -> AppConfig {
     let dir = config_dir();
     let file = config_file();
 
@@ -94,8 +412,8 @@ pub fn load_config() -> AppConfig {
     match fs::read_to_string(&file) {
         Ok(raw) => match serde_json::from_str::<AppConfig>(&raw) {
             Ok(mut config) => {
-                // Migrate: if saved model is not valid, reset to default
-                if !VALID_MODELS.contains(&config.model.as_str()) {
+                // Migrate: if saved model is not in the registry, reset to default
+                if config.find_model(&config.model).is_none() {
                     config.model = DEFAULT_MODEL.to_string();
                     let _ = save_config(&config);
                 }
@@ -107,18 +425,166 @@ pub fn load_config() -> AppConfig {
     }
 }
 
+/// Project config files for `cwd`, lowest precedence first, as
+/// `(path, trusted_with_secrets)`. The project root is the enclosing git
+/// repository, or `cwd` outside of git.
+pub fn project_config_files(cwd: &Path) -> Vec<(PathBuf, bool)> {
+    let root = find_git_root(cwd).unwrap_or_else(|| cwd.to_path_buf());
+    vec![
+        (root.join(PROJECT_CONFIG_FILE), false),
+        (root.join(LOCAL_CONFIG_FILE), true),
+    ]
+}
+
+/// Settings that decide where the API key comes from or which projects are
+/// trusted; never taken from a checked-in file.
+const SECRET_KEYS: &[&str] = &["apiKey", "apiKeyEnv", "apiKeyStore", "trustedProjects"];
+
+/// Merge config files over `base`. Objects merge key by key (so a local
+/// override can add one MCP server), anything else replaces the value below
+/// it. `SECRET_KEYS` are dropped from files not trusted with secrets, and
+/// their MCP servers, which run commands at startup, are held in
+/// `untrusted_mcp_servers` unless `trust_project` is set. Missing files are
+/// skipped; files that cannot be read or parsed are skipped with a warning.
+fn layer_config(base: &AppConfig, files: &[(PathBuf, bool)], trust_project: bool) -> AppConfig {
+    let Ok(mut merged) = serde_json::to_value(base) else {
+        return base.clone();
+    };
+    let mut warnings = base.warnings.clone();
+    let mut untrusted_mcp_servers = HashMap::new();
+    for (path, secrets) in files {
+        let raw = match fs::read_to_string(path) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => {
+                warnings.push(format!("Ignored {}: {}", path.display(), e));
+                continue;
+            }
+        };
+        let mut layer = match serde_json::from_str::<serde_json::Value>(&raw) {
+            Ok(layer) if layer.is_object() => layer,
+            Ok(_) => {
+                warnings.push(format!("Ignored {}: expected a JSON object", path.display()));
+                continue;
+            }
+            Err(e) => {
+                warnings.push(format!("Ignored {}: {}", path.display(), e));
+                continue;
+            }
+        };
+        if !secrets {
+            if let Some(obj) = layer.as_object_mut() {
+                for key in SECRET_KEYS {
+                    if obj.remove(*key).is_some() {
+                        warnings.push(format!("Ignored \"{}\" in {}", key, path.display()));
+                    }
+                }
+                if !trust_project {
+                    if let Some(servers) = obj.remove("mcpServers") {
+                        match serde_json::from_value::<HashMap<String, McpServerConfig>>(servers) {
+                            Ok(servers) if !servers.is_empty() => {
+                                warnings.push(untrusted_servers_warning(path, &servers));
+                                untrusted_mcp_servers.extend(servers);
+                            }
+                            Ok(_) => {}
+                            Err(e) => warnings.push(format!(
+                                "Ignored \"mcpServers\" in {}: {}",
+                                path.display(),
+                                e
+                            )),
+                        }
+                    }
+                }
+            }
+        }
+        let mut candidate = merged.clone();
+        merge_json(&mut candidate, layer);
+        match serde_json::from_value::<AppConfig>(candidate.clone()) {
+            Ok(_) => merged = candidate,
+            Err(e) => warnings.push(format!("Ignored {}: {}", path.display(), e)),
+        }
+    }
+    let mut config = serde_json::from_value::<AppConfig>(merged).unwrap_or_else(|_| base.clone());
+    if config.find_model(&config.model).is_none() {
+        config.model = base.model.clone();
+    }
+    // A server also defined in a personal or global file is not held back
+    untrusted_mcp_servers.retain(|name, _| !config.mcp_servers.contains_key(name));
+    config.untrusted_mcp_servers = untrusted_mcp_servers;
+    config.warnings = warnings;
+    config
+}
+
+fn untrusted_servers_warning(path: &Path, servers: &HashMap<String, McpServerConfig>) -> String {
+    let mut list: Vec<String> = servers
+        .iter()
+        .map(|(name, server)| {
+            let command = std::iter::once(&server.command)
+                .chain(&server.args)
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!("{} ({})", name, command)
+        })
+        .collect();
+    list.sort();
+    format!(
+        "{} defines MCP servers that run commands on this machine: {}. They were not started; review them and run /trust to allow this project's MCP servers",
+        path.display(),
+        list.join(", ")
+    )
+}
+
+fn merge_json(base: &mut serde_json::Value, layer: serde_json::Value) {
+    match (base, layer) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
 pub fn save_config(config: &AppConfig) -> Result<()> {
     let dir = config_dir();
     if !dir.exists() {
         fs::create_dir_all(&dir)?;
     }
     let json = serde_json::to_string_pretty(config)?;
-    fs::write(config_file(), json)?;
+    write_private(&config_file(), &json)
+}
+
+/// Write a file only the owner can read (mode 0600 on Unix), tightening
+/// the permissions of an existing file.
+pub fn write_private(path: &Path, contents: &str) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        file.write_all(contents.as_bytes())?;
+    }
+    #[cfg(not(unix))]
+    fs::write(path, contents)?;
     Ok(())
 }
 
+/// Change settings in the global config file. Only the given keys are
+/// written, so project settings in effect are never copied into it.
 pub fn update_config(partial: serde_json::Value) -> Result<AppConfig> {
-    let mut config = load_config();
+    let mut config = load_global_config();
 
     if let Some(key) = partial.get("apiKey").and_then(|v| v.as_str()) {
         config.api_key = key.to_string();
@@ -129,11 +595,26 @@ pub fn update_config(partial: serde_json::Value) -> Result<AppConfig> {
     if let Some(theme) = partial.get("theme").and_then(|v| v.as_str()) {
         config.theme = theme.to_string();
     }
+    if let Some(store) = partial.get("apiKeyStore") {
+        config.api_key_store = serde_json::from_value(store.clone())?;
+    }
 
     save_config(&config)?;
     Ok(config)
 }
 
+/// Record `root` in the global config as a project whose checked-in MCP
+/// servers may start.
+pub fn trust_project(root: &Path) -> Result<()> {
+    let mut config = load_global_config();
+    let root = root.to_string_lossy().to_string();
+    if !config.trusted_projects.contains(&root) {
+        config.trusted_projects.push(root);
+        save_config(&config)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -158,15 +639,54 @@ mod tests {
     fn config_serialization_round_trip() {
         let config = AppConfig {
             api_key: "test-key-123".to_string(),
+            api_key_env: Some("WORK_MINIMAX_KEY".to_string()),
+            api_key_store: KeyStore::Keyring,
             model: "MiniMax-M2.5-highspeed".to_string(),
             theme: "gruvbox".to_string(),
             mcp_servers: HashMap::new(),
+            auto_title: false,
+            title_model: Some("MiniMax-M2.5-highspeed".to_string()),
+            prices: HashMap::from([(
+                "MiniMax-M2.5".to_string(),
+                ModelPrice { input: 0.3, output: 1.2 },
+            )]),
+            models: Vec::new(),
+            storage: StorageConfig {
+                retention_days: Some(90),
+                max_sessions: None,
+                compress_tool_results_over: Some(16_384),
+            },
+            instructions: InstructionsConfig {
+                file_names: vec!["AGENTS.md".to_string()],
+                max_chars: 8_000,
+            },
+            context: ContextConfig {
+                auto_compact: false,
+                compact_at: 0.7,
+                compact_model: Some("MiniMax-M2.5-highspeed".to_string()),
+            },
+            clipboard: ClipboardBackend::System,
+            trusted_projects: vec!["/work/repo".to_string()],
+            untrusted_mcp_servers: HashMap::new(),
+            warnings: Vec::new(),
         };
         let json = serde_json::to_string(&config).unwrap();
         let deserialized: AppConfig = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized.api_key, "test-key-123");
         assert_eq!(deserialized.model, "MiniMax-M2.5-highspeed");
         assert_eq!(deserialized.theme, "gruvbox");
+        assert!(!deserialized.auto_title);
+        assert_eq!(deserialized.title_model.as_deref(), Some("MiniMax-M2.5-highspeed"));
+        assert_eq!(deserialized.prices["MiniMax-M2.5"], ModelPrice { input: 0.3, output: 1.2 });
+        assert_eq!(deserialized.storage, config.storage);
+        assert!(json.contains("\"retentionDays\":90"));
+        assert_eq!(deserialized.instructions, config.instructions);
+        assert_eq!(deserialized.api_key_env.as_deref(), Some("WORK_MINIMAX_KEY"));
+        assert_eq!(deserialized.api_key_store, KeyStore::Keyring);
+        assert_eq!(deserialized.context, config.context);
+        assert_eq!(deserialized.clipboard, ClipboardBackend::System);
+        assert!(json.contains("\"clipboard\":\"system\""));
+        assert_eq!(deserialized.trusted_projects, ["/work/repo"]);
     }
 
     #[test]
@@ -176,6 +696,34 @@ mod tests {
         assert_eq!(config.api_key, "abc");
         assert_eq!(config.model, "MiniMax-M2.5");
         assert_eq!(config.theme, "tokyo-night");
+        assert!(config.auto_title);
+        assert!(config.title_model.is_none());
+        assert!(config.storage.is_default());
+        assert_eq!(config.instructions.file_names[0], "agent.md");
+
+        let partial: AppConfig = serde_json::from_str(r#"{"instructions": {"maxChars": 100}}"#).unwrap();
+        assert_eq!(partial.instructions.max_chars, 100);
+        assert_eq!(partial.instructions.file_names, InstructionsConfig::default().file_names);
+    }
+
+    #[test]
+    fn mcp_server_config_tool_lists() {
+        let json = r#"{
+            "command": "npx",
+            "enabledTools": ["read", "write", "search"],
+            "disabledTools": ["write"],
+            "readOnlyTools": ["search"]
+        }"#;
+        let server: McpServerConfig = serde_json::from_str(json).unwrap();
+        assert!(server.is_tool_enabled("read"));
+        assert!(!server.is_tool_enabled("write"));
+        assert!(!server.is_tool_enabled("delete"));
+        assert!(server.marks_read_only("search"));
+        assert!(!server.marks_read_only("read"));
+
+        let all: McpServerConfig = serde_json::from_str(r#"{"command": "x", "readOnly": true}"#).unwrap();
+        assert!(all.is_tool_enabled("anything"));
+        assert!(all.marks_read_only("anything"));
     }
 
     #[test]
@@ -193,4 +741,125 @@ mod tests {
             assert_eq!(loaded.api_key, "my-key");
         });
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_private_restricts_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        with_temp_config(|path| {
+            fs::write(&path, "{}").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+            write_private(&path, "{\"apiKey\": \"k\"}").unwrap();
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+            assert_eq!(fs::read_to_string(&path).unwrap(), "{\"apiKey\": \"k\"}");
+        });
+    }
+
+    #[test]
+    fn project_layers_override_global() {
+        let tmp = TempDir::new().unwrap();
+        let project = tmp.path().join("project.json");
+        let local = tmp.path().join("local.json");
+        fs::write(
+            &project,
+            r#"{"apiKey": "leaked", "model": "MiniMax-M2.5-highspeed",
+                "mcpServers": {"db": {"command": "db-mcp"}}, "storage": {"retentionDays": 30}}"#,
+        )
+        .unwrap();
+        fs::write(
+            &local,
+            r#"{"apiKey": "local-key", "mcpServers": {"db": {"command": "db-mcp"}}, "storage": {"maxSessions": 10}}"#,
+        )
+        .unwrap();
+
+        let mut global = AppConfig {
+            api_key: "global-key".to_string(),
+            theme: "gruvbox".to_string(),
+            ..AppConfig::default()
+        };
+        global.mcp_servers.insert("fs".to_string(), McpServerConfig::default());
+
+        let shared = layer_config(&global, &[(project.clone(), false)], false);
+        assert_eq!(shared.api_key, "global-key");
+        assert_eq!(shared.warnings.len(), 2);
+        assert!(shared.warnings[1].contains("db (db-mcp)"));
+        assert!(shared.warnings[1].contains("/trust"));
+        assert_eq!(shared.model, "MiniMax-M2.5-highspeed");
+        assert_eq!(shared.theme, "gruvbox");
+        assert_eq!(shared.mcp_servers.keys().collect::<Vec<_>>(), ["fs"]);
+        assert_eq!(shared.untrusted_mcp_servers.keys().collect::<Vec<_>>(), ["db"]);
+
+        let config = layer_config(&global, &[(project, false), (local, true)], false);
+        assert_eq!(config.api_key, "local-key");
+        assert_eq!(config.storage.retention_days, Some(30));
+        assert_eq!(config.storage.max_sessions, Some(10));
+        assert_eq!(config.mcp_servers.len(), 2);
+        assert!(config.untrusted_mcp_servers.is_empty());
+    }
+
+    #[test]
+    fn trusted_project_starts_checked_in_mcp_servers() {
+        let tmp = TempDir::new().unwrap();
+        let project = tmp.path().join("project.json");
+        fs::write(
+            &project,
+            r#"{"mcpServers": {"db": {"command": "db-mcp"}}, "trustedProjects": ["/"]}"#,
+        )
+        .unwrap();
+
+        let config = layer_config(&AppConfig::default(), &[(project, false)], true);
+        assert_eq!(config.mcp_servers["db"].command, "db-mcp");
+        assert!(config.untrusted_mcp_servers.is_empty());
+        assert!(config.trusted_projects.is_empty());
+        assert_eq!(config.warnings.len(), 1);
+    }
+
+    #[test]
+    fn invalid_project_layers_are_ignored() {
+        let tmp = TempDir::new().unwrap();
+        let broken = tmp.path().join("broken.json");
+        let bad_model = tmp.path().join("model.json");
+        fs::write(&broken, "{ not json").unwrap();
+        fs::write(&bad_model, r#"{"model": "gpt-9"}"#).unwrap();
+        let missing = tmp.path().join("missing.json");
+
+        let global = AppConfig::default();
+        let bad_type = tmp.path().join("type.json");
+        fs::write(&bad_type, r#"{"theme": 3}"#).unwrap();
+
+        let files = [(broken.clone(), false), (missing, true), (bad_model, false), (bad_type.clone(), false)];
+        let config = layer_config(&global, &files, false);
+        assert_eq!(config.model, DEFAULT_MODEL);
+        assert_eq!(config.theme, DEFAULT_THEME);
+        assert_eq!(config.warnings.len(), 2);
+        assert!(config.warnings[0].starts_with(&format!("Ignored {}: ", broken.display())));
+        assert!(config.warnings[1].starts_with(&format!("Ignored {}: ", bad_type.display())));
+    }
+
+    #[test]
+    fn model_registry_applies_config_entries() {
+        let json = r#"{
+            "model": "MiniMax-M2.1",
+            "models": [
+                {"id": "MiniMax-M2.5", "label": "Pinned", "contextWindow": 128000, "price": {"input": 1, "output": 2}},
+                {"id": "local-coder", "maxOutputTokens": 4096, "reasoning": false}
+            ],
+            "prices": {"MiniMax-M2.5": {"input": 0.3, "output": 1.2}}
+        }"#;
+        let config: AppConfig = serde_json::from_str(json).unwrap();
+        let models = config.models();
+        assert_eq!(models.len(), builtin_models().len() + 1);
+        assert_eq!(models[0].label, "Pinned");
+        assert_eq!(models[0].context_window, 128_000);
+
+        let custom = config.find_model("local-coder").unwrap();
+        assert_eq!(custom.context_window, DEFAULT_CONTEXT_WINDOW);
+        assert_eq!(custom.max_output_tokens, Some(4096));
+        assert!(!custom.reasoning);
+
+        // The previous generation is selectable, not reset to the default
+        assert_eq!(config.model_info().id, "MiniMax-M2.1");
+        assert_eq!(config.model_prices()["MiniMax-M2.5"], ModelPrice { input: 0.3, output: 1.2 });
+        assert_eq!(models[0].description(), "Pinned · 128k context");
+    }
 }
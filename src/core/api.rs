@@ -36,6 +36,8 @@ pub struct Usage {
     pub prompt_tokens: u64,
     pub completion_tokens: u64,
     pub total_tokens: u64,
+    /// Part of `completion_tokens` spent on reasoning, when reported.
+    pub reasoning_tokens: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -83,6 +85,10 @@ impl MiniMaxClient {
         }
     }
 
+    pub fn api_key(&self) -> &str {
+        &self.api_key
+    }
+
     fn headers(&self) -> HeaderMap {
         let mut headers = HeaderMap::new();
         headers.insert(
@@ -177,6 +183,7 @@ impl MiniMaxClient {
     pub async fn stream_chat(
         &self,
         model: &str,
+        max_tokens: Option<u64>,
         messages: &[Value],
         tools: Option<&[Value]>,
         event_tx: Option<mpsc::UnboundedSender<StreamEvent>>,
@@ -189,6 +196,9 @@ impl MiniMaxClient {
             "stream_options": { "include_usage": true },
             "temperature": 1.0,
         });
+        if let Some(max_tokens) = max_tokens {
+            body["max_tokens"] = serde_json::json!(max_tokens);
+        }
 
         if let Some(tools) = tools {
             if !tools.is_empty() {
@@ -587,6 +597,10 @@ fn process_chunk(
             .and_then(|v| v.as_u64())
             .unwrap_or(0);
         usage.total_tokens = u.get("total_tokens").and_then(|v| v.as_u64()).unwrap_or(0);
+        usage.reasoning_tokens = u
+            .pointer("/completion_tokens_details/reasoning_tokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
     }
 
     // API-level error
@@ -788,7 +802,8 @@ mod tests {
             "usage": {
                 "prompt_tokens": 100,
                 "completion_tokens": 50,
-                "total_tokens": 150
+                "total_tokens": 150,
+                "completion_tokens_details": { "reasoning_tokens": 30 }
             },
             "choices": [{"delta": {}}]
         });
@@ -803,6 +818,7 @@ mod tests {
         assert_eq!(usage.prompt_tokens, 100);
         assert_eq!(usage.completion_tokens, 50);
         assert_eq!(usage.total_tokens, 150);
+        assert_eq!(usage.reasoning_tokens, 30);
     }
 
     #[test]
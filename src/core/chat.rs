@@ -1,15 +1,20 @@
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::{Arc, Mutex};
 use tokio::sync::{mpsc, oneshot};
 use tokio_util::sync::CancellationToken;
 
-use crate::core::api::{AccumulatedToolCall, MiniMaxClient, StreamEvent};
+use crate::config::settings::{config_dir, ContextConfig, InstructionsConfig, ModelInfo};
+use crate::core::api::{AccumulatedToolCall, MiniMaxClient, StreamEvent, Usage};
+use crate::core::compaction::{self, summary_messages, Preserved};
+use crate::core::instructions::Instructions;
 use crate::core::mcp::McpManager;
 use crate::core::parser::{coerce_arg, parse_model_output};
-use crate::core::session::SessionStore;
+use crate::core::session::{SessionStore, StoredMessage, UsageStats};
+use crate::core::tokens::{self, TokenEstimator};
 use crate::core::Mode;
-use crate::tools;
+use crate::tools::{self, ToolResultMeta};
 
 // ── Agent Question types ─────────────────────────────────────────────────
 
@@ -77,6 +82,8 @@ pub enum ChatEvent {
         id: String,
         name: String,
         result: String,
+        is_error: bool,
+        meta: Option<ToolResultMeta>,
     },
     /// Error during streaming or tool execution.
     Error(String),
@@ -98,6 +105,12 @@ pub enum ChatEvent {
         original_tokens: usize,
         compressed_tokens: usize,
     },
+    /// Estimated prompt tokens of the request about to be sent.
+    ContextEstimate(usize),
+    /// A summary of the whole conversation is ready to start a new session.
+    HandoffReady { summary: String, summarized: usize },
+    /// The user message that started this turn was saved with this row id.
+    UserMessageSaved(i64),
 }
 
 /// The final assistant message after streaming completes.
@@ -120,11 +133,144 @@ pub struct ChatMessage {
     pub name: Option<String>,
 }
 
+// ── Stored message metadata ────────────────────────────────────────────
+
+/// UI state kept with a stored message (`StoredMessage::meta`) so a resumed
+/// session renders and continues like the live one.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageMeta {
+    /// The tool result was a failure.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_error: bool,
+    /// Diff data of edit/write tool results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<ToolResultMeta>,
+    /// Set on the marker saved where the context was compressed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<CompressionMarker>,
+}
+
+impl MessageMeta {
+    pub fn parse(json: Option<&str>) -> Self {
+        json.and_then(|j| serde_json::from_str(j).ok()).unwrap_or_default()
+    }
+}
+
+/// A context compression: the summary that replaced older history and how
+/// many recent history entries were kept after it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompressionMarker {
+    pub original_tokens: usize,
+    pub compressed_tokens: usize,
+    pub summarized: usize,
+    pub kept: usize,
+    pub summary: String,
+}
+
+/// Stored `name` of compression marker messages (role "system").
+pub const COMPRESSION_MARKER: &str = "context_compressed";
+
+/// Text shown where the context was compressed.
+pub fn compression_notice(original_tokens: usize, compressed_tokens: usize) -> String {
+    format!(
+        "Context compressed: ~{}K → ~{}K tokens",
+        original_tokens / 1000,
+        compressed_tokens / 1000
+    )
+}
+
+/// Text shown at the start of a session continued from a summary.
+pub fn handoff_notice(summarized: usize) -> String {
+    format!("Continued from a summary of {} earlier messages", summarized)
+}
+
+/// Most recent history entries kept verbatim by automatic compaction.
+const MESSAGES_TO_KEEP: usize = 20;
+
+/// Rebuild the engine history of a stored session: reasoning goes back into
+/// `reasoning_details` when the model takes it (`reasoning`), and compression
+/// markers are replayed so the model sees the same context it had before the
+/// session was closed.
+pub fn history_from_stored(messages: &[StoredMessage], reasoning: bool) -> Vec<Value> {
+    let mut history: Vec<Value> = Vec::new();
+    for m in messages {
+        if m.role == "system" {
+            if let Some(marker) = MessageMeta::parse(m.meta.as_deref()).compression {
+                let recent = history.split_off(history.len().saturating_sub(marker.kept));
+                history = summary_messages(marker.summarized, &marker.summary).to_vec();
+                history.extend(recent);
+            }
+            continue;
+        }
+        let mut entry = serde_json::json!({
+            "role": m.role,
+            "content": m.content
+        });
+        if let Some(tc) = &m.tool_calls {
+            if let Ok(parsed) = serde_json::from_str::<Value>(tc) {
+                entry["tool_calls"] = parsed;
+            }
+        }
+        if let Some(id) = &m.tool_call_id {
+            entry["tool_call_id"] = serde_json::json!(id);
+        }
+        if let Some(text) = m.reasoning.as_deref().filter(|r| reasoning && !r.is_empty()) {
+            entry["reasoning_details"] = serde_json::json!([{ "text": text }]);
+        }
+        history.push(entry);
+    }
+    history
+}
+
+/// Parse the `todos` argument of a `todo_write` call.
+pub fn parse_todo_items(args: &Value) -> Vec<TodoItem> {
+    args.get("todos")
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|item| {
+                    let content = item.get("content")?.as_str()?.to_string();
+                    let status = match item
+                        .get("status")
+                        .and_then(|s| s.as_str())
+                        .unwrap_or("pending")
+                    {
+                        "in_progress" => TodoStatus::InProgress,
+                        "completed" => TodoStatus::Completed,
+                        _ => TodoStatus::Pending,
+                    };
+                    Some(TodoItem { content, status })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The todo list as last written in a stored session.
+pub fn todo_items_from_stored(messages: &[StoredMessage]) -> Vec<TodoItem> {
+    messages
+        .iter()
+        .rev()
+        .filter(|m| m.role == "assistant")
+        .filter_map(|m| serde_json::from_str::<Vec<AccumulatedToolCall>>(m.tool_calls.as_deref()?).ok())
+        .find_map(|calls| {
+            calls
+                .into_iter()
+                .rev()
+                .find(|tc| tc.function.name == "todo_write")
+                .and_then(|tc| serde_json::from_str::<Value>(&tc.function.arguments).ok())
+        })
+        .map(|args| parse_todo_items(&args))
+        .unwrap_or_default()
+}
+
 // ── Chat Engine ─────────────────────────────────────────────────────────
 
 pub struct ChatEngine {
     client: MiniMaxClient,
-    model: String,
+    model: ModelInfo,
     mode: Mode,
     history: Vec<Value>,
     session_id: Option<String>,
@@ -133,13 +279,19 @@ pub struct ChatEngine {
     accumulated_completion_tokens: u64,
     cancel_token: CancellationToken,
     mcp_manager: Option<Arc<tokio::sync::Mutex<McpManager>>>,
+    instructions: InstructionsConfig,
+    /// Instruction files as added to the system prompt, loaded once per
+    /// request rather than on every prompt build.
+    instructions_prompt: String,
+    context_policy: ContextConfig,
+    estimator: TokenEstimator,
 }
 
 impl ChatEngine {
     pub fn new(client: MiniMaxClient, model: &str, mode: Mode) -> Self {
         Self {
             client,
-            model: model.to_string(),
+            model: ModelInfo::with_defaults(model),
             mode,
             history: Vec::new(),
             session_id: None,
@@ -148,6 +300,10 @@ impl ChatEngine {
             accumulated_completion_tokens: 0,
             cancel_token: CancellationToken::new(),
             mcp_manager: None,
+            instructions: InstructionsConfig::default(),
+            instructions_prompt: String::new(),
+            context_policy: ContextConfig::default(),
+            estimator: TokenEstimator::default(),
         }
     }
 
@@ -155,6 +311,21 @@ impl ChatEngine {
         self.mcp_manager = Some(manager);
     }
 
+    pub fn set_instructions(&mut self, config: InstructionsConfig) {
+        self.instructions = config;
+        self.reload_instructions();
+    }
+
+    /// Read the instruction files again: global, then project root down to cwd.
+    pub fn reload_instructions(&mut self) {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        self.instructions_prompt = Instructions::load(&cwd, &config_dir(), &self.instructions).to_prompt();
+    }
+
+    pub fn set_context_policy(&mut self, policy: ContextConfig) {
+        self.context_policy = policy;
+    }
+
     pub fn set_session(&mut self, session_id: String, store: Arc<SessionStore>) {
         self.session_id = Some(session_id);
         self.session_store = Some(store);
@@ -164,8 +335,13 @@ impl ChatEngine {
         self.mode = mode;
     }
 
-    pub fn set_model(&mut self, model: &str) {
-        self.model = model.to_string();
+    /// Switch models; the context window and output limit come with it.
+    pub fn set_model(&mut self, model: ModelInfo) {
+        // Another model may use another tokenizer
+        if model.id != self.model.id {
+            self.estimator = TokenEstimator::default();
+        }
+        self.model = model;
     }
 
     pub fn total_tokens(&self) -> u64 {
@@ -215,7 +391,8 @@ impl ChatEngine {
         let mode_section = match self.mode {
             Mode::Plan => "\n\n\
                 CURRENT MODE: READ-ONLY (PLAN)\n\
-                Available tools: read_file, glob, grep, list_directory, web_search (read-only), ask_user, todo_write.\n\
+                Available tools: read_file, glob, grep, list_directory, web_search (read-only), ask_user, todo_write, \
+                plus any MCP tools classified as read-only.\n\
                 You CANNOT write, edit, or run commands in this mode.\n\
                 Focus on: analysis, planning, explaining code, suggesting implementation strategies.\n\
                 IMPORTANT: Never tell the user to manually copy, paste, or create files themselves. \
@@ -235,17 +412,7 @@ impl ChatEngine {
                 .to_string(),
         };
 
-        let mut prompt = format!("{}{}", base, mode_section);
-
-        // Load agent.md if present
-        let agent_path = std::path::Path::new(&cwd).join("agent.md");
-        if agent_path.exists() {
-            if let Ok(agent_content) = std::fs::read_to_string(&agent_path) {
-                prompt = format!("{}\n\n--- agent.md ---\n{}", prompt, agent_content);
-            }
-        }
-
-        prompt
+        format!("{}{}{}", base, mode_section, self.instructions_prompt)
     }
 
     fn build_full_history(&self) -> Vec<Value> {
@@ -297,111 +464,173 @@ impl ChatEngine {
         messages
     }
 
-    /// Estimate the total number of tokens in the history using a chars/4 heuristic.
-    fn estimate_history_tokens(&self) -> usize {
-        self.history
-            .iter()
-            .map(|msg| {
-                let content_len = msg
-                    .get("content")
-                    .and_then(|c| c.as_str())
-                    .map(|s| s.len())
-                    .unwrap_or(0);
-                let reasoning_len = msg
-                    .get("reasoning_details")
-                    .and_then(|r| serde_json::to_string(r).ok())
-                    .map(|s| s.len())
-                    .unwrap_or(0);
-                let tool_args_len = msg
-                    .get("tool_calls")
-                    .and_then(|t| serde_json::to_string(t).ok())
-                    .map(|s| s.len())
-                    .unwrap_or(0);
-                (content_len + reasoning_len + tool_args_len) / 4
-            })
-            .sum()
+    /// Built-in tool definitions for the current mode, plus MCP tools.
+    fn tool_definitions(&self) -> Vec<Value> {
+        let mut tool_defs = tools::get_tool_definitions(self.mode);
+        if let Some(mcp) = &self.mcp_manager {
+            if let Ok(manager) = mcp.try_lock() {
+                tool_defs.extend(manager.get_tool_definitions(self.mode));
+            }
+        }
+        tool_defs
     }
 
-    /// Compress old history by summarizing it via an API call when estimated
-    /// tokens exceed the threshold.  Recent messages are preserved intact.
-    async fn compress_history(
-        &mut self,
-        event_tx: &mpsc::UnboundedSender<ChatEvent>,
-    ) -> Result<()> {
-        const COMPRESSION_THRESHOLD: usize = 100_000;
-        const MESSAGES_TO_KEEP: usize = 20;
+    /// Estimated prompt tokens of the next request: system prompt, history
+    /// and tool definitions.
+    pub fn context_tokens(&self) -> usize {
+        self.estimator
+            .estimate_request(&self.build_full_history(), &self.tool_definitions())
+    }
 
-        let estimated = self.estimate_history_tokens();
-        if estimated < COMPRESSION_THRESHOLD {
+    /// Compact history before a request once it fills `compact_at` of the
+    /// model's context window. Without automatic compaction, history is
+    /// still compacted when the request and its reply would not fit at all.
+    async fn auto_compact(&mut self, event_tx: &mpsc::UnboundedSender<ChatEvent>) -> Result<()> {
+        let window = self.model.context_window;
+        let budget = window.saturating_sub(self.model.max_output_tokens.unwrap_or(0)) as usize;
+        let threshold = if self.context_policy.auto_compact {
+            ((window as f64 * self.context_policy.compact_at) as usize).min(budget)
+        } else {
+            budget
+        };
+        if self.context_tokens() < threshold {
             return Ok(());
         }
+        self.compact_keeping(MESSAGES_TO_KEEP, None, event_tx).await?;
+        Ok(())
+    }
 
-        let keep_count = MESSAGES_TO_KEEP.min(self.history.len());
-        let split_point = self.history.len().saturating_sub(keep_count);
-        if split_point == 0 {
-            return Ok(());
-        }
+    /// Summarize older history now, keeping the recent half (at most
+    /// `MESSAGES_TO_KEEP` messages) intact. `focus` tells the summary what to
+    /// concentrate on. Returns false when there is nothing to summarize.
+    pub async fn compact(
+        &mut self,
+        focus: Option<&str>,
+        event_tx: &mpsc::UnboundedSender<ChatEvent>,
+    ) -> Result<bool> {
+        let keep = MESSAGES_TO_KEEP.min(self.history.len() / 2);
+        self.compact_keeping(keep, focus, event_tx).await
+    }
 
-        // Build a condensed version of old messages for the summarization prompt
-        let old_messages: Vec<String> = self.history[..split_point]
-            .iter()
-            .map(|m| {
-                let role = m.get("role").and_then(|r| r.as_str()).unwrap_or("?");
-                let content: String = m
-                    .get("content")
-                    .and_then(|c| c.as_str())
-                    .unwrap_or("")
-                    .chars()
-                    .take(1000)
-                    .collect();
-                format!("[{}]: {}", role, content)
-            })
-            .collect();
-
-        let summary_prompt = format!(
-            "Summarize this coding assistant conversation history. This summary will replace the original messages \
-             in the conversation context, so it must preserve all essential information needed to continue working.\n\n\
-             MUST preserve:\n\
-             - All file paths that were read, created, or modified\n\
-             - Key decisions made and their reasoning\n\
-             - Current task state and what remains to be done\n\
-             - Important code patterns, bugs found, or errors encountered\n\
-             - User preferences or constraints mentioned\n\
-             - Any architectural or design decisions\n\n\
-             Conversation:\n{}",
-            old_messages.join("\n")
-        );
+    /// Replace older history with a summary, keeping about `keep_count`
+    /// recent entries intact. The cut falls on a turn boundary, a summary
+    /// from an earlier compaction is updated rather than summarized again,
+    /// and if the model cannot write the summary the older messages are
+    /// truncated instead.
+    async fn compact_keeping(
+        &mut self,
+        keep_count: usize,
+        focus: Option<&str>,
+        event_tx: &mpsc::UnboundedSender<ChatEvent>,
+    ) -> Result<bool> {
+        let previous = compaction::previous_summary(&self.history);
+        let first_new = if previous.is_some() { 2 } else { 0 };
+        let split_point = compaction::cut_point(&self.history, keep_count);
+        if split_point <= first_new {
+            return Ok(false);
+        }
 
-        // Use the user's configured model for the summary
-        let summary = self
-            .client
-            .simple_completion(&self.model, &summary_prompt)
-            .await?;
+        let estimated = self.context_tokens();
+        let (previously, previous) = previous.unzip();
+        let summarized = previously.unwrap_or(0) + split_point - first_new;
+        let old = &self.history[first_new..split_point];
+        let preserved = Preserved::collect(old, &self.history[split_point..], previous.as_deref());
+        let summary = match self.summarize(old, previous.as_deref(), focus).await {
+            Ok(summary) => summary,
+            Err(e) => {
+                let _ = event_tx.send(ChatEvent::Error(format!(
+                    "Summarization failed, older messages were truncated instead: {}",
+                    e
+                )));
+                compaction::fallback_summary(old, previous.as_deref(), &e.to_string())
+            }
+        };
+        let summary = preserved.append_to(&summary);
 
         // Replace old messages with the summary
         let recent = self.history.split_off(split_point);
-        self.history.clear();
-        self.history.push(serde_json::json!({
-            "role": "user",
-            "content": format!(
-                "[CONVERSATION CONTEXT — Summary of {} earlier messages]\n\
-                 The following is a summary of our conversation so far. \
-                 All files, decisions, and progress described below are real and should be treated as established context.\n\n\
-                 {}", split_point, summary)
-        }));
-        self.history.push(serde_json::json!({
-            "role": "assistant",
-            "content": "Understood. I have full context from our previous conversation and will continue seamlessly from where we left off."
-        }));
+        let kept = recent.len();
+        self.history = summary_messages(summarized, &summary).to_vec();
         self.history.extend(recent);
 
-        let compressed = self.estimate_history_tokens();
+        let compressed = self.context_tokens();
+        self.persist_compression(&compression_notice(estimated, compressed), CompressionMarker {
+            original_tokens: estimated,
+            compressed_tokens: compressed,
+            summarized,
+            kept,
+            summary,
+        });
         let _ = event_tx.send(ChatEvent::ContextCompressed {
             original_tokens: estimated,
             compressed_tokens: compressed,
         });
 
-        Ok(())
+        Ok(true)
+    }
+
+    /// Ask the model for a summary of `messages` that can stand in for them,
+    /// updating `previous` when the history was compacted before.
+    async fn summarize(
+        &self,
+        messages: &[Value],
+        previous: Option<&str>,
+        focus: Option<&str>,
+    ) -> Result<String> {
+        let prompt = compaction::summary_prompt(messages, previous, focus);
+        let model = self.context_policy.compact_model.as_deref().unwrap_or(&self.model.id);
+        self.client.simple_completion(model, &prompt).await
+    }
+
+    /// Summary of the whole conversation, to continue in a new session,
+    /// with the number of messages it covers.
+    pub async fn summarize_for_handoff(&self, focus: Option<&str>) -> Result<(String, usize)> {
+        let previous = compaction::previous_summary(&self.history);
+        let first_new = if previous.is_some() { 2 } else { 0 };
+        if self.history.len() <= first_new {
+            return Err(anyhow::anyhow!("nothing to summarize yet"));
+        }
+        let (previously, previous) = previous.unzip();
+        let messages = &self.history[first_new..];
+        let summary = self.summarize(messages, previous.as_deref(), focus).await?;
+        let preserved = Preserved::collect(messages, &[], previous.as_deref());
+        Ok((
+            preserved.append_to(&summary),
+            previously.unwrap_or(0) + messages.len(),
+        ))
+    }
+
+    /// Start the current (new) session from a summary of `summarized`
+    /// messages of another one.
+    pub fn start_from_summary(&mut self, summary: String, summarized: usize) {
+        self.clear();
+        self.history = summary_messages(summarized, &summary).to_vec();
+        let compressed = self.context_tokens();
+        self.persist_compression(&handoff_notice(summarized), CompressionMarker {
+            original_tokens: 0,
+            compressed_tokens: compressed,
+            summarized,
+            kept: 0,
+            summary,
+        });
+    }
+
+    /// Store a compression marker so a resumed session replays it.
+    fn persist_compression(&self, notice: &str, marker: CompressionMarker) {
+        let id = self.persist_message(
+            "system",
+            notice,
+            None,
+            None,
+            Some(COMPRESSION_MARKER),
+        );
+        self.persist_meta(
+            id,
+            &MessageMeta {
+                compression: Some(marker),
+                ..Default::default()
+            },
+        );
     }
 
     /// Send a user message and run the agentic loop.
@@ -417,6 +646,8 @@ impl ChatEngine {
         file_context: Option<&str>,
         event_tx: mpsc::UnboundedSender<ChatEvent>,
     ) -> Result<()> {
+        // Pick up instruction files edited since the last request
+        self.reload_instructions();
 
         // Build API content with file context if present
         let api_content = match file_context {
@@ -431,7 +662,9 @@ impl ChatEngine {
         }));
 
         // Persist user message
-        self.persist_message("user", user_input, None, None, None);
+        if let Some(id) = self.persist_message("user", user_input, None, None, None) {
+            let _ = event_tx.send(ChatEvent::UserMessageSaved(id));
+        }
 
         // Agentic loop
         loop {
@@ -441,22 +674,18 @@ impl ChatEngine {
 
             let _ = event_tx.send(ChatEvent::StreamStart);
 
-            // Compress history if approaching context window limit
-            if let Err(e) = self.compress_history(&event_tx).await {
+            // Compact history if approaching the context window limit
+            if let Err(e) = self.auto_compact(&event_tx).await {
                 let _ = event_tx.send(ChatEvent::Error(format!(
                     "Context compression failed (non-fatal): {}",
                     e
                 )));
             }
 
-            let mut tool_defs = tools::get_tool_definitions(self.mode);
-            // Append MCP tool definitions if available
-            if let Some(mcp) = &self.mcp_manager {
-                if let Ok(manager) = mcp.try_lock() {
-                    tool_defs.extend(manager.get_tool_definitions());
-                }
-            }
+            let tool_defs = self.tool_definitions();
             let full_history = self.build_full_history();
+            let raw_tokens = tokens::raw_request_tokens(&full_history, &tool_defs);
+            let _ = event_tx.send(ChatEvent::ContextEstimate(self.estimator.scale(raw_tokens)));
 
             // Create a channel for stream events
             let (stream_tx, mut stream_rx) = mpsc::unbounded_channel::<StreamEvent>();
@@ -480,10 +709,12 @@ impl ChatEngine {
                 }
             });
 
+            let started = std::time::Instant::now();
             let result = self
                 .client
                 .stream_chat(
-                    &self.model,
+                    &self.model.id,
+                    self.model.max_output_tokens,
                     &full_history,
                     Some(&tool_defs),
                     Some(stream_tx),
@@ -507,6 +738,7 @@ impl ChatEngine {
             // completion_tokens are new tokens generated per request, so we accumulate those.
             self.accumulated_completion_tokens += result.usage.completion_tokens;
             self.total_tokens = result.usage.prompt_tokens + self.accumulated_completion_tokens;
+            self.estimator.calibrate(raw_tokens, result.usage.prompt_tokens);
 
             // Parse content for XML tool calls (fallback)
             let parsed = parse_model_output(&result.content);
@@ -579,7 +811,8 @@ impl ChatEngine {
                 "role": "assistant",
                 "content": result.content
             });
-            if !result.reasoning_details.is_empty() {
+            // Reasoning is passed back only to models that use it
+            if self.model.reasoning && !result.reasoning_details.is_empty() {
                 hist_entry["reasoning_details"] = serde_json::json!(
                     result.reasoning_details.iter().map(|t| serde_json::json!({"text": t})).collect::<Vec<_>>()
                 );
@@ -613,13 +846,19 @@ impl ChatEngine {
             } else {
                 Some(serde_json::to_string(&sanitized_tool_calls).unwrap_or_default())
             };
-            self.persist_message(
+            let message_id = self.persist_message(
                 "assistant",
                 &final_content,
                 tool_calls_json.as_deref(),
                 None,
                 None,
             );
+            if let (Some(id), Some(store)) = (message_id, &self.session_store) {
+                if !result.reasoning_details.is_empty() {
+                    let _ = store.set_message_reasoning(id, &result.reasoning_details.join(""));
+                }
+            }
+            self.record_usage(message_id, &result.usage, started.elapsed());
 
             // Execute tool calls if any
             if !final_tool_calls.is_empty() {
@@ -647,28 +886,7 @@ impl ChatEngine {
                 for (i, tc) in final_tool_calls.iter().enumerate() {
                     if tc.function.name == "todo_write" {
                         // Handle todo_write: parse items and send update to UI
-                        let args = &parsed_args[i];
-                        let items: Vec<TodoItem> = args
-                            .get("todos")
-                            .and_then(|v| v.as_array())
-                            .map(|arr| {
-                                arr.iter()
-                                    .filter_map(|item| {
-                                        let content = item.get("content")?.as_str()?.to_string();
-                                        let status = match item
-                                            .get("status")
-                                            .and_then(|s| s.as_str())
-                                            .unwrap_or("pending")
-                                        {
-                                            "in_progress" => TodoStatus::InProgress,
-                                            "completed" => TodoStatus::Completed,
-                                            _ => TodoStatus::Pending,
-                                        };
-                                        Some(TodoItem { content, status })
-                                    })
-                                    .collect()
-                            })
-                            .unwrap_or_default();
+                        let items = parse_todo_items(&parsed_args[i]);
 
                         let item_count = items.len();
                         let completed = items
@@ -693,6 +911,8 @@ impl ChatEngine {
                             id: tc.id.clone(),
                             name: "todo_write".to_string(),
                             result: result_msg.clone(),
+                            is_error: false,
+                            meta: None,
                         });
 
                         results[i] = Some((
@@ -778,6 +998,8 @@ impl ChatEngine {
                             id: tc.id.clone(),
                             name: "ask_user".to_string(),
                             result: user_answer.clone(),
+                            is_error: false,
+                            meta: None,
                         });
 
                         // Format result: single question uses simple format, multi uses structured
@@ -813,6 +1035,7 @@ impl ChatEngine {
                     let mcp = self.mcp_manager.clone();
                     let tx = event_tx.clone();
                     let cancel = self.cancel_token.clone();
+                    let api_key = self.client.api_key().to_string();
 
                     handles.push((idx, tokio::spawn(async move {
                         if cancel.is_cancelled() {
@@ -821,6 +1044,8 @@ impl ChatEngine {
                                 id: id.clone(),
                                 name: name.clone(),
                                 result: result.result.clone(),
+                                is_error: false,
+                                meta: None,
                             });
                             return (id, name, result);
                         }
@@ -828,17 +1053,19 @@ impl ChatEngine {
                         let result = if name.starts_with("mcp__") {
                             if let Some(mcp) = mcp {
                                 let manager = mcp.lock().await;
-                                match manager.call_tool(&name, args).await {
-                                    Ok(result) => tools::ToolExecutionResult::text(result),
-                                    Err(e) => tools::ToolExecutionResult::text(
+                                match manager.call_tool(&name, args, mode).await {
+                                    Ok(result) => result,
+                                    Err(e) => tools::ToolExecutionResult::error(
                                         format!("Error: MCP tool failed: {}", e),
                                     ),
                                 }
                             } else {
-                                tools::ToolExecutionResult::text(
+                                tools::ToolExecutionResult::error(
                                     format!("Error: MCP tool \"{}\" called but no MCP manager available", name),
                                 )
                             }
+                        } else if name == "web_search" {
+                            tools::web_search::execute(args, &api_key).await
                         } else {
                             tools::execute_tool(&name, args, mode).await
                         };
@@ -848,6 +1075,8 @@ impl ChatEngine {
                             id: id.clone(),
                             name: name.clone(),
                             result: result.result.clone(),
+                            is_error: result.is_error,
+                            meta: result.meta.clone(),
                         });
 
                         (id, name, result)
@@ -859,7 +1088,7 @@ impl ChatEngine {
                     let (id, name, result) = match handle.await {
                         Ok(r) => r,
                         Err(e) => {
-                            let err_result = tools::ToolExecutionResult::text(format!("Error: {}", e));
+                            let err_result = tools::ToolExecutionResult::error(format!("Error: {}", e));
                             (String::new(), String::new(), err_result)
                         }
                     };
@@ -894,13 +1123,21 @@ impl ChatEngine {
                 // Persist all tool messages
                 for (i, tc) in final_tool_calls.iter().enumerate() {
                     if let Some((_, _, result)) = ordered_results.get(i) {
-                        self.persist_message(
+                        let id = self.persist_message(
                             "tool",
                             &result.result,
                             None,
                             Some(&tc.id),
                             Some(&tc.function.name),
                         );
+                        self.persist_meta(
+                            id,
+                            &MessageMeta {
+                                is_error: result.is_error || result.result.starts_with("Error:"),
+                                tool: result.meta.clone(),
+                                ..Default::default()
+                            },
+                        );
                     }
                 }
 
@@ -920,6 +1157,39 @@ impl ChatEngine {
         Ok(())
     }
 
+    /// Attach UI metadata to a persisted message, if there is any to keep.
+    fn persist_meta(&self, message_id: Option<i64>, meta: &MessageMeta) {
+        if *meta == MessageMeta::default() {
+            return;
+        }
+        if let (Some(id), Some(store), Ok(json)) =
+            (message_id, &self.session_store, serde_json::to_string(meta))
+        {
+            let _ = store.set_message_meta(id, &json);
+        }
+    }
+
+    /// Store the token usage and latency of one request with the session.
+    fn record_usage(&self, message_id: Option<i64>, usage: &Usage, latency: std::time::Duration) {
+        if usage.prompt_tokens == 0 && usage.completion_tokens == 0 {
+            return;
+        }
+        if let (Some(session_id), Some(store)) = (&self.session_id, &self.session_store) {
+            let _ = store.record_usage(
+                session_id,
+                message_id,
+                &UsageStats {
+                    model: self.model.id.clone(),
+                    requests: 1,
+                    prompt_tokens: usage.prompt_tokens,
+                    completion_tokens: usage.completion_tokens,
+                    reasoning_tokens: usage.reasoning_tokens,
+                    latency_ms: latency.as_millis() as u64,
+                },
+            );
+        }
+    }
+
     fn persist_message(
         &self,
         role: &str,
@@ -927,9 +1197,13 @@ impl ChatEngine {
         tool_calls: Option<&str>,
         tool_call_id: Option<&str>,
         name: Option<&str>,
-    ) {
+    ) -> Option<i64> {
         if let (Some(session_id), Some(store)) = (&self.session_id, &self.session_store) {
-            let _ = store.save_message(session_id, role, content, tool_calls, tool_call_id, name);
+            store
+                .save_message(session_id, role, content, tool_calls, tool_call_id, name)
+                .ok()
+        } else {
+            None
         }
     }
 }
@@ -976,6 +1250,15 @@ mod tests {
         assert_eq!(engine.total_tokens, 0);
     }
 
+    #[test]
+    fn system_prompt_uses_loaded_instructions() {
+        let client = MiniMaxClient::new("test");
+        let mut engine = ChatEngine::new(client, "MiniMax-M2.5", Mode::Builder);
+        engine.instructions_prompt = "\n\nPROJECT RULES: use tabs".to_string();
+        assert!(engine.get_system_prompt().ends_with("PROJECT RULES: use tabs"));
+        assert_eq!(engine.get_system_prompt(), engine.get_system_prompt());
+    }
+
     #[test]
     fn build_full_history_includes_system() {
         let client = MiniMaxClient::new("test");
@@ -991,21 +1274,34 @@ mod tests {
     }
 
     #[test]
-    fn estimate_tokens_basic() {
+    fn context_tokens_cover_system_prompt_and_tools() {
         let client = MiniMaxClient::new("test");
         let mut engine = ChatEngine::new(client, "MiniMax-M2.5", Mode::Builder);
-        // 400 chars ≈ 100 tokens
+        let empty = engine.context_tokens();
+        let prompt_and_tools = tokens::raw_request_tokens(&engine.build_full_history(), &engine.tool_definitions());
+        assert!(empty > 0);
+        assert_eq!(empty, prompt_and_tools);
+
+        let content = "a".repeat(400);
         engine
             .history
-            .push(serde_json::json!({"role": "user", "content": "a".repeat(400)}));
-        assert_eq!(engine.estimate_history_tokens(), 100);
+            .push(serde_json::json!({"role": "user", "content": content}));
+        // Message overhead, the role and the content
+        let added = 4 + tokens::count_tokens("user") + tokens::count_tokens(&content);
+        assert_eq!(engine.context_tokens(), empty + added);
     }
 
     #[test]
-    fn estimate_tokens_empty_history() {
+    fn context_tokens_follow_calibration() {
         let client = MiniMaxClient::new("test");
-        let engine = ChatEngine::new(client, "MiniMax-M2.5", Mode::Builder);
-        assert_eq!(engine.estimate_history_tokens(), 0);
+        let mut engine = ChatEngine::new(client, "MiniMax-M2.5", Mode::Builder);
+        let raw = engine.context_tokens();
+        engine.estimator.calibrate(raw, raw as u64 * 2);
+        assert_eq!(engine.context_tokens(), raw * 2);
+
+        // A different model starts uncalibrated
+        engine.set_model(ModelInfo::with_defaults("other-model"));
+        assert_eq!(engine.context_tokens(), raw);
     }
 
     #[test]
@@ -1077,4 +1373,99 @@ mod tests {
         let tool_content = full[1]["content"].as_str().unwrap();
         assert_eq!(tool_content.len(), 5000);
     }
+
+    fn stored(role: &str, content: &str) -> StoredMessage {
+        StoredMessage {
+            id: 0,
+            session_id: "s".to_string(),
+            role: role.to_string(),
+            content: content.to_string(),
+            tool_calls: None,
+            tool_call_id: None,
+            name: None,
+            reasoning: None,
+            meta: None,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn history_from_stored_replays_compression() {
+        let marker = MessageMeta {
+            compression: Some(CompressionMarker {
+                original_tokens: 90_000,
+                compressed_tokens: 5_000,
+                summarized: 2,
+                kept: 1,
+                summary: "Earlier work".to_string(),
+            }),
+            ..Default::default()
+        };
+        let mut answer = stored("assistant", "second answer");
+        answer.reasoning = Some("thinking".to_string());
+        let messages = vec![
+            stored("user", "first"),
+            stored("assistant", "first answer"),
+            stored("user", "second"),
+            StoredMessage {
+                name: Some(COMPRESSION_MARKER.to_string()),
+                meta: serde_json::to_string(&marker).ok(),
+                ..stored("system", &compression_notice(90_000, 5_000))
+            },
+            answer,
+        ];
+
+        let history = history_from_stored(&messages, true);
+        assert_eq!(history.len(), 4);
+        assert!(history[0]["content"].as_str().unwrap().contains("Earlier work"));
+        assert_eq!(history[2]["content"], "second");
+        assert_eq!(history[3]["reasoning_details"][0]["text"], "thinking");
+    }
+
+    #[test]
+    fn history_from_stored_drops_reasoning_for_models_without_it() {
+        let mut answer = stored("assistant", "answer");
+        answer.reasoning = Some("thinking".to_string());
+        let history = history_from_stored(&[stored("user", "question"), answer], false);
+        assert_eq!(history[1]["content"], "answer");
+        assert!(history[1].get("reasoning_details").is_none());
+    }
+
+    #[test]
+    fn todo_items_restore_last_write() {
+        let call = |todos: &str| {
+            let args = serde_json::json!({ "todos": serde_json::from_str::<Value>(todos).unwrap() });
+            let mut m = stored("assistant", "");
+            m.tool_calls = Some(
+                serde_json::json!([{
+                    "id": "tc",
+                    "type": "function",
+                    "function": { "name": "todo_write", "arguments": args.to_string() }
+                }])
+                .to_string(),
+            );
+            m
+        };
+        let messages = vec![
+            call(r#"[{"content": "old", "status": "pending"}]"#),
+            stored("tool", "ok"),
+            call(r#"[{"content": "read", "status": "completed"}, {"content": "fix", "status": "in_progress"}]"#),
+        ];
+
+        let items = todo_items_from_stored(&messages);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].content, "read");
+        assert_eq!(items[0].status, TodoStatus::Completed);
+        assert_eq!(items[1].status, TodoStatus::InProgress);
+        assert!(todo_items_from_stored(&messages[..1]).iter().all(|i| i.content == "old"));
+    }
+
+    #[test]
+    fn message_meta_skips_empty_fields() {
+        let meta = MessageMeta { is_error: true, ..Default::default() };
+        let json = serde_json::to_string(&meta).unwrap();
+        assert_eq!(json, r#"{"isError":true}"#);
+        assert_eq!(MessageMeta::parse(Some(&json)), meta);
+        assert_eq!(MessageMeta::parse(None), MessageMeta::default());
+    }
 }
@@ -1,5 +1,6 @@
-use crate::config::settings::MODEL_IDS;
+use crate::config::settings::ModelInfo;
 use crate::config::themes::THEMES;
+use crate::core::export::ExportFormat;
 use std::fs;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -9,13 +10,47 @@ pub enum CommandResult {
     Clear,
     Exit,
     Sessions,
+    Search(String),
+    Export {
+        format: ExportFormat,
+        include_reasoning: bool,
+        path: Option<String>,
+    },
+    Import(String),
+    /// Fork the session; `Some(n)` branches before the n-th user message (0-based).
+    Fork(Option<usize>),
+    Usage,
+    Context,
+    /// Allow the checked-in project config's MCP servers to start.
+    Trust,
+    /// Summarize older history now, optionally focused on the given text.
+    Compact(Option<String>),
+    /// Offer to continue in a new session that starts from a summary.
+    Handoff(Option<String>),
+    /// Open a file in the external editor: `path[:line]`, or the file the
+    /// agent edited last.
+    Open(Option<String>),
+    /// Copy the last reply, or the last tool output, to the clipboard.
+    Copy(CopyTarget),
+    /// Copy code block n (1-based, as numbered in the chat) to the
+    /// clipboard; the last one when `None`.
+    Yank(Option<usize>),
     Config,
     SetModel(String),
     SetTheme(String),
     None,
 }
 
-pub fn handle_command(input: &str) -> CommandResult {
+/// What `/copy` copies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CopyTarget {
+    Reply,
+    ToolOutput,
+}
+
+/// Parse a slash command. `/model` is resolved against `models`, the
+/// configured model registry.
+pub fn handle_command(input: &str, models: &[ModelInfo]) -> CommandResult {
     let trimmed = input.trim();
     if !trimmed.starts_with('/') {
         return CommandResult::None;
@@ -34,29 +69,72 @@ pub fn handle_command(input: &str) -> CommandResult {
         "/clear" => CommandResult::Clear,
         "/exit" | "/quit" => CommandResult::Exit,
         "/sessions" => CommandResult::Sessions,
-        "/config" => CommandResult::Config,
+        "/search" => CommandResult::Search(arg),
+        "/usage" => CommandResult::Usage,
+        "/context" => CommandResult::Context,
+        "/trust" => CommandResult::Trust,
+        "/compact" => CommandResult::Compact(Some(arg).filter(|a| !a.is_empty())),
+        "/handoff" => CommandResult::Handoff(Some(arg).filter(|a| !a.is_empty())),
+        "/open" => CommandResult::Open(Some(arg).filter(|a| !a.is_empty())),
+        "/copy" => match arg.as_str() {
+            "" => CommandResult::Copy(CopyTarget::Reply),
+            "tool" => CommandResult::Copy(CopyTarget::ToolOutput),
+            _ => CommandResult::Message(
+                "Usage: /copy [tool] — copy the last reply, or the last tool output".to_string(),
+            ),
+        },
+        "/yank" => {
+            if arg.is_empty() {
+                return CommandResult::Yank(None);
+            }
+            match arg.parse::<usize>() {
+                Ok(n) if n > 0 => CommandResult::Yank(Some(n)),
+                _ => CommandResult::Message(
+                    "Usage: /yank [n] — copy code block n, or the last code block".to_string(),
+                ),
+            }
+        }
 
-        "/model" => {
+        "/export" => {
+            let mut format = ExportFormat::Markdown;
+            let mut include_reasoning = false;
+            let mut path = None;
+            for part in &parts[1..] {
+                if *part == "--reasoning" {
+                    include_reasoning = true;
+                } else if let Some(f) = ExportFormat::parse(part) {
+                    format = f;
+                } else {
+                    path = Some(part.to_string());
+                }
+            }
+            CommandResult::Export { format, include_reasoning, path }
+        }
+
+        "/fork" => {
             if arg.is_empty() {
-                let list: Vec<String> = MODEL_IDS.iter().map(|m| format!("  - {}", m)).collect();
-                return CommandResult::Message(format!(
-                    "Available models:\n{}\n\nUsage: /model <name>",
-                    list.join("\n")
-                ));
+                return CommandResult::Fork(None);
             }
-            let matched = MODEL_IDS
-                .iter()
-                .find(|m| m.eq_ignore_ascii_case(&arg));
-            match matched {
-                Some(model) => CommandResult::SetModel(model.to_string()),
-                None => CommandResult::Message(format!(
-                    "Unknown model \"{}\". Available: {}",
-                    arg,
-                    MODEL_IDS.join(", ")
-                )),
+            match arg.parse::<usize>() {
+                Ok(n) if n > 0 => CommandResult::Fork(Some(n - 1)),
+                _ => CommandResult::Message(
+                    "Usage: /fork [n] — copy this session, or branch before your n-th message".to_string(),
+                ),
             }
         }
 
+        "/import" => {
+            if arg.is_empty() {
+                return CommandResult::Message(
+                    "Usage: /import <file.json>".to_string(),
+                );
+            }
+            CommandResult::Import(arg)
+        }
+        "/config" => CommandResult::Config,
+
+        "/model" => model_command(&arg, models),
+
         "/theme" => {
             if arg.is_empty() {
                 let list: Vec<String> = THEMES.keys().map(|t| format!("  - {}", t)).collect();
@@ -111,6 +189,18 @@ pub fn handle_command(input: &str) -> CommandResult {
             "Available commands:\n\
               /new        - Start a new session\n\
               /sessions   - Browse previous sessions\n\
+              /search     - Search all sessions (/search <query>)\n\
+              /export     - Export this session (/export [md|json] [--reasoning] [path])\n\
+              /import     - Import a session from a JSON export\n\
+              /fork       - Fork this session (/fork <n> branches before your n-th message)\n\
+              /usage      - Token usage and cost: this session, today, last 30 days\n\
+              /context    - Show which instruction files are loaded\n\
+              /trust      - Allow this project's checked-in MCP servers to start\n\
+              /compact    - Summarize older messages now (/compact [focus])\n\
+              /handoff    - Continue in a new session from a summary (/handoff [focus])\n\
+              /open       - Open the last edited file in $EDITOR (/open <path[:line]>)\n\
+              /copy       - Copy the last reply (/copy tool copies the last tool output)\n\
+              /yank       - Copy code block n as numbered in the chat (/yank <n>)\n\
               /config     - Open configuration (API key, theme, model)\n\
               /model      - Change or list models\n\
               /theme      - Change or list themes\n\
@@ -128,57 +218,175 @@ pub fn handle_command(input: &str) -> CommandResult {
     }
 }
 
+/// `/model [name]` against `models`: list them, or select one by id,
+/// ignoring case.
+fn model_command(arg: &str, models: &[ModelInfo]) -> CommandResult {
+    if arg.is_empty() {
+        let list: Vec<String> = models
+            .iter()
+            .map(|m| format!("  - {} ({})", m.id, m.description()))
+            .collect();
+        return CommandResult::Message(format!(
+            "Available models:\n{}\n\nUsage: /model <name>",
+            list.join("\n")
+        ));
+    }
+    let matched = models.iter().find(|m| m.id.eq_ignore_ascii_case(arg));
+    match matched {
+        Some(model) => CommandResult::SetModel(model.id.clone()),
+        None => {
+            let ids: Vec<&str> = models.iter().map(|m| m.id.as_str()).collect();
+            CommandResult::Message(format!(
+                "Unknown model \"{}\". Available: {}",
+                arg,
+                ids.join(", ")
+            ))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::settings::builtin_models;
 
     #[test]
     fn non_command_returns_none() {
-        assert_eq!(handle_command("hello"), CommandResult::None);
-        assert_eq!(handle_command("not a /command"), CommandResult::None);
+        assert_eq!(handle_command("hello", &builtin_models()), CommandResult::None);
+        assert_eq!(handle_command("not a /command", &builtin_models()), CommandResult::None);
     }
 
     #[test]
     fn basic_commands() {
-        assert_eq!(handle_command("/new"), CommandResult::NewSession);
-        assert_eq!(handle_command("/clear"), CommandResult::Clear);
-        assert_eq!(handle_command("/exit"), CommandResult::Exit);
-        assert_eq!(handle_command("/quit"), CommandResult::Exit);
-        assert_eq!(handle_command("/sessions"), CommandResult::Sessions);
-        assert_eq!(handle_command("/config"), CommandResult::Config);
+        assert_eq!(handle_command("/new", &builtin_models()), CommandResult::NewSession);
+        assert_eq!(handle_command("/clear", &builtin_models()), CommandResult::Clear);
+        assert_eq!(handle_command("/exit", &builtin_models()), CommandResult::Exit);
+        assert_eq!(handle_command("/quit", &builtin_models()), CommandResult::Exit);
+        assert_eq!(handle_command("/sessions", &builtin_models()), CommandResult::Sessions);
+        assert_eq!(handle_command("/config", &builtin_models()), CommandResult::Config);
+        assert_eq!(handle_command("/usage", &builtin_models()), CommandResult::Usage);
+        assert_eq!(handle_command("/context", &builtin_models()), CommandResult::Context);
+        assert_eq!(handle_command("/trust", &builtin_models()), CommandResult::Trust);
+    }
+
+    #[test]
+    fn compact_and_handoff_commands() {
+        assert_eq!(handle_command("/compact", &builtin_models()), CommandResult::Compact(None));
+        assert_eq!(
+            handle_command("/compact keep the API design", &builtin_models()),
+            CommandResult::Compact(Some("keep the API design".to_string()))
+        );
+        assert_eq!(handle_command("/handoff", &builtin_models()), CommandResult::Handoff(None));
+        assert_eq!(handle_command("/open", &builtin_models()), CommandResult::Open(None));
+        assert_eq!(
+            handle_command("/open src/main.rs:12", &builtin_models()),
+            CommandResult::Open(Some("src/main.rs:12".to_string()))
+        );
+        assert_eq!(
+            handle_command("/handoff the failing test", &builtin_models()),
+            CommandResult::Handoff(Some("the failing test".to_string()))
+        );
+    }
+
+    #[test]
+    fn copy_and_yank_commands() {
+        assert_eq!(handle_command("/copy", &builtin_models()), CommandResult::Copy(CopyTarget::Reply));
+        assert_eq!(handle_command("/copy tool", &builtin_models()), CommandResult::Copy(CopyTarget::ToolOutput));
+        assert!(matches!(handle_command("/copy all", &builtin_models()), CommandResult::Message(_)));
+        assert_eq!(handle_command("/yank", &builtin_models()), CommandResult::Yank(None));
+        assert_eq!(handle_command("/yank 2", &builtin_models()), CommandResult::Yank(Some(2)));
+        assert!(matches!(handle_command("/yank 0", &builtin_models()), CommandResult::Message(_)));
+    }
+
+    #[test]
+    fn export_command() {
+        assert_eq!(
+            handle_command("/export", &builtin_models()),
+            CommandResult::Export { format: ExportFormat::Markdown, include_reasoning: false, path: None }
+        );
+        assert_eq!(
+            handle_command("/export json out/chat.json", &builtin_models()),
+            CommandResult::Export {
+                format: ExportFormat::Json,
+                include_reasoning: false,
+                path: Some("out/chat.json".to_string()),
+            }
+        );
+        assert_eq!(
+            handle_command("/export --reasoning", &builtin_models()),
+            CommandResult::Export { format: ExportFormat::Markdown, include_reasoning: true, path: None }
+        );
+    }
+
+    #[test]
+    fn fork_command() {
+        assert_eq!(handle_command("/fork", &builtin_models()), CommandResult::Fork(None));
+        assert_eq!(handle_command("/fork 2", &builtin_models()), CommandResult::Fork(Some(1)));
+        assert!(matches!(handle_command("/fork 0", &builtin_models()), CommandResult::Message(_)));
+        assert!(matches!(handle_command("/fork last", &builtin_models()), CommandResult::Message(_)));
+    }
+
+    #[test]
+    fn import_command() {
+        assert_eq!(handle_command("/import chat.json", &builtin_models()), CommandResult::Import("chat.json".to_string()));
+        assert!(matches!(handle_command("/import", &builtin_models()), CommandResult::Message(_)));
+    }
+
+    #[test]
+    fn search_command() {
+        assert_eq!(
+            handle_command("/search database migration", &builtin_models()),
+            CommandResult::Search("database migration".to_string())
+        );
+        assert_eq!(handle_command("/search", &builtin_models()), CommandResult::Search(String::new()));
     }
 
     #[test]
     fn model_command() {
-        match handle_command("/model MiniMax-M2.5") {
+        match handle_command("/model MiniMax-M2.5", &builtin_models()) {
             CommandResult::SetModel(m) => assert_eq!(m, "MiniMax-M2.5"),
             _ => panic!("Expected SetModel"),
         }
 
-        match handle_command("/model minimax-m2.5") {
+        match handle_command("/model minimax-m2.5", &builtin_models()) {
             CommandResult::SetModel(m) => assert_eq!(m, "MiniMax-M2.5"),
             _ => panic!("Expected SetModel (case insensitive)"),
         }
 
-        match handle_command("/model") {
+        match handle_command("/model", &builtin_models()) {
             CommandResult::Message(msg) => assert!(msg.contains("Available models")),
             _ => panic!("Expected Message with model list"),
         }
 
-        match handle_command("/model nonexistent") {
+        match handle_command("/model nonexistent", &builtin_models()) {
             CommandResult::Message(msg) => assert!(msg.contains("Unknown model")),
             _ => panic!("Expected unknown model message"),
         }
     }
 
+    #[test]
+    fn model_command_uses_registry() {
+        let mut models = builtin_models();
+        models.push(ModelInfo::with_defaults("local-coder"));
+        assert_eq!(
+            handle_command("/model LOCAL-coder", &models),
+            CommandResult::SetModel("local-coder".to_string())
+        );
+        match handle_command("/model", &models) {
+            CommandResult::Message(msg) => assert!(msg.contains("local-coder")),
+            _ => panic!("Expected Message with model list"),
+        }
+        assert!(matches!(handle_command("/model local-coder", &builtin_models()), CommandResult::Message(_)));
+    }
+
     #[test]
     fn theme_command() {
-        match handle_command("/theme gruvbox") {
+        match handle_command("/theme gruvbox", &builtin_models()) {
             CommandResult::SetTheme(t) => assert_eq!(t, "gruvbox"),
             _ => panic!("Expected SetTheme"),
         }
 
-        match handle_command("/theme") {
+        match handle_command("/theme", &builtin_models()) {
             CommandResult::Message(msg) => assert!(msg.contains("Available themes")),
             _ => panic!("Expected Message with theme list"),
         }
@@ -186,9 +394,11 @@ mod tests {
 
     #[test]
     fn help_command() {
-        match handle_command("/help") {
+        match handle_command("/help", &builtin_models()) {
             CommandResult::Message(msg) => {
                 assert!(msg.contains("/new"));
+                assert!(msg.contains("/compact"));
+                assert!(msg.contains("/handoff"));
                 assert!(msg.contains("/exit"));
                 assert!(msg.contains("/help"));
             }
@@ -198,7 +408,7 @@ mod tests {
 
     #[test]
     fn unknown_command() {
-        match handle_command("/foo") {
+        match handle_command("/foo", &builtin_models()) {
             CommandResult::Message(msg) => assert!(msg.contains("Unknown command")),
             _ => panic!("Expected unknown command message"),
         }
@@ -0,0 +1,463 @@
+use serde_json::Value;
+
+use crate::core::chat::{parse_todo_items, TodoStatus};
+
+/// Start of the history entry that stands in for summarized messages.
+const SUMMARY_HEADER: &str = "[CONVERSATION CONTEXT — Summary of ";
+/// Heading of the part of a summary built from the history itself rather
+/// than written by the model.
+const PRESERVED_HEADER: &str = "## Preserved context";
+/// Most recently read files whose contents are kept in the summary.
+const RECENT_FILES: usize = 3;
+/// Characters kept of each of those files.
+const RECENT_FILE_CHARS: usize = 4000;
+/// Per-message caps in the transcript sent for summarization.
+const USER_CHARS: usize = 2000;
+const ASSISTANT_CHARS: usize = 1500;
+const TOOL_ARGS_CHARS: usize = 300;
+const TOOL_RESULT_CHARS: usize = 800;
+/// User requests listed when summarization fails.
+const FALLBACK_REQUESTS: usize = 10;
+const FALLBACK_REQUEST_CHARS: usize = 300;
+
+/// The two history entries that stand in for `summarized` earlier messages.
+pub fn summary_messages(summarized: usize, summary: &str) -> [Value; 2] {
+    [
+        serde_json::json!({
+            "role": "user",
+            "content": format!(
+                "{}{} earlier messages]\n\
+                 The following is a summary of our conversation so far. \
+                 All files, decisions, and progress described below are real and should be treated as established context.\n\n\
+                 {}", SUMMARY_HEADER, summarized, summary)
+        }),
+        serde_json::json!({
+            "role": "assistant",
+            "content": "Understood. I have full context from our previous conversation and will continue seamlessly from where we left off."
+        }),
+    ]
+}
+
+/// The summary a history starts with, if it was compacted before: how many
+/// messages it covers and its text.
+pub fn previous_summary(history: &[Value]) -> Option<(usize, String)> {
+    let first = history.first()?;
+    if role(first) != "user" || history.get(1).map(role) != Some("assistant") {
+        return None;
+    }
+    let rest = content(first).strip_prefix(SUMMARY_HEADER)?;
+    let (count, _) = rest.split_once(' ')?;
+    let (_, summary) = rest.split_once("\n\n")?;
+    Some((count.parse().ok()?, summary.to_string()))
+}
+
+/// Where to cut `history` so that about `keep` recent entries stay. The cut
+/// is moved back to the start of a user turn, or failing that to any entry
+/// that is not a tool result, so a `tool_calls` message is never separated
+/// from its results. Returns 0 when there is no such place.
+pub fn cut_point(history: &[Value], keep: usize) -> usize {
+    let desired = history.len().saturating_sub(keep);
+    let boundary = |i: usize, turn_only: bool| {
+        i == history.len()
+            || match role(&history[i]) {
+                "user" => true,
+                "tool" => false,
+                _ => !turn_only,
+            }
+    };
+    (1..=desired)
+        .rev()
+        .find(|&i| boundary(i, true))
+        .or_else(|| (1..=desired).rev().find(|&i| boundary(i, false)))
+        .unwrap_or(0)
+}
+
+/// Prompt asking for a summary of `messages`. With a `previous` summary,
+/// the model updates it with the new messages instead of starting over.
+pub fn summary_prompt(messages: &[Value], previous: Option<&str>, focus: Option<&str>) -> String {
+    let focus = focus
+        .filter(|f| !f.trim().is_empty())
+        .map(|f| format!("The user asked the summary to focus on: {}\n\n", f.trim()))
+        .unwrap_or_default();
+    let (task, earlier) = match previous {
+        Some(previous) => (
+            "Update the summary of this coding assistant conversation with the messages that followed it. \
+             The result replaces both, so carry over everything from the previous summary that still matters.",
+            format!("Previous summary:\n{}\n\nMessages since then:\n", strip_preserved(previous)),
+        ),
+        None => (
+            "Summarize this coding assistant conversation history. This summary will replace the original messages \
+             in the conversation context, so it must preserve all essential information needed to continue working.",
+            "Conversation:\n".to_string(),
+        ),
+    };
+    format!(
+        "{}\n\n\
+         MUST preserve:\n\
+         - All file paths that were read, created, or modified\n\
+         - Key decisions made and their reasoning\n\
+         - Current task state and what remains to be done\n\
+         - Important code patterns, bugs found, or errors encountered\n\
+         - User preferences or constraints mentioned\n\
+         - Any architectural or design decisions\n\n\
+         {}{}{}",
+        task,
+        focus,
+        earlier,
+        transcript(messages)
+    )
+}
+
+/// Messages as text for the summarization prompt, with tool calls and
+/// their results shown under the call they answer.
+fn transcript(messages: &[Value]) -> String {
+    let mut lines = Vec::new();
+    for m in messages {
+        match role(m) {
+            "assistant" => {
+                if !content(m).is_empty() {
+                    lines.push(format!("[assistant]: {}", clip(content(m), ASSISTANT_CHARS)));
+                }
+                for (_, name, args) in tool_calls(m) {
+                    lines.push(format!("[assistant → {}]: {}", name, clip(&args, TOOL_ARGS_CHARS)));
+                }
+            }
+            "tool" => lines.push(format!("[tool result]: {}", clip(content(m), TOOL_RESULT_CHARS))),
+            "user" => lines.push(format!("[user]: {}", clip(content(m), USER_CHARS))),
+            other => lines.push(format!("[{}]: {}", other, clip(content(m), USER_CHARS))),
+        }
+    }
+    lines.join("\n")
+}
+
+/// Stand-in summary when the model could not write one: the previous
+/// summary, if any, and what the user asked for in the dropped messages.
+pub fn fallback_summary(messages: &[Value], previous: Option<&str>, error: &str) -> String {
+    let mut out = format!(
+        "Earlier messages were truncated because they could not be summarized ({}).",
+        error
+    );
+    if let Some(previous) = previous {
+        out.push_str(&format!("\n\nSummary before that:\n{}", strip_preserved(previous)));
+    }
+    let requests: Vec<&str> = messages
+        .iter()
+        .filter(|m| role(m) == "user")
+        .map(content)
+        .filter(|c| !c.is_empty())
+        .collect();
+    if !requests.is_empty() {
+        out.push_str("\n\nThe user's requests in those messages:");
+        for request in &requests[requests.len().saturating_sub(FALLBACK_REQUESTS)..] {
+            out.push_str(&format!("\n- {}", clip(request, FALLBACK_REQUEST_CHARS)));
+        }
+    }
+    out
+}
+
+// ── Preserved context ──────────────────────────────────────────────────
+
+/// Facts taken from the summarized messages as they are, so they survive
+/// however the model words its summary.
+#[derive(Debug, Default, PartialEq)]
+pub struct Preserved {
+    pub files_read: Vec<String>,
+    pub files_modified: Vec<String>,
+    /// Todo list lines, e.g. `- [x] Add tests`.
+    pub todos: Vec<String>,
+    /// Latest contents of recently read files: path and text.
+    pub recent_files: Vec<(String, String)>,
+}
+
+impl Preserved {
+    /// Collect from the messages being summarized, given the `kept` messages
+    /// that follow them and the `previous` summary they may start from.
+    pub fn collect(summarized: &[Value], kept: &[Value], previous: Option<&str>) -> Self {
+        let mut preserved = previous.map(Self::parse).unwrap_or_default();
+        preserved.recent_files.clear();
+
+        // Latest read_file result per path, dropped once the file changes
+        let mut reads: Vec<(String, String)> = Vec::new();
+        for (i, m) in summarized.iter().enumerate() {
+            for (id, name, args) in tool_calls(m) {
+                let Some(path) = serde_json::from_str::<Value>(&args)
+                    .ok()
+                    .and_then(|a| a.get("path").and_then(|p| p.as_str()).map(str::to_string))
+                else {
+                    continue;
+                };
+                match name.as_str() {
+                    "read_file" => {
+                        push_unique(&mut preserved.files_read, &path);
+                        reads.retain(|(p, _)| *p != path);
+                        let result = summarized[i + 1..]
+                            .iter()
+                            .find(|r| r.get("tool_call_id").and_then(|v| v.as_str()) == Some(id.as_str()))
+                            .map(content)
+                            .filter(|c| !c.starts_with("Error:"));
+                        if let Some(result) = result {
+                            reads.push((path, result.to_string()));
+                        }
+                    }
+                    "write_file" | "edit_file" => {
+                        push_unique(&mut preserved.files_modified, &path);
+                        reads.retain(|(p, _)| *p != path);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // Files the kept messages touch again are already in context
+        let touched_later: Vec<String> = kept
+            .iter()
+            .flat_map(tool_calls)
+            .filter_map(|(_, _, args)| {
+                serde_json::from_str::<Value>(&args)
+                    .ok()?
+                    .get("path")?
+                    .as_str()
+                    .map(str::to_string)
+            })
+            .collect();
+        preserved.recent_files = reads
+            .into_iter()
+            .rev()
+            .filter(|(path, _)| !touched_later.contains(path))
+            .take(RECENT_FILES)
+            .map(|(path, text)| (path, clip(&text, RECENT_FILE_CHARS)))
+            .collect();
+
+        let last_todos = summarized
+            .iter()
+            .chain(kept)
+            .flat_map(tool_calls)
+            .rfind(|(_, name, _)| name == "todo_write");
+        if let Some((_, _, args)) = last_todos {
+            let args = serde_json::from_str::<Value>(&args).unwrap_or_default();
+            preserved.todos = parse_todo_items(&args)
+                .into_iter()
+                .map(|item| {
+                    let mark = match item.status {
+                        TodoStatus::Completed => "x",
+                        TodoStatus::InProgress => "~",
+                        TodoStatus::Pending => " ",
+                    };
+                    format!("- [{}] {}", mark, item.content)
+                })
+                .collect();
+        }
+        preserved
+    }
+
+    /// Read the file lists and todo list back from a summary's preserved
+    /// section.
+    fn parse(summary: &str) -> Self {
+        let mut preserved = Self::default();
+        let Some((_, section)) = summary.split_once(PRESERVED_HEADER) else {
+            return preserved;
+        };
+        let mut in_todos = false;
+        for line in section.lines() {
+            if let Some(files) = line.strip_prefix("Files read: ") {
+                preserved.files_read = split_paths(files);
+            } else if let Some(files) = line.strip_prefix("Files modified: ") {
+                preserved.files_modified = split_paths(files);
+            } else if line == "Todo list:" {
+                in_todos = true;
+                continue;
+            } else if in_todos && line.starts_with("- [") {
+                preserved.todos.push(line.to_string());
+                continue;
+            }
+            in_todos = false;
+        }
+        preserved
+    }
+
+    fn is_empty(&self) -> bool {
+        self.files_read.is_empty()
+            && self.files_modified.is_empty()
+            && self.todos.is_empty()
+            && self.recent_files.is_empty()
+    }
+
+    /// `summary` followed by the preserved section.
+    pub fn append_to(&self, summary: &str) -> String {
+        let summary = strip_preserved(summary).trim_end();
+        if self.is_empty() {
+            return summary.to_string();
+        }
+        let mut out = format!("{}\n\n{}", summary, PRESERVED_HEADER);
+        if !self.files_read.is_empty() {
+            out.push_str(&format!("\nFiles read: {}", self.files_read.join(", ")));
+        }
+        if !self.files_modified.is_empty() {
+            out.push_str(&format!("\nFiles modified: {}", self.files_modified.join(", ")));
+        }
+        if !self.todos.is_empty() {
+            out.push_str(&format!("\nTodo list:\n{}", self.todos.join("\n")));
+        }
+        for (path, text) in &self.recent_files {
+            out.push_str(&format!("\n\n### {} (as last read)\n{}", path, text));
+        }
+        out
+    }
+}
+
+fn split_paths(list: &str) -> Vec<String> {
+    list.split(", ").filter(|p| !p.is_empty()).map(str::to_string).collect()
+}
+
+fn push_unique(list: &mut Vec<String>, path: &str) {
+    if !list.iter().any(|p| p == path) {
+        list.push(path.to_string());
+    }
+}
+
+/// A summary without its preserved section.
+fn strip_preserved(summary: &str) -> &str {
+    summary
+        .split_once(&format!("\n\n{}", PRESERVED_HEADER))
+        .map(|(text, _)| text)
+        .unwrap_or(summary)
+}
+
+// ── Message helpers ────────────────────────────────────────────────────
+
+fn role(m: &Value) -> &str {
+    m.get("role").and_then(|r| r.as_str()).unwrap_or("?")
+}
+
+fn content(m: &Value) -> &str {
+    m.get("content").and_then(|c| c.as_str()).unwrap_or("")
+}
+
+/// Id, name and arguments of each tool call in an assistant message.
+fn tool_calls(m: &Value) -> Vec<(String, String, String)> {
+    m.get("tool_calls")
+        .and_then(|t| t.as_array())
+        .map(|calls| {
+            calls
+                .iter()
+                .map(|tc| {
+                    let field = |v: Option<&Value>| v.and_then(|v| v.as_str()).unwrap_or("").to_string();
+                    (
+                        field(tc.get("id")),
+                        field(tc.pointer("/function/name")),
+                        field(tc.pointer("/function/arguments")),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn clip(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}…", &text[..idx]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn call(id: &str, name: &str, args: Value) -> Value {
+        json!({"id": id, "type": "function", "function": {"name": name, "arguments": args.to_string()}})
+    }
+
+    fn history() -> Vec<Value> {
+        vec![
+            json!({"role": "user", "content": "fix the parser"}),
+            json!({"role": "assistant", "content": "", "tool_calls": [
+                call("1", "read_file", json!({"path": "src/parser.rs"})),
+                call("2", "read_file", json!({"path": "src/lib.rs"})),
+            ]}),
+            json!({"role": "tool", "tool_call_id": "1", "content": "fn parse() {}"}),
+            json!({"role": "tool", "tool_call_id": "2", "content": "mod parser;"}),
+            json!({"role": "assistant", "content": "", "tool_calls": [
+                call("3", "edit_file", json!({"path": "src/parser.rs", "old_str": "a", "new_str": "b"})),
+                call("4", "todo_write", json!({"todos": [
+                    {"content": "Fix parser", "status": "completed"},
+                    {"content": "Add tests", "status": "pending"},
+                ]})),
+            ]}),
+            json!({"role": "tool", "tool_call_id": "3", "content": "ok"}),
+            json!({"role": "tool", "tool_call_id": "4", "content": "Todo list updated"}),
+            json!({"role": "assistant", "content": "Fixed."}),
+            json!({"role": "user", "content": "now add tests"}),
+            json!({"role": "assistant", "content": "On it."}),
+        ]
+    }
+
+    #[test]
+    fn cut_point_never_splits_tool_results() {
+        let h = history();
+        assert_eq!(cut_point(&h, 2), 8);
+        // Keeping 5 would start at a tool result; with no user turn before
+        // that, the cut goes before the assistant message that made the call
+        assert_eq!(cut_point(&h, 5), 4);
+        assert_eq!(cut_point(&h, 7), 1);
+        assert_eq!(cut_point(&h, 10), 0);
+
+        let single_turn = &h[..8];
+        assert_eq!(cut_point(single_turn, 3), 4);
+        assert_eq!(cut_point(single_turn, 0), 8);
+        assert_eq!(cut_point(&[], 3), 0);
+    }
+
+    #[test]
+    fn preserved_keeps_paths_todos_and_unchanged_files() {
+        let h = history();
+        let preserved = Preserved::collect(&h[..8], &h[8..], None);
+        assert_eq!(preserved.files_read, ["src/parser.rs", "src/lib.rs"]);
+        assert_eq!(preserved.files_modified, ["src/parser.rs"]);
+        assert_eq!(preserved.todos, ["- [x] Fix parser", "- [ ] Add tests"]);
+        // parser.rs was edited after it was read, so only lib.rs is current
+        assert_eq!(preserved.recent_files, vec![("src/lib.rs".to_string(), "mod parser;".to_string())]);
+
+        let summary = preserved.append_to("Fixed the parser.");
+        assert!(summary.starts_with("Fixed the parser.\n\n## Preserved context\nFiles read: src/parser.rs, src/lib.rs"));
+        assert!(summary.contains("### src/lib.rs (as last read)\nmod parser;"));
+    }
+
+    #[test]
+    fn resummarizing_carries_previous_summary() {
+        let h = history();
+        let first = Preserved::collect(&h[..8], &[], None).append_to("Fixed the parser.");
+        let mut compacted = summary_messages(8, &first).to_vec();
+        compacted.extend(h[8..].iter().cloned());
+        compacted.push(json!({"role": "assistant", "content": "", "tool_calls": [
+            call("5", "write_file", json!({"path": "tests/parser.rs", "content": "#[test]"})),
+        ]}));
+
+        let (count, previous) = previous_summary(&compacted).unwrap();
+        assert_eq!(count, 8);
+        assert_eq!(previous, first);
+
+        let prompt = summary_prompt(&compacted[2..], Some(&previous), Some("tests"));
+        assert!(prompt.contains("Previous summary:\nFixed the parser.\n\nMessages since then:"));
+        assert!(!prompt.contains("## Preserved context"));
+        assert!(prompt.contains("focus on: tests"));
+        assert!(prompt.contains("[assistant → write_file]"));
+
+        let second = Preserved::collect(&compacted[2..], &[], Some(&previous));
+        assert_eq!(second.files_read, ["src/parser.rs", "src/lib.rs"]);
+        assert_eq!(second.files_modified, ["src/parser.rs", "tests/parser.rs"]);
+        assert_eq!(second.todos.len(), 2);
+        assert!(second.recent_files.is_empty());
+        assert_eq!(previous_summary(&h), None);
+    }
+
+    #[test]
+    fn fallback_lists_user_requests() {
+        let h = history();
+        let text = fallback_summary(&h, Some("Earlier work.\n\n## Preserved context\nFiles read: a"), "timeout");
+        assert!(text.starts_with("Earlier messages were truncated because they could not be summarized (timeout)."));
+        assert!(text.contains("Summary before that:\nEarlier work.\n\nThe user's requests"));
+        assert!(text.contains("\n- fix the parser\n- now add tests"));
+    }
+}
@@ -0,0 +1,385 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::core::api::AccumulatedToolCall;
+use crate::core::project::ProjectInfo;
+use crate::core::session::{Session, SessionStore, StoredMessage};
+
+/// Marker identifying a session export file.
+const FORMAT_NAME: &str = "minmax-code-session";
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "md" | "markdown" => Some(Self::Markdown),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Json => "json",
+        }
+    }
+}
+
+// ── JSON document ──────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionDocument {
+    format: String,
+    version: u32,
+    session: ExportedSession,
+    messages: Vec<ExportedMessage>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedSession {
+    id: String,
+    name: String,
+    model: String,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedMessage {
+    role: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reasoning: Option<String>,
+    /// Tool status and diff data, compression markers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    meta: Option<Value>,
+    #[serde(default)]
+    created_at: String,
+}
+
+fn load(store: &SessionStore, session_id: &str) -> Result<(Session, Vec<StoredMessage>)> {
+    let session = store
+        .get_session(session_id)?
+        .ok_or_else(|| anyhow!("Session {} not found", session_id))?;
+    let messages = store.get_session_messages(session_id)?;
+    Ok((session, messages))
+}
+
+/// Export a session in the given format.
+pub fn export_session(
+    store: &SessionStore,
+    session_id: &str,
+    format: ExportFormat,
+    include_reasoning: bool,
+) -> Result<String> {
+    let (session, messages) = load(store, session_id)?;
+    match format {
+        ExportFormat::Markdown => Ok(to_markdown(&session, &messages, include_reasoning)),
+        ExportFormat::Json => to_json(&session, &messages),
+    }
+}
+
+/// Lossless JSON export; `import_json` turns it back into a session.
+pub fn to_json(session: &Session, messages: &[StoredMessage]) -> Result<String> {
+    let doc = SessionDocument {
+        format: FORMAT_NAME.to_string(),
+        version: FORMAT_VERSION,
+        session: ExportedSession {
+            id: session.id.clone(),
+            name: session.name.clone(),
+            model: session.model.clone(),
+            created_at: session.created_at.clone(),
+            updated_at: session.updated_at.clone(),
+        },
+        messages: messages
+            .iter()
+            .map(|m| ExportedMessage {
+                role: m.role.clone(),
+                content: m.content.clone(),
+                // Stored as a JSON string; embed it as JSON so the file stays readable
+                tool_calls: m
+                    .tool_calls
+                    .as_deref()
+                    .map(|tc| serde_json::from_str(tc).unwrap_or_else(|_| Value::String(tc.to_string()))),
+                tool_call_id: m.tool_call_id.clone(),
+                name: m.name.clone(),
+                reasoning: m.reasoning.clone(),
+                meta: m.meta.as_deref().and_then(|meta| serde_json::from_str(meta).ok()),
+                created_at: m.created_at.clone(),
+            })
+            .collect(),
+    };
+    Ok(serde_json::to_string_pretty(&doc)?)
+}
+
+/// Recreate a session from a JSON export, filed under `project`. The imported
+/// session gets a new id so importing the same file twice never collides.
+pub fn import_json(store: &SessionStore, json: &str, project: Option<&ProjectInfo>) -> Result<Session> {
+    let doc: SessionDocument =
+        serde_json::from_str(json).map_err(|e| anyhow!("Not a session export: {}", e))?;
+    if doc.format != FORMAT_NAME {
+        return Err(anyhow!("Not a session export (format \"{}\")", doc.format));
+    }
+    if doc.version > FORMAT_VERSION {
+        return Err(anyhow!(
+            "Session export version {} is newer than supported ({})",
+            doc.version,
+            FORMAT_VERSION
+        ));
+    }
+
+    let session = Session {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: doc.session.name,
+        model: doc.session.model,
+        created_at: doc.session.created_at,
+        updated_at: doc.session.updated_at,
+        parent_id: None,
+        cwd: project.map(|p| p.cwd.clone()),
+        git_root: project.and_then(|p| p.git_root.clone()),
+        git_branch: project.and_then(|p| p.git_branch.clone()),
+        pinned: false,
+    };
+    let messages: Vec<StoredMessage> = doc
+        .messages
+        .into_iter()
+        .map(|m| StoredMessage {
+            id: 0,
+            session_id: session.id.clone(),
+            role: m.role,
+            content: m.content,
+            tool_calls: m.tool_calls.map(|tc| match tc {
+                Value::String(s) => s,
+                other => other.to_string(),
+            }),
+            tool_call_id: m.tool_call_id,
+            name: m.name,
+            reasoning: m.reasoning,
+            meta: m.meta.map(|meta| meta.to_string()),
+            created_at: m.created_at,
+        })
+        .collect();
+
+    store.import_session(&session, &messages)?;
+    Ok(session)
+}
+
+// ── Markdown ───────────────────────────────────────────────────────────
+
+/// Readable Markdown transcript. Tool calls and results are collapsed in
+/// `<details>` blocks; reasoning is included only when asked for.
+pub fn to_markdown(session: &Session, messages: &[StoredMessage], include_reasoning: bool) -> String {
+    let mut out = format!("# {}\n\n", session.name);
+    out.push_str(&format!("- **Model:** {}\n", session.model));
+    out.push_str(&format!("- **Session:** `{}`\n", session.id));
+    if let Some(first) = messages.first() {
+        out.push_str(&format!("- **Started:** {}\n", first.created_at));
+    }
+    out.push_str("\n---\n");
+
+    for m in messages {
+        match m.role.as_str() {
+            "user" => {
+                out.push_str("\n## User\n\n");
+                out.push_str(m.content.trim_end());
+                out.push('\n');
+            }
+            "assistant" => {
+                out.push_str("\n## Assistant\n\n");
+                if include_reasoning {
+                    if let Some(reasoning) = m.reasoning.as_deref().filter(|r| !r.trim().is_empty()) {
+                        out.push_str(&details("Reasoning", &fenced(reasoning, "")));
+                    }
+                }
+                if !m.content.trim().is_empty() {
+                    out.push_str(m.content.trim_end());
+                    out.push_str("\n\n");
+                }
+                for call in parse_tool_calls(m.tool_calls.as_deref()) {
+                    let args = serde_json::from_str::<Value>(&call.function.arguments)
+                        .ok()
+                        .and_then(|v| serde_json::to_string_pretty(&v).ok())
+                        .unwrap_or(call.function.arguments.clone());
+                    out.push_str(&details(
+                        &format!("Tool call: <code>{}</code>", call.function.name),
+                        &fenced(&args, "json"),
+                    ));
+                }
+            }
+            "tool" => {
+                let name = m.name.as_deref().unwrap_or("tool");
+                out.push('\n');
+                out.push_str(&details(
+                    &format!("Tool result: <code>{}</code>", name),
+                    &fenced(&m.content, ""),
+                ));
+            }
+            _ => {
+                out.push_str(&format!("\n> {}\n", m.content.trim_end().replace('\n', "\n> ")));
+            }
+        }
+    }
+    out
+}
+
+fn parse_tool_calls(json: Option<&str>) -> Vec<AccumulatedToolCall> {
+    json.and_then(|tc| serde_json::from_str(tc).ok()).unwrap_or_default()
+}
+
+fn details(summary: &str, body: &str) -> String {
+    format!("<details>\n<summary>{}</summary>\n\n{}\n</details>\n\n", summary, body)
+}
+
+/// Wrap text in a code fence longer than any backtick run inside it.
+fn fenced(text: &str, lang: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in text.chars() {
+        if c == '`' {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}{}\n{}\n{}\n", fence, lang, text.trim_end(), fence)
+}
+
+/// File name for an export, e.g. `fix-login-bug-1a2b3c4d.md`.
+pub fn default_file_name(session: &Session, format: ExportFormat) -> String {
+    let slug: String = session
+        .name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let short_id: String = session.id.chars().take(8).collect();
+    let stem = if slug.is_empty() {
+        format!("session-{}", short_id)
+    } else {
+        format!("{}-{}", slug, short_id)
+    };
+    format!("{}.{}", stem, format.extension())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_store() -> (tempfile::TempDir, SessionStore, Session) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = SessionStore::open_at(dir.path().join("sessions.db")).unwrap();
+        let session = store.create_session("MiniMax-M2.5", None).unwrap();
+        store.rename_session(&session.id, "Fix login bug").unwrap();
+        store
+            .save_message(&session.id, "user", "Why does login fail?", None, None, None)
+            .unwrap();
+        let id = store
+            .save_message(
+                &session.id,
+                "assistant",
+                "Let me check.",
+                Some(r#"[{"id":"tc1","type":"function","function":{"name":"read_file","arguments":"{\"path\":\"src/auth.rs\"}"}}]"#),
+                None,
+                None,
+            )
+            .unwrap();
+        store.set_message_reasoning(id, "Probably the token check").unwrap();
+        store
+            .save_message(&session.id, "tool", "fn login() {}", None, Some("tc1"), Some("read_file"))
+            .unwrap();
+        let session = store.get_session(&session.id).unwrap().unwrap();
+        (dir, store, session)
+    }
+
+    #[test]
+    fn markdown_collapses_tools() {
+        let (_dir, store, session) = sample_store();
+        let md = export_session(&store, &session.id, ExportFormat::Markdown, false).unwrap();
+        assert!(md.starts_with("# Fix login bug\n"));
+        assert!(md.contains("## User\n\nWhy does login fail?"));
+        assert!(md.contains("<summary>Tool call: <code>read_file</code></summary>"));
+        assert!(md.contains("\"path\": \"src/auth.rs\""));
+        assert!(md.contains("<summary>Tool result: <code>read_file</code></summary>"));
+        assert!(!md.contains("Probably the token check"));
+
+        let with_reasoning = export_session(&store, &session.id, ExportFormat::Markdown, true).unwrap();
+        assert!(with_reasoning.contains("Probably the token check"));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let (_dir, store, session) = sample_store();
+        let json = export_session(&store, &session.id, ExportFormat::Json, false).unwrap();
+        let imported = import_json(&store, &json, None).unwrap();
+        assert_ne!(imported.id, session.id);
+        assert_eq!(imported.name, "Fix login bug");
+
+        let original = store.get_session_messages(&session.id).unwrap();
+        let copy = store.get_session_messages(&imported.id).unwrap();
+        assert_eq!(original.len(), copy.len());
+        for (a, b) in original.iter().zip(&copy) {
+            assert_eq!(a.role, b.role);
+            assert_eq!(a.content, b.content);
+            assert_eq!(a.tool_call_id, b.tool_call_id);
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.reasoning, b.reasoning);
+            assert_eq!(a.created_at, b.created_at);
+            let parse = |tc: &Option<String>| tc.as_deref().map(|s| serde_json::from_str::<Value>(s).unwrap());
+            assert_eq!(parse(&a.tool_calls), parse(&b.tool_calls));
+        }
+    }
+
+    #[test]
+    fn import_rejects_other_json() {
+        let (_dir, store, _) = sample_store();
+        assert!(import_json(&store, r#"{"hello": "world"}"#, None).is_err());
+        let wrong = r#"{"format": "other", "version": 1,
+            "session": {"id": "x", "name": "n", "model": "m", "createdAt": "", "updatedAt": ""},
+            "messages": []}"#;
+        assert!(import_json(&store, wrong, None).is_err());
+    }
+
+    #[test]
+    fn fence_outgrows_backticks_in_content() {
+        let text = "```rust\nfn main() {}\n```";
+        let out = fenced(text, "");
+        assert!(out.starts_with("````\n"));
+        assert!(out.ends_with("\n````\n"));
+    }
+
+    #[test]
+    fn file_name_from_session_name() {
+        let session = Session {
+            id: "1a2b3c4d-0000".to_string(),
+            name: "Fix: login bug!".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(default_file_name(&session, ExportFormat::Markdown), "fix-login-bug-1a2b3c4d.md");
+        assert_eq!(ExportFormat::parse("JSON"), Some(ExportFormat::Json));
+        assert_eq!(ExportFormat::parse("txt"), None);
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::settings::{InstructionsConfig, LOCAL_CONFIG_FILE, PROJECT_CONFIG_FILE};
+use crate::core::project::find_git_root;
+use crate::core::usage::format_tokens;
+
+/// How deep `@path` includes may nest.
+const MAX_INCLUDE_DEPTH: usize = 5;
+
+/// An instruction file added to the system prompt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstructionFile {
+    /// Short name shown in the prompt and in `/context`.
+    pub label: String,
+    pub path: PathBuf,
+    /// Text with includes expanded, cut to the size cap.
+    pub content: String,
+    /// Files pulled in through `@path` lines.
+    pub includes: Vec<PathBuf>,
+    /// Characters before the size cap was applied.
+    pub original_chars: usize,
+}
+
+impl InstructionFile {
+    pub fn truncated(&self) -> bool {
+        self.content.chars().count() < self.original_chars
+    }
+}
+
+/// Instruction files in effect for a working directory: the global file in
+/// the config directory, then one file per directory from the project root
+/// down to `cwd`, so more specific conventions come last.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Instructions {
+    pub files: Vec<InstructionFile>,
+    pub max_chars: usize,
+}
+
+impl Instructions {
+    /// Find and read the instruction files for `cwd`. The project root is the
+    /// enclosing git repository, or `cwd` itself outside of git.
+    pub fn load(cwd: &Path, global_dir: &Path, config: &InstructionsConfig) -> Self {
+        let root = find_git_root(cwd).unwrap_or_else(|| cwd.to_path_buf());
+        let mut dirs: Vec<&Path> = cwd.ancestors().take_while(|d| d.starts_with(&root)).collect();
+        dirs.reverse();
+
+        let mut found: Vec<(String, PathBuf)> = Vec::new();
+        if let Some(path) = find_instruction_file(global_dir, &config.file_names) {
+            found.push((format!("~/.minmax-code/{}", file_name(&path)), path));
+        }
+        for dir in dirs {
+            if dir == global_dir {
+                continue;
+            }
+            if let Some(path) = find_instruction_file(dir, &config.file_names) {
+                let label = path
+                    .strip_prefix(&root)
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_else(|_| path.to_string_lossy().to_string());
+                found.push((label, path));
+            }
+        }
+
+        let scope = IncludeScope::new(&root, global_dir, dirs::home_dir());
+        let mut files: Vec<InstructionFile> = found
+            .into_iter()
+            .filter_map(|(label, path)| {
+                let raw = fs::read_to_string(&path).ok()?;
+                let base = path.parent().unwrap_or(Path::new("."));
+                let mut seen = HashSet::from([canonical(&path)]);
+                let mut includes = Vec::new();
+                let content = expand_includes(&raw, base, &scope, 0, &mut seen, &mut includes);
+                let original_chars = content.chars().count();
+                Some(InstructionFile { label, path, content, includes, original_chars })
+            })
+            .collect();
+
+        // When over the cap, the files closest to `cwd` keep their text
+        let mut budget = config.max_chars;
+        for file in files.iter_mut().rev() {
+            if file.original_chars > budget {
+                file.content = file.content.chars().take(budget).collect();
+            }
+            budget -= file.content.chars().count();
+        }
+
+        Self { files, max_chars: config.max_chars }
+    }
+
+    pub fn total_chars(&self) -> usize {
+        self.files.iter().map(|f| f.content.chars().count()).sum()
+    }
+
+    /// Sections appended to the system prompt, one per file.
+    pub fn to_prompt(&self) -> String {
+        let mut out = String::new();
+        for file in self.files.iter().filter(|f| !f.content.is_empty()) {
+            out.push_str(&format!("\n\n--- {} ---\n{}", file.label, file.content));
+            if file.truncated() {
+                out.push_str("\n[truncated: instruction size limit reached]");
+            }
+        }
+        out
+    }
+
+    /// Text for `/context`.
+    pub fn summary(&self, file_names: &[String]) -> String {
+        if self.files.is_empty() {
+            return format!(
+                "No instruction files loaded. Looked for {} in ~/.minmax-code and in every directory from the project root down to the current one.",
+                file_names.join(", ")
+            );
+        }
+        let mut out = format!(
+            "Instruction files ({} of {} chars):",
+            format_tokens(self.total_chars() as u64),
+            format_tokens(self.max_chars as u64)
+        );
+        for file in &self.files {
+            let chars = file.content.chars().count() as u64;
+            out.push_str(&format!("\n  {}  {}", file.label, format_tokens(chars)));
+            if file.content.is_empty() {
+                out.push_str(" (dropped: size limit)");
+            } else if file.truncated() {
+                out.push_str(&format!(
+                    " (truncated from {})",
+                    format_tokens(file.original_chars as u64)
+                ));
+            }
+            for include in &file.includes {
+                out.push_str(&format!("\n    @{}", include.display()));
+            }
+        }
+        out
+    }
+}
+
+fn find_instruction_file(dir: &Path, names: &[String]) -> Option<PathBuf> {
+    names.iter().map(|n| dir.join(n)).find(|p| p.is_file())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Where `@path` includes may read from: the project root and the global
+/// config directory, never the config or secrets files, which can hold the
+/// API key.
+struct IncludeScope {
+    roots: Vec<PathBuf>,
+    denied: Vec<PathBuf>,
+    home: Option<PathBuf>,
+}
+
+impl IncludeScope {
+    fn new(root: &Path, global_dir: &Path, home: Option<PathBuf>) -> Self {
+        let denied = [
+            global_dir.join("config.json"),
+            global_dir.join("secrets.json"),
+            root.join(PROJECT_CONFIG_FILE),
+            root.join(LOCAL_CONFIG_FILE),
+        ];
+        Self {
+            roots: vec![canonical(root), canonical(global_dir)],
+            denied: denied.iter().map(|p| canonical(p)).collect(),
+            home,
+        }
+    }
+
+    /// The canonical path for `target`, if it exists and may be included.
+    fn resolve(&self, target: &str, base: &Path) -> Option<PathBuf> {
+        let path = match (target.strip_prefix("~/"), &self.home) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ => base.join(target),
+        };
+        let path = path.canonicalize().ok()?;
+        (self.roots.iter().any(|r| path.starts_with(r)) && !self.denied.contains(&path))
+            .then_some(path)
+    }
+}
+
+/// Replace lines consisting of `@path` with the contents of that file,
+/// resolved relative to the including file. Lines naming files that do not
+/// exist, that are outside the include scope, or that were already included
+/// are left as written.
+fn expand_includes(
+    text: &str,
+    base: &Path,
+    scope: &IncludeScope,
+    depth: usize,
+    seen: &mut HashSet<PathBuf>,
+    includes: &mut Vec<PathBuf>,
+) -> String {
+    let mut out = Vec::new();
+    for line in text.lines() {
+        let Some(target) = include_target(line) else {
+            out.push(line.to_string());
+            continue;
+        };
+        let content = scope
+            .resolve(target, base)
+            .filter(|path| depth < MAX_INCLUDE_DEPTH && seen.insert(path.clone()))
+            .and_then(|path| Some((fs::read_to_string(&path).ok()?, path)));
+        match content {
+            Some((content, path)) => {
+                includes.push(path.clone());
+                let nested_base = path.parent().unwrap_or(base).to_path_buf();
+                out.push(expand_includes(&content, &nested_base, scope, depth + 1, seen, includes));
+            }
+            None => out.push(line.to_string()),
+        }
+    }
+    out.join("\n")
+}
+
+fn include_target(line: &str) -> Option<&str> {
+    let target = line.trim().strip_prefix('@')?;
+    (!target.is_empty() && !target.contains(char::is_whitespace)).then_some(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_chars: usize) -> InstructionsConfig {
+        InstructionsConfig { max_chars, ..InstructionsConfig::default() }
+    }
+
+    #[test]
+    fn loads_global_then_root_to_cwd() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let global = dir.path().join("global");
+        let root = dir.path().join("repo");
+        let pkg = root.join("packages/api");
+        fs::create_dir_all(&global).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(&pkg).unwrap();
+        fs::write(global.join("agent.md"), "global rules").unwrap();
+        fs::write(root.join("AGENTS.md"), "repo rules").unwrap();
+        fs::write(pkg.join("agent.md"), "api rules").unwrap();
+        fs::write(pkg.join("CLAUDE.md"), "ignored: agent.md is preferred").unwrap();
+
+        let loaded = Instructions::load(&pkg, &global, &config(10_000));
+        let labels: Vec<&str> = loaded.files.iter().map(|f| f.label.as_str()).collect();
+        assert_eq!(labels, ["~/.minmax-code/agent.md", "AGENTS.md", "packages/api/agent.md"]);
+
+        let prompt = loaded.to_prompt();
+        assert!(prompt.contains("--- AGENTS.md ---\nrepo rules"));
+        assert!(prompt.find("global rules").unwrap() < prompt.find("api rules").unwrap());
+        assert!(!prompt.contains("ignored"));
+    }
+
+    #[test]
+    fn expands_includes_once() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("agent.md"), "intro\n@docs/style.md\n@missing.md\n@docs/style.md").unwrap();
+        fs::write(root.join("docs/style.md"), "use tabs\n@../agent.md").unwrap();
+
+        let loaded = Instructions::load(root, &root.join("none"), &config(10_000));
+        let file = &loaded.files[0];
+        assert_eq!(file.content, "intro\nuse tabs\n@../agent.md\n@missing.md\n@docs/style.md");
+        assert_eq!(file.includes, vec![root.canonicalize().unwrap().join("docs/style.md")]);
+    }
+
+    #[test]
+    fn rejects_includes_outside_the_project() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().join("repo");
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(dir.path().join("outside.md"), "outside").unwrap();
+        fs::write(root.join("agent.md"), "@../outside.md").unwrap();
+
+        let loaded = Instructions::load(&root, &root.join("none"), &config(10_000));
+        assert_eq!(loaded.files[0].content, "@../outside.md");
+        assert!(loaded.files[0].includes.is_empty());
+    }
+
+    #[test]
+    fn rejects_home_includes_outside_the_config_dir_and_secret_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let home = dir.path().join("home");
+        let global = home.join(".minmax-code");
+        let root = dir.path().join("repo");
+        fs::create_dir_all(&global).unwrap();
+        fs::create_dir_all(&root).unwrap();
+        fs::write(home.join("notes.md"), "notes").unwrap();
+        fs::write(global.join("style.md"), "shared style").unwrap();
+        fs::write(global.join("secrets.json"), r#"{"apiKey":"sk-test"}"#).unwrap();
+        fs::write(global.join("config.json"), r#"{"apiKey":"sk-test"}"#).unwrap();
+
+        let scope = IncludeScope::new(&root, &global, Some(home));
+        let text = "@~/notes.md\n@~/.minmax-code/secrets.json\n@~/.minmax-code/config.json\n@~/.minmax-code/style.md";
+        let mut includes = Vec::new();
+        let out = expand_includes(text, &root, &scope, 0, &mut HashSet::new(), &mut includes);
+        assert_eq!(
+            out,
+            "@~/notes.md\n@~/.minmax-code/secrets.json\n@~/.minmax-code/config.json\nshared style"
+        );
+        assert_eq!(includes.len(), 1);
+    }
+
+    #[test]
+    fn size_cap_keeps_nearest_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        let nested = root.join("sub");
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join("agent.md"), "r".repeat(50)).unwrap();
+        fs::write(nested.join("agent.md"), "n".repeat(30)).unwrap();
+
+        let loaded = Instructions::load(&nested, &root.join("none"), &config(40));
+        assert_eq!(loaded.files[1].content.len(), 30);
+        assert_eq!(loaded.files[0].content.len(), 10);
+        assert!(loaded.files[0].truncated());
+        assert_eq!(loaded.total_chars(), 40);
+        assert!(loaded.to_prompt().contains("[truncated"));
+        assert!(loaded.summary(&[]).contains("truncated from 50"));
+    }
+}
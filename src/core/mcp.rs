@@ -1,13 +1,17 @@
 use anyhow::{anyhow, Result};
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::process::Stdio;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command};
 use tokio::sync::Mutex;
 
 use crate::config::settings::McpServerConfig;
+use crate::core::Mode;
+use crate::tools::ToolExecutionResult;
 
 // ── JSON-RPC types ─────────────────────────────────────────────────────
 
@@ -44,6 +48,25 @@ pub struct McpToolInfo {
     pub tool_name: String,
     pub description: String,
     pub input_schema: Value,
+    /// Safe to call in PLAN mode (config override or `readOnlyHint`).
+    pub read_only: bool,
+    /// Server reported `destructiveHint: true`.
+    pub destructive: bool,
+}
+
+/// Classify a tool as read-only. The server config wins; otherwise the tool must
+/// advertise `readOnlyHint: true` without `destructiveHint: true`. Unannotated
+/// tools are treated as write-capable, matching the MCP defaults.
+fn is_read_only_tool(config: &McpServerConfig, tool: &str, annotations: Option<&Value>) -> bool {
+    if config.marks_read_only(tool) {
+        return true;
+    }
+    let hint = |key: &str| {
+        annotations
+            .and_then(|a| a.get(key))
+            .and_then(|v| v.as_bool())
+    };
+    hint("readOnlyHint") == Some(true) && hint("destructiveHint") != Some(true)
 }
 
 // ── MCP Connection ─────────────────────────────────────────────────────
@@ -238,8 +261,17 @@ impl McpManager {
         if let Some(tools) = tools_result.get("tools").and_then(|t| t.as_array()) {
             for tool in tools {
                 let name = tool.get("name").and_then(|n| n.as_str()).unwrap_or("");
+                if !config.is_tool_enabled(name) {
+                    continue;
+                }
                 let description = tool.get("description").and_then(|d| d.as_str()).unwrap_or("");
                 let input_schema = tool.get("inputSchema").cloned().unwrap_or(serde_json::json!({}));
+                let annotations = tool.get("annotations");
+                let read_only = is_read_only_tool(config, name, annotations);
+                let destructive = annotations
+                    .and_then(|a| a.get("destructiveHint"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
 
                 let prefixed_name = format!("mcp__{}_{}", server_name, name);
                 let info = McpToolInfo {
@@ -247,6 +279,8 @@ impl McpManager {
                     tool_name: name.to_string(),
                     description: description.to_string(),
                     input_schema,
+                    read_only,
+                    destructive,
                 };
 
                 conn.tools.insert(prefixed_name.clone(), info.clone());
@@ -261,16 +295,19 @@ impl McpManager {
         Ok(tool_names)
     }
 
-    /// Get OpenAI-compatible tool definitions for all MCP tools.
-    pub fn get_tool_definitions(&self) -> Vec<Value> {
+    /// Get OpenAI-compatible tool definitions for the MCP tools usable in `mode`.
+    /// PLAN mode only sees read-only tools.
+    pub fn get_tool_definitions(&self, mode: Mode) -> Vec<Value> {
         self.tool_map
             .iter()
+            .filter(|(_, info)| mode == Mode::Builder || info.read_only)
             .map(|(prefixed_name, info)| {
+                let tag = if info.destructive { " (destructive)" } else { "" };
                 serde_json::json!({
                     "type": "function",
                     "function": {
                         "name": prefixed_name,
-                        "description": format!("[MCP:{}] {}", info.server_name, info.description),
+                        "description": format!("[MCP:{}]{} {}", info.server_name, tag, info.description),
                         "parameters": info.input_schema
                     }
                 })
@@ -278,13 +315,20 @@ impl McpManager {
             .collect()
     }
 
-    /// Call an MCP tool by its prefixed name.
-    pub async fn call_tool(&self, prefixed_name: &str, args: Value) -> Result<String> {
+    /// Call an MCP tool by its prefixed name. Write-capable tools are refused in PLAN mode.
+    pub async fn call_tool(&self, prefixed_name: &str, args: Value, mode: Mode) -> Result<ToolExecutionResult> {
         let info = self
             .tool_map
             .get(prefixed_name)
             .ok_or_else(|| anyhow!("Unknown MCP tool: {}", prefixed_name))?;
 
+        if mode == Mode::Plan && !info.read_only {
+            return Ok(ToolExecutionResult::error(format!(
+                "Error: Tool \"{}\" is not available in PLAN mode. Switch to BUILDER mode (Tab) to use it.",
+                prefixed_name
+            )));
+        }
+
         let server_name = &info.server_name;
         let tool_name = &info.tool_name;
 
@@ -308,25 +352,7 @@ impl McpManager {
         .map_err(|_| anyhow!("MCP tool call timeout"))?
         .map_err(|e| anyhow!("MCP tool call failed: {}", e))?;
 
-        // Extract text content from result
-        if let Some(content) = result.get("content").and_then(|c| c.as_array()) {
-            let texts: Vec<String> = content
-                .iter()
-                .filter_map(|item| {
-                    if item.get("type").and_then(|t| t.as_str()) == Some("text") {
-                        item.get("text").and_then(|t| t.as_str()).map(String::from)
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-            if !texts.is_empty() {
-                return Ok(texts.join("\n"));
-            }
-        }
-
-        // Fallback: stringify the result
-        Ok(serde_json::to_string_pretty(&result)?)
+        Ok(format_call_result(server_name, &result))
     }
 
     /// Check if a tool name is an MCP tool.
@@ -334,6 +360,11 @@ impl McpManager {
         self.tool_map.contains_key(name)
     }
 
+    /// Check if an MCP tool is classified as read-only.
+    pub fn is_read_only(&self, name: &str) -> bool {
+        self.tool_map.get(name).is_some_and(|info| info.read_only)
+    }
+
     /// Shutdown all MCP servers gracefully.
     pub async fn shutdown(&mut self) {
         for (_name, conn_mutex) in self.connections.drain() {
@@ -344,6 +375,160 @@ impl McpManager {
             let _ = conn.child.kill().await;
         }
         self.tool_map.clear();
+        remove_blobs();
+    }
+}
+
+// ── Tool result content ────────────────────────────────────────────────
+
+/// Convert a `tools/call` result into a tool result, preserving every content
+/// type: text is kept as-is, images/audio are saved to temp files and referenced
+/// by path, embedded resources are inlined when textual, and `structuredContent`
+/// is appended as JSON. `isError` marks the result as failed.
+fn format_call_result(server_name: &str, result: &Value) -> ToolExecutionResult {
+    let mut parts: Vec<String> = Vec::new();
+
+    if let Some(content) = result.get("content").and_then(|c| c.as_array()) {
+        for item in content {
+            if let Some(part) = format_content_item(server_name, item) {
+                parts.push(part);
+            }
+        }
+    }
+
+    if let Some(structured) = result.get("structuredContent") {
+        let json = serde_json::to_string_pretty(structured).unwrap_or_default();
+        if parts.is_empty() {
+            parts.push(json);
+        } else {
+            parts.push(format!("Structured result:\n{}", json));
+        }
+    }
+
+    let text = if parts.is_empty() {
+        // Fallback: stringify the result
+        serde_json::to_string_pretty(result).unwrap_or_default()
+    } else {
+        parts.join("\n")
+    };
+
+    if result.get("isError").and_then(|v| v.as_bool()).unwrap_or(false) {
+        // Prefix so the model sees the failure too, not just the UI
+        if text.starts_with("Error") {
+            ToolExecutionResult::error(text)
+        } else {
+            ToolExecutionResult::error(format!("Error: {}", text))
+        }
+    } else {
+        ToolExecutionResult::text(text)
+    }
+}
+
+fn format_content_item(server_name: &str, item: &Value) -> Option<String> {
+    let str_field = |v: &Value, key: &str| v.get(key).and_then(|s| s.as_str()).map(String::from);
+
+    match item.get("type").and_then(|t| t.as_str())? {
+        "text" => str_field(item, "text"),
+        kind @ ("image" | "audio") => {
+            let mime = str_field(item, "mimeType").unwrap_or_default();
+            let data = str_field(item, "data").unwrap_or_default();
+            Some(match save_blob(server_name, &data, &mime) {
+                Ok((path, size)) => format!("[{}: {} ({}, {} bytes)]", kind, path.display(), mime, size),
+                Err(e) => format!("[{}: could not be saved: {}]", kind, e),
+            })
+        }
+        "resource" => {
+            let resource = item.get("resource")?;
+            let uri = str_field(resource, "uri").unwrap_or_default();
+            let mime = str_field(resource, "mimeType").unwrap_or_default();
+            if let Some(text) = str_field(resource, "text") {
+                return Some(format!("[resource: {}]\n{}", uri, text));
+            }
+            let blob = str_field(resource, "blob").unwrap_or_default();
+            if is_textual_mime(&mime) {
+                if let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(blob.as_bytes()) {
+                    if let Ok(text) = String::from_utf8(bytes) {
+                        return Some(format!("[resource: {}]\n{}", uri, text));
+                    }
+                }
+            }
+            Some(match save_blob(server_name, &blob, &mime) {
+                Ok((path, size)) => format!("[resource: {} saved to {} ({}, {} bytes)]", uri, path.display(), mime, size),
+                Err(e) => format!("[resource: {} could not be saved: {}]", uri, e),
+            })
+        }
+        "resource_link" => {
+            let uri = str_field(item, "uri").unwrap_or_default();
+            let name = str_field(item, "name").unwrap_or_else(|| uri.clone());
+            Some(match str_field(item, "description") {
+                Some(desc) => format!("[resource link: {} <{}>] {}", name, uri, desc),
+                None => format!("[resource link: {} <{}>]", name, uri),
+            })
+        }
+        _ => Some(serde_json::to_string(item).unwrap_or_default()),
+    }
+}
+
+fn is_textual_mime(mime: &str) -> bool {
+    mime.starts_with("text/")
+        || matches!(
+            mime,
+            "application/json"
+                | "application/xml"
+                | "application/yaml"
+                | "application/x-yaml"
+                | "application/toml"
+                | "application/javascript"
+                | "application/typescript"
+                | "application/x-sh"
+        )
+        || mime.ends_with("+json")
+        || mime.ends_with("+xml")
+}
+
+/// Directory for this process's saved blobs, so they can be removed on exit
+/// without touching other running instances. Stored tool results keep
+/// pointing here, so switching or reopening sessions leaves it in place.
+fn blob_dir() -> PathBuf {
+    std::env::temp_dir()
+        .join("minmax-code")
+        .join("mcp")
+        .join(std::process::id().to_string())
+}
+
+/// Delete the images and binary resources saved for tool results.
+pub fn remove_blobs() {
+    let _ = std::fs::remove_dir_all(blob_dir());
+}
+
+/// Decode base64 data and write it to a temp file. Returns the path and size.
+fn save_blob(server_name: &str, data: &str, mime: &str) -> Result<(PathBuf, usize)> {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(data.as_bytes())?;
+    let dir = blob_dir();
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "{}-{}.{}",
+        server_name,
+        uuid::Uuid::new_v4(),
+        extension_for_mime(mime)
+    ));
+    std::fs::write(&path, &bytes)?;
+    Ok((path, bytes.len()))
+}
+
+fn extension_for_mime(mime: &str) -> &'static str {
+    match mime {
+        "image/png" => "png",
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        "audio/wav" | "audio/x-wav" => "wav",
+        "audio/mpeg" => "mp3",
+        "audio/ogg" => "ogg",
+        "application/pdf" => "pdf",
+        "application/zip" => "zip",
+        _ => "bin",
     }
 }
 
@@ -368,7 +553,7 @@ mod tests {
     #[test]
     fn get_tool_definitions_empty() {
         let manager = McpManager::new();
-        assert!(manager.get_tool_definitions().is_empty());
+        assert!(manager.get_tool_definitions(Mode::Builder).is_empty());
     }
 
     #[test]
@@ -381,15 +566,144 @@ mod tests {
                 tool_name: "read".to_string(),
                 description: "Read a resource".to_string(),
                 input_schema: serde_json::json!({"type": "object"}),
+                read_only: true,
+                destructive: false,
             },
         );
 
         assert!(manager.is_mcp_tool("mcp__myserver__read"));
         assert!(!manager.is_mcp_tool("mcp__other__read"));
 
-        let defs = manager.get_tool_definitions();
+        let defs = manager.get_tool_definitions(Mode::Builder);
         assert_eq!(defs.len(), 1);
         let name = defs[0]["function"]["name"].as_str().unwrap();
         assert_eq!(name, "mcp__myserver__read");
     }
+
+    fn manager_with_write_tool() -> McpManager {
+        let mut manager = McpManager::new();
+        for (name, read_only) in [("read", true), ("write", false)] {
+            manager.tool_map.insert(
+                format!("mcp__srv_{}", name),
+                McpToolInfo {
+                    server_name: "srv".to_string(),
+                    tool_name: name.to_string(),
+                    description: String::new(),
+                    input_schema: serde_json::json!({"type": "object"}),
+                    read_only,
+                    destructive: !read_only,
+                },
+            );
+        }
+        manager
+    }
+
+    #[test]
+    fn plan_mode_hides_write_capable_tools() {
+        let manager = manager_with_write_tool();
+        let plan = manager.get_tool_definitions(Mode::Plan);
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0]["function"]["name"], "mcp__srv_read");
+        assert_eq!(manager.get_tool_definitions(Mode::Builder).len(), 2);
+        assert!(manager.is_read_only("mcp__srv_read"));
+        assert!(!manager.is_read_only("mcp__srv_write"));
+    }
+
+    #[tokio::test]
+    async fn plan_mode_refuses_write_capable_calls() {
+        let manager = manager_with_write_tool();
+        let result = manager
+            .call_tool("mcp__srv_write", serde_json::json!({}), Mode::Plan)
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(result.result.contains("not available in PLAN mode"));
+    }
+
+    #[test]
+    fn read_only_classification() {
+        let config = McpServerConfig {
+            command: "x".to_string(),
+            read_only_tools: vec!["forced".to_string()],
+            ..Default::default()
+        };
+        let read_only = serde_json::json!({"readOnlyHint": true});
+        let conflicting = serde_json::json!({"readOnlyHint": true, "destructiveHint": true});
+        let destructive = serde_json::json!({"destructiveHint": true});
+
+        assert!(is_read_only_tool(&config, "search", Some(&read_only)));
+        assert!(!is_read_only_tool(&config, "search", Some(&conflicting)));
+        assert!(!is_read_only_tool(&config, "search", Some(&destructive)));
+        assert!(!is_read_only_tool(&config, "search", None));
+        assert!(is_read_only_tool(&config, "forced", Some(&destructive)));
+    }
+
+    #[test]
+    fn call_result_joins_text_items() {
+        let result = serde_json::json!({
+            "content": [
+                {"type": "text", "text": "first"},
+                {"type": "text", "text": "second"}
+            ]
+        });
+        let out = format_call_result("srv", &result);
+        assert_eq!(out.result, "first\nsecond");
+        assert!(!out.is_error);
+    }
+
+    #[test]
+    fn call_result_maps_is_error() {
+        let result = serde_json::json!({
+            "content": [{"type": "text", "text": "file not found"}],
+            "isError": true
+        });
+        let out = format_call_result("srv", &result);
+        assert_eq!(out.result, "Error: file not found");
+        assert!(out.is_error);
+    }
+
+    #[test]
+    fn call_result_saves_images_to_temp_files() {
+        // "hello" in base64
+        let result = serde_json::json!({
+            "content": [{"type": "image", "data": "aGVsbG8=", "mimeType": "image/png"}]
+        });
+        let out = format_call_result("srv", &result);
+        assert!(out.result.starts_with("[image: "));
+        assert!(out.result.contains("image/png, 5 bytes"));
+
+        let path = out.result.trim_start_matches("[image: ").split(" (").next().unwrap();
+        assert!(path.ends_with(".png"));
+        assert_eq!(std::fs::read(path).unwrap(), b"hello");
+        remove_blobs();
+        assert!(!std::path::Path::new(path).exists());
+    }
+
+    #[test]
+    fn call_result_inlines_textual_resources() {
+        let result = serde_json::json!({
+            "content": [
+                {"type": "resource", "resource": {"uri": "file:///a.txt", "text": "inline text"}},
+                {"type": "resource", "resource": {"uri": "file:///b.json", "mimeType": "application/json", "blob": "eyJhIjoxfQ=="}}
+            ]
+        });
+        let out = format_call_result("srv", &result);
+        assert!(out.result.contains("[resource: file:///a.txt]\ninline text"));
+        assert!(out.result.contains("[resource: file:///b.json]\n{\"a\":1}"));
+    }
+
+    #[test]
+    fn call_result_passes_structured_content() {
+        let result = serde_json::json!({
+            "content": [{"type": "text", "text": "summary"}],
+            "structuredContent": {"count": 3}
+        });
+        let out = format_call_result("srv", &result);
+        assert!(out.result.starts_with("summary\nStructured result:\n"));
+        assert!(out.result.contains("\"count\": 3"));
+
+        let only_structured = serde_json::json!({ "structuredContent": {"ok": true} });
+        let out = format_call_result("srv", &only_structured);
+        assert_eq!(serde_json::from_str::<Value>(&out.result).unwrap()["ok"], true);
+    }
 }
@@ -0,0 +1,359 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::core::Mode;
+use crate::tools;
+
+/// Built-in tools exposed by `minmax-code mcp-serve`.
+pub const SERVED_TOOLS: &[&str] = &["read_file", "grep", "glob", "edit_file", "bash"];
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+// JSON-RPC error codes
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const PARSE_ERROR: i64 = -32700;
+
+/// How a message was framed on the wire. Replies use the same framing as the request.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Framing {
+    ContentLength,
+    Newline,
+}
+
+// ── MCP Server ─────────────────────────────────────────────────────────
+
+/// MCP stdio server that exposes the built-in tool layer to other agents and editors.
+pub struct McpServer {
+    mode: Mode,
+}
+
+impl McpServer {
+    /// Create a server. In `Mode::Plan` only read-only tools are listed and callable.
+    pub fn new(mode: Mode) -> Self {
+        Self { mode }
+    }
+
+    /// MCP tool descriptors, using the same schemas as `tools::get_tool_definitions`.
+    pub fn list_tools(&self) -> Vec<Value> {
+        tools::get_tool_definitions(self.mode)
+            .into_iter()
+            .filter_map(|def| {
+                let function = def.get("function")?;
+                let name = function.get("name")?.as_str()?;
+                if !SERVED_TOOLS.contains(&name) {
+                    return None;
+                }
+                Some(serde_json::json!({
+                    "name": name,
+                    "description": function.get("description").cloned().unwrap_or(Value::Null),
+                    "inputSchema": function.get("parameters").cloned().unwrap_or(serde_json::json!({"type": "object"})),
+                }))
+            })
+            .collect()
+    }
+
+    fn is_listed(&self, name: &str) -> bool {
+        self.list_tools()
+            .iter()
+            .any(|t| t.get("name").and_then(|n| n.as_str()) == Some(name))
+    }
+
+    /// Handle one JSON-RPC message. Returns `None` for notifications.
+    pub async fn handle_message(&self, msg: &Value) -> Option<Value> {
+        let id = msg.get("id").cloned()?;
+        let method = msg.get("method").and_then(|m| m.as_str()).unwrap_or("");
+        let params = msg.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => Ok(serde_json::json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": { "listChanged": false } },
+                "serverInfo": {
+                    "name": "minmax-code",
+                    "version": env!("CARGO_PKG_VERSION")
+                }
+            })),
+            "ping" | "shutdown" => Ok(serde_json::json!({})),
+            "tools/list" => Ok(serde_json::json!({ "tools": self.list_tools() })),
+            "tools/call" => self.call_tool(&params).await,
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    async fn call_tool(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(|n| n.as_str())
+            .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+
+        if !self.is_listed(name) {
+            let reason = if SERVED_TOOLS.contains(&name) {
+                format!("Tool \"{}\" is not available in PLAN mode", name)
+            } else {
+                format!("Unknown tool: {}", name)
+            };
+            return Err((INVALID_PARAMS, reason));
+        }
+
+        let args = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or(serde_json::json!({}));
+        let result = tools::execute_tool(name, args, self.mode).await;
+
+        Ok(serde_json::json!({
+            "content": [{ "type": "text", "text": result.result }],
+            "isError": result.is_error
+        }))
+    }
+
+    /// Serve requests on stdin/stdout until stdin is closed.
+    pub async fn run_stdio(&self) -> Result<()> {
+        self.serve(BufReader::new(tokio::io::stdin()), tokio::io::stdout())
+            .await
+    }
+
+    /// Serve requests from `reader`, writing replies to `writer`, until EOF.
+    pub async fn serve<R, W>(&self, mut reader: R, mut writer: W) -> Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        while let Some((body, framing)) = read_message(&mut reader).await? {
+            let reply = match serde_json::from_str::<Value>(&body) {
+                Ok(msg) => self.handle_message(&msg).await,
+                Err(e) => Some(error_response(
+                    Value::Null,
+                    PARSE_ERROR,
+                    &format!("Parse error: {}", e),
+                )),
+            };
+            if let Some(reply) = reply {
+                write_message(&mut writer, &reply, framing).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message }
+    })
+}
+
+/// Read the next message body, accepting both Content-Length framing and
+/// newline-delimited JSON. Returns `None` on EOF.
+async fn read_message<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<(String, Framing)>> {
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        if let Some(len_str) = trimmed.strip_prefix("Content-Length:") {
+            let content_length: usize = len_str.trim().parse()?;
+
+            // Skip any remaining headers up to the blank separator line
+            loop {
+                let mut header = String::new();
+                if reader.read_line(&mut header).await? == 0 {
+                    return Err(anyhow!("Unexpected EOF reading MCP headers"));
+                }
+                if header.trim().is_empty() {
+                    break;
+                }
+            }
+
+            let mut body = vec![0u8; content_length];
+            reader.read_exact(&mut body).await?;
+            return Ok(Some((String::from_utf8_lossy(&body).to_string(), Framing::ContentLength)));
+        }
+
+        return Ok(Some((trimmed.to_string(), Framing::Newline)));
+    }
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(writer: &mut W, msg: &Value, framing: Framing) -> Result<()> {
+    let json = serde_json::to_string(msg)?;
+    match framing {
+        Framing::ContentLength => {
+            let header = format!("Content-Length: {}\r\n\r\n", json.len());
+            writer.write_all(header.as_bytes()).await?;
+            writer.write_all(json.as_bytes()).await?;
+        }
+        Framing::Newline => {
+            writer.write_all(json.as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+        }
+    }
+    writer.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_names(server: &McpServer) -> Vec<String> {
+        server
+            .list_tools()
+            .iter()
+            .filter_map(|t| t["name"].as_str().map(String::from))
+            .collect()
+    }
+
+    #[test]
+    fn builder_mode_lists_all_served_tools() {
+        let server = McpServer::new(Mode::Builder);
+        let names = tool_names(&server);
+        assert_eq!(names.len(), SERVED_TOOLS.len());
+        for name in SERVED_TOOLS {
+            assert!(names.contains(&name.to_string()));
+        }
+    }
+
+    #[test]
+    fn plan_mode_lists_only_read_only_tools() {
+        let server = McpServer::new(Mode::Plan);
+        let names = tool_names(&server);
+        assert!(names.contains(&"read_file".to_string()));
+        assert!(names.contains(&"grep".to_string()));
+        assert!(!names.contains(&"bash".to_string()));
+        assert!(!names.contains(&"edit_file".to_string()));
+    }
+
+    #[test]
+    fn tool_schema_matches_definitions() {
+        let server = McpServer::new(Mode::Builder);
+        let read = server
+            .list_tools()
+            .into_iter()
+            .find(|t| t["name"] == "read_file")
+            .unwrap();
+        let def = tools::read_file::definition();
+        assert_eq!(read["inputSchema"], def["function"]["parameters"]);
+    }
+
+    #[tokio::test]
+    async fn notifications_get_no_reply() {
+        let server = McpServer::new(Mode::Builder);
+        let msg = serde_json::json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        assert!(server.handle_message(&msg).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn plan_mode_rejects_write_tools() {
+        let server = McpServer::new(Mode::Plan);
+        let msg = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "tools/call",
+            "params": { "name": "bash", "arguments": { "command": "echo hi" } }
+        });
+        let reply = server.handle_message(&msg).await.unwrap();
+        assert_eq!(reply["error"]["code"], INVALID_PARAMS);
+        assert!(reply["error"]["message"].as_str().unwrap().contains("PLAN mode"));
+    }
+
+    #[tokio::test]
+    async fn call_tool_returns_text_content() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("hello.txt");
+        std::fs::write(&path, "hello\nworld").unwrap();
+
+        let server = McpServer::new(Mode::Plan);
+        let msg = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": { "name": "read_file", "arguments": { "path": path.to_string_lossy() } }
+        });
+        let reply = server.handle_message(&msg).await.unwrap();
+        assert_eq!(reply["result"]["isError"], false);
+        assert_eq!(reply["result"]["content"][0]["text"], "1\thello\n2\tworld");
+    }
+
+    #[tokio::test]
+    async fn call_tool_output_starting_with_error_is_not_a_failure() {
+        // glob skips dotfiles, and TempDir names start with a dot
+        let dir = tempfile::TempDir::new().unwrap();
+        let logs = dir.path().join("logs");
+        std::fs::create_dir(&logs).unwrap();
+        std::fs::write(logs.join("Errors.log"), "").unwrap();
+
+        let server = McpServer::new(Mode::Plan);
+        let msg = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "tools/call",
+            "params": { "name": "glob", "arguments": { "pattern": "*.log", "cwd": logs.to_string_lossy() } }
+        });
+        let reply = server.handle_message(&msg).await.unwrap();
+        assert_eq!(reply["result"]["content"][0]["text"], "Errors.log");
+        assert_eq!(reply["result"]["isError"], false);
+    }
+
+    #[tokio::test]
+    async fn call_tool_failure_sets_is_error() {
+        // Reading a directory fails with "Error reading file", not an "Error:" prefix
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path();
+
+        let server = McpServer::new(Mode::Plan);
+        let msg = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 5,
+            "method": "tools/call",
+            "params": { "name": "read_file", "arguments": { "path": path.to_string_lossy() } }
+        });
+        let reply = server.handle_message(&msg).await.unwrap();
+        let text = reply["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(!text.starts_with("Error:"));
+        assert_eq!(reply["result"]["isError"], true);
+    }
+
+    #[tokio::test]
+    async fn unknown_method_returns_error() {
+        let server = McpServer::new(Mode::Builder);
+        let msg = serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "resources/list"});
+        let reply = server.handle_message(&msg).await.unwrap();
+        assert_eq!(reply["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn serve_replies_with_request_framing() {
+        let server = McpServer::new(Mode::Builder);
+        let init = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#;
+        let list = r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#;
+        let input = format!("Content-Length: {}\r\n\r\n{}{}\n", init.len(), init, list);
+
+        let mut output = Vec::new();
+        server.serve(input.as_bytes(), &mut output).await.unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        // First reply is Content-Length framed, second is a single JSON line
+        let (header, rest) = output.split_once("\r\n\r\n").unwrap();
+        let len: usize = header.strip_prefix("Content-Length: ").unwrap().parse().unwrap();
+        let first: Value = serde_json::from_str(&rest[..len]).unwrap();
+        assert_eq!(first["id"], 1);
+        assert_eq!(first["result"]["protocolVersion"], PROTOCOL_VERSION);
+
+        let second: Value = serde_json::from_str(rest[len..].trim_end_matches('\n')).unwrap();
+        assert_eq!(second["id"], 2);
+        assert_eq!(second["result"]["tools"].as_array().unwrap().len(), SERVED_TOOLS.len());
+    }
+}
@@ -1,10 +1,18 @@
 pub mod api;
 pub mod chat;
 pub mod commands;
+pub mod compaction;
+pub mod export;
+pub mod instructions;
 pub mod mcp;
+pub mod mcp_server;
 pub mod parser;
+pub mod project;
 pub mod session;
+pub mod title;
+pub mod tokens;
 pub mod update;
+pub mod usage;
 
 /// Operating mode for the CLI.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
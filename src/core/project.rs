@@ -0,0 +1,128 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where a session was started: working directory plus enclosing git
+/// repository, used to group sessions by project.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectInfo {
+    pub cwd: String,
+    pub git_root: Option<String>,
+    pub git_branch: Option<String>,
+}
+
+impl ProjectInfo {
+    /// Detect the project for the current working directory.
+    pub fn current() -> Self {
+        Self::detect(&std::env::current_dir().unwrap_or_default())
+    }
+
+    /// Detect the project for `dir` by looking for an enclosing `.git`.
+    pub fn detect(dir: &Path) -> Self {
+        let git_root = find_git_root(dir);
+        let git_branch = git_root.as_deref().and_then(read_branch);
+        Self {
+            cwd: dir.to_string_lossy().to_string(),
+            git_root: git_root.map(|p| p.to_string_lossy().to_string()),
+            git_branch,
+        }
+    }
+
+    /// Sessions belong to the same project when they share a git root, or
+    /// the same directory outside of git.
+    pub fn key(&self) -> &str {
+        self.git_root.as_deref().unwrap_or(&self.cwd)
+    }
+
+    /// Short display name: the repository or directory name.
+    pub fn name(&self) -> String {
+        Path::new(self.key())
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.key().to_string())
+    }
+}
+
+pub(crate) fn find_git_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|d| d.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Current branch from `HEAD`, or a short commit id when detached.
+fn read_branch(root: &Path) -> Option<String> {
+    let git = root.join(".git");
+    // Worktrees and submodules use a `.git` file pointing at the real git dir
+    let git_dir = if git.is_file() {
+        let content = fs::read_to_string(&git).ok()?;
+        let target = content.trim().strip_prefix("gitdir:")?.trim();
+        root.join(target)
+    } else {
+        git
+    };
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    match head.strip_prefix("ref: ") {
+        Some(reference) => Some(
+            reference
+                .strip_prefix("refs/heads/")
+                .unwrap_or(reference)
+                .to_string(),
+        ),
+        None => Some(head.chars().take(8).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_git_root_and_branch() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join(".git/HEAD"), "ref: refs/heads/feature/login\n").unwrap();
+        let nested = root.join("src/core");
+        fs::create_dir_all(&nested).unwrap();
+
+        let info = ProjectInfo::detect(&nested);
+        assert_eq!(info.cwd, nested.to_string_lossy());
+        assert_eq!(info.git_root.as_deref(), Some(root.to_string_lossy().as_ref()));
+        assert_eq!(info.git_branch.as_deref(), Some("feature/login"));
+        assert_eq!(info.key(), root.to_string_lossy());
+    }
+
+    #[test]
+    fn detached_head_uses_short_commit() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".git/HEAD"), "0123456789abcdef0123\n").unwrap();
+        let info = ProjectInfo::detect(dir.path());
+        assert_eq!(info.git_branch.as_deref(), Some("01234567"));
+    }
+
+    #[test]
+    fn worktree_git_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let real = dir.path().join("main/.git/worktrees/wt");
+        fs::create_dir_all(&real).unwrap();
+        fs::write(real.join("HEAD"), "ref: refs/heads/hotfix\n").unwrap();
+        let wt = dir.path().join("wt");
+        fs::create_dir_all(&wt).unwrap();
+        fs::write(wt.join(".git"), format!("gitdir: {}\n", real.display())).unwrap();
+
+        let info = ProjectInfo::detect(&wt);
+        assert_eq!(info.git_branch.as_deref(), Some("hotfix"));
+    }
+
+    #[test]
+    fn outside_git_keys_on_cwd() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let info = ProjectInfo::detect(dir.path());
+        // The temp dir could sit inside a repository; only check the fallback
+        if info.git_root.is_none() {
+            assert_eq!(info.key(), info.cwd);
+            assert!(info.git_branch.is_none());
+        }
+    }
+}
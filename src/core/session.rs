@@ -1,20 +1,82 @@
 use anyhow::Result;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
 use rusqlite::{params, Connection};
 use std::fs;
+use std::io::{Read, Write};
 use std::path::PathBuf;
 use std::sync::Mutex;
 
 use crate::config::settings::config_dir;
+use crate::core::project::ProjectInfo;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Session {
     pub id: String,
     pub name: String,
     pub model: String,
     pub created_at: String,
     pub updated_at: String,
+    /// Session this one was forked from, if any.
+    pub parent_id: Option<String>,
+    /// Where the session was started; `None` for sessions from before this
+    /// was recorded.
+    pub cwd: Option<String>,
+    pub git_root: Option<String>,
+    pub git_branch: Option<String>,
+    /// Pinned sessions are listed first.
+    pub pinned: bool,
+}
+
+/// Name given to new sessions until they are titled or renamed.
+pub const DEFAULT_SESSION_NAME: &str = "New Session";
+
+impl Session {
+    /// Repository or directory name the session was started in.
+    pub fn project_name(&self) -> Option<String> {
+        let path = self.git_root.as_deref().or(self.cwd.as_deref())?;
+        std::path::Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+    }
+}
+
+const SESSION_COLUMNS: &str =
+    "id, name, model, created_at, updated_at, parent_id, cwd, git_root, git_branch, pinned";
+
+fn session_from_row(row: &rusqlite::Row) -> rusqlite::Result<Session> {
+    Ok(Session {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        model: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+        parent_id: row.get(5)?,
+        cwd: row.get(6)?,
+        git_root: row.get(7)?,
+        git_branch: row.get(8)?,
+        pinned: row.get(9)?,
+    })
+}
+
+/// A message matching a full-text search, with its session for display.
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub message_id: i64,
+    pub session_id: String,
+    pub session_name: String,
+    pub role: String,
+    /// Excerpt around the match; matched terms are wrapped in
+    /// `MATCH_START` / `MATCH_END`.
+    pub snippet: String,
+    pub created_at: String,
 }
 
+/// Markers around matched terms in `SearchHit::snippet`.
+pub const MATCH_START: char = '\u{1}';
+pub const MATCH_END: char = '\u{2}';
+
 #[derive(Debug, Clone)]
 pub struct StoredMessage {
     pub id: i64,
@@ -24,11 +86,56 @@ pub struct StoredMessage {
     pub tool_calls: Option<String>,
     pub tool_call_id: Option<String>,
     pub name: Option<String>,
+    pub reasoning: Option<String>,
+    /// JSON `MessageMeta`: tool status and diff data, compression markers.
+    pub meta: Option<String>,
     pub created_at: String,
 }
 
+/// Token usage and latency of model requests: one request when recorded,
+/// or the sum over `requests` requests when read back from a report.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageStats {
+    pub model: String,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Part of `completion_tokens` spent on reasoning.
+    pub reasoning_tokens: u64,
+    pub latency_ms: u64,
+}
+
+/// Usage per model for the current session, today and the last 30 days.
+#[derive(Debug, Clone, Default)]
+pub struct UsageReport {
+    pub session: Vec<UsageStats>,
+    pub today: Vec<UsageStats>,
+    pub last_30_days: Vec<UsageStats>,
+}
+
+/// Storage used by one session, for the `db stats` report.
+#[derive(Debug, Clone)]
+pub struct SessionSize {
+    pub id: String,
+    pub name: String,
+    pub pinned: bool,
+    pub messages: u64,
+    /// Bytes of stored message content, tool calls and reasoning.
+    pub bytes: u64,
+}
+
+/// Characters of a compressed tool result kept as plain text, so it still
+/// shows up in search.
+const COMPRESSED_PREVIEW_CHARS: usize = 1000;
+
+/// `updated_at` as a datetime: older rows store Unix seconds.
+const LAST_ACTIVE_SQL: &str = "CASE WHEN updated_at NOT LIKE '%-%' \
+     THEN datetime(updated_at, 'unixepoch') ELSE updated_at END";
+
 pub struct SessionStore {
     conn: Mutex<Connection>,
+    /// Tool results larger than this many bytes are stored compressed.
+    compress_tool_results_over: Option<usize>,
 }
 
 impl SessionStore {
@@ -46,48 +153,35 @@ impl SessionStore {
     pub fn open_at(path: PathBuf) -> Result<Self> {
         let conn = Connection::open(path)?;
         conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON;")?;
+        init_schema(&conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            compress_tool_results_over: None,
+        })
+    }
 
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS sessions (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                model TEXT NOT NULL,
-                created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
-            );
-
-            CREATE TABLE IF NOT EXISTS messages (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                session_id TEXT NOT NULL,
-                role TEXT NOT NULL,
-                content TEXT NOT NULL DEFAULT '',
-                tool_calls TEXT,
-                tool_call_id TEXT,
-                name TEXT,
-                created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
-            );",
-        )?;
-
-        Ok(Self { conn: Mutex::new(conn) })
+    /// Store tool results larger than `threshold` bytes compressed.
+    pub fn with_tool_result_compression(mut self, threshold: Option<usize>) -> Self {
+        self.compress_tool_results_over = threshold.filter(|t| *t > 0);
+        self
     }
 
-    pub fn create_session(&self, model: &str) -> Result<Session> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
-        let id = uuid::Uuid::new_v4().to_string();
-        let now = chrono_now();
-        let name = "New Session";
-        conn.execute(
-            "INSERT INTO sessions (id, name, model, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![id, name, model, now, now],
-        )?;
-        Ok(Session {
-            id,
-            name: name.to_string(),
+    pub fn create_session(&self, model: &str, project: Option<&ProjectInfo>) -> Result<Session> {
+        let session = Session {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: DEFAULT_SESSION_NAME.to_string(),
             model: model.to_string(),
-            created_at: now.clone(),
-            updated_at: now,
-        })
+            created_at: chrono_now(),
+            updated_at: chrono_now(),
+            parent_id: None,
+            cwd: project.map(|p| p.cwd.clone()),
+            git_root: project.and_then(|p| p.git_root.clone()),
+            git_branch: project.and_then(|p| p.git_branch.clone()),
+            pinned: false,
+        };
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        insert_session(&conn, &session)?;
+        Ok(session)
     }
 
     pub fn rename_session(&self, id: &str, name: &str) -> Result<()> {
@@ -99,24 +193,116 @@ impl SessionStore {
         Ok(())
     }
 
+    /// Pin or unpin a session. Does not count as activity, so the session
+    /// keeps its place among other sessions when unpinned.
+    pub fn set_session_pinned(&self, id: &str, pinned: bool) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        conn.execute(
+            "UPDATE sessions SET pinned = ?1 WHERE id = ?2",
+            params![pinned, id],
+        )?;
+        Ok(())
+    }
+
+    /// All sessions, pinned first, then most recent first.
     pub fn list_sessions(&self) -> Result<Vec<Session>> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
-        let mut stmt = conn
-            .prepare("SELECT id, name, model, created_at, updated_at FROM sessions ORDER BY updated_at DESC")?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM sessions ORDER BY pinned DESC, {} DESC",
+            SESSION_COLUMNS, LAST_ACTIVE_SQL
+        ))?;
         let sessions = stmt
-            .query_map([], |row| {
-                Ok(Session {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    model: row.get(2)?,
-                    created_at: row.get(3)?,
-                    updated_at: row.get(4)?,
-                })
-            })?
+            .query_map([], session_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(sessions)
+    }
+
+    /// The session active most recently, pinned or not.
+    pub fn most_recent_session(&self) -> Result<Option<Session>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM sessions ORDER BY {} DESC LIMIT 1",
+            SESSION_COLUMNS, LAST_ACTIVE_SQL
+        ))?;
+        let mut rows = stmt.query_map([], session_from_row)?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Sessions started in the given project, pinned first, then most recent
+    /// first. Sessions from before projects were recorded have no project and
+    /// are listed everywhere, so they stay reachable without `--all`.
+    pub fn list_project_sessions(&self, project: &ProjectInfo) -> Result<Vec<Session>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM sessions
+             WHERE git_root = ?1 OR (git_root IS NULL AND (cwd = ?1 OR cwd IS NULL))
+             ORDER BY pinned DESC, {} DESC",
+            SESSION_COLUMNS, LAST_ACTIVE_SQL
+        ))?;
+        let sessions = stmt
+            .query_map(params![project.key()], session_from_row)?
             .collect::<std::result::Result<Vec<_>, _>>()?;
         Ok(sessions)
     }
 
+    pub fn get_session(&self, id: &str) -> Result<Option<Session>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM sessions WHERE id = ?1", SESSION_COLUMNS))?;
+        let mut rows = stmt.query_map(params![id], session_from_row)?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Find a session by full id or unique id prefix.
+    pub fn find_session(&self, id_or_prefix: &str) -> Result<Option<Session>> {
+        if let Some(session) = self.get_session(id_or_prefix)? {
+            return Ok(Some(session));
+        }
+        let mut matches: Vec<Session> = self
+            .list_sessions()?
+            .into_iter()
+            .filter(|s| s.id.starts_with(id_or_prefix))
+            .collect();
+        match matches.len() {
+            0 => Ok(None),
+            1 => Ok(matches.pop()),
+            n => Err(anyhow::anyhow!(
+                "Session id prefix \"{}\" is ambiguous ({} matches)",
+                id_or_prefix,
+                n
+            )),
+        }
+    }
+
+    /// Fork a session into a new one that starts with a copy of its messages.
+    /// With `before_message_id`, only messages saved before that one are copied,
+    /// so the conversation can be retried from that point.
+    pub fn fork_session(&self, id: &str, before_message_id: Option<i64>) -> Result<Session> {
+        let parent = self
+            .get_session(id)?
+            .ok_or_else(|| anyhow::anyhow!("Session {} not found", id))?;
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        let tx = conn.transaction()?;
+
+        let fork = Session {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: format!("{} (fork)", parent.name),
+            created_at: chrono_now(),
+            updated_at: chrono_now(),
+            parent_id: Some(parent.id.clone()),
+            pinned: false,
+            ..parent.clone()
+        };
+        insert_session(&tx, &fork)?;
+        tx.execute(
+            "INSERT INTO messages (session_id, role, content, content_z, tool_calls, tool_call_id, name, reasoning, meta, created_at)
+             SELECT ?1, role, content, content_z, tool_calls, tool_call_id, name, reasoning, meta, created_at
+             FROM messages WHERE session_id = ?2 AND id < ?3 ORDER BY id ASC",
+            params![fork.id, parent.id, before_message_id.unwrap_or(i64::MAX)],
+        )?;
+        tx.commit()?;
+        Ok(fork)
+    }
+
     pub fn delete_session(&self, id: &str) -> Result<()> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
         conn.execute("DELETE FROM sessions WHERE id = ?1", params![id])?;
@@ -131,27 +317,192 @@ impl SessionStore {
         tool_calls: Option<&str>,
         tool_call_id: Option<&str>,
         name: Option<&str>,
-    ) -> Result<()> {
+    ) -> Result<i64> {
+        let (stored, compressed) = self.stored_content(role, content)?;
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
         conn.execute(
-            "INSERT INTO messages (session_id, role, content, tool_calls, tool_call_id, name) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![session_id, role, content, tool_calls, tool_call_id, name],
+            "INSERT INTO messages (session_id, role, content, content_z, tool_calls, tool_call_id, name)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![session_id, role, stored, compressed, tool_calls, tool_call_id, name],
         )?;
+        let id = conn.last_insert_rowid();
         conn.execute(
             "UPDATE sessions SET updated_at = datetime('now') WHERE id = ?1",
             params![session_id],
         )?;
+        Ok(id)
+    }
+
+    /// Attach UI metadata (JSON) to a saved message.
+    pub fn set_message_meta(&self, message_id: i64, meta: &str) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        conn.execute(
+            "UPDATE messages SET meta = ?1 WHERE id = ?2",
+            params![meta, message_id],
+        )?;
+        Ok(())
+    }
+
+    /// Attach the model's reasoning to a saved assistant message.
+    pub fn set_message_reasoning(&self, message_id: i64, reasoning: &str) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        conn.execute(
+            "UPDATE messages SET reasoning = ?1 WHERE id = ?2",
+            params![reasoning, message_id],
+        )?;
+        Ok(())
+    }
+
+    /// Content to store for a message and, for large tool results, its
+    /// compressed form.
+    fn stored_content(&self, role: &str, content: &str) -> Result<(String, Option<Vec<u8>>)> {
+        match self.compress_tool_results_over {
+            Some(threshold) if role == "tool" && content.len() > threshold => {
+                Ok((preview(content), Some(compress(content)?)))
+            }
+            _ => Ok((content.to_string(), None)),
+        }
+    }
+
+    /// Insert a session and its messages as-is, keeping names and timestamps.
+    /// Message ids are reassigned; large tool results are compressed as when
+    /// they are saved.
+    pub fn import_session(&self, session: &Session, messages: &[StoredMessage]) -> Result<()> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        let tx = conn.transaction()?;
+        insert_session(&tx, session)?;
+        for m in messages {
+            let (stored, compressed) = self.stored_content(&m.role, &m.content)?;
+            tx.execute(
+                "INSERT INTO messages (session_id, role, content, content_z, tool_calls, tool_call_id, name, reasoning, meta, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    session.id,
+                    m.role,
+                    stored,
+                    compressed,
+                    m.tool_calls,
+                    m.tool_call_id,
+                    m.name,
+                    m.reasoning,
+                    m.meta,
+                    m.created_at
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Record the token usage of one model request.
+    pub fn record_usage(&self, session_id: &str, message_id: Option<i64>, usage: &UsageStats) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        conn.execute(
+            "INSERT INTO usage (session_id, message_id, model, prompt_tokens, completion_tokens, reasoning_tokens, latency_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                session_id,
+                message_id,
+                usage.model,
+                usage.prompt_tokens as i64,
+                usage.completion_tokens as i64,
+                usage.reasoning_tokens as i64,
+                usage.latency_ms as i64
+            ],
+        )?;
         Ok(())
     }
 
+    /// Usage per model for a session, since local midnight, and over the
+    /// last 30 days.
+    pub fn usage_report(&self, session_id: &str) -> Result<UsageReport> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        let query = |filter: &str, args: &[&dyn rusqlite::ToSql]| -> Result<Vec<UsageStats>> {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM usage WHERE {} GROUP BY model ORDER BY model",
+                USAGE_SUMS, filter
+            ))?;
+            let rows = stmt
+                .query_map(args, |row| usage_from_row(row, 0))?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            Ok(rows)
+        };
+        Ok(UsageReport {
+            session: query("session_id = ?1", &[&session_id])?,
+            today: query("created_at >= datetime('now', 'localtime', 'start of day', 'utc')", &[])?,
+            last_30_days: query("created_at >= datetime('now', '-30 days')", &[])?,
+        })
+    }
+
+    /// Usage per session and model, for showing what each session cost.
+    pub fn usage_by_session(&self) -> Result<Vec<(String, UsageStats)>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT session_id, {} FROM usage GROUP BY session_id, model",
+            USAGE_SUMS
+        ))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, usage_from_row(row, 1)?)))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Full-text search over all message content, best matches first.
+    pub fn search_messages(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        let Some(fts) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        let mut stmt = conn.prepare(
+            "SELECT m.id, m.session_id, s.name, m.role, m.created_at,
+                    snippet(messages_fts, 0, char(1), char(2), '…', 12)
+             FROM messages_fts
+             JOIN messages m ON m.id = messages_fts.rowid
+             JOIN sessions s ON s.id = m.session_id
+             WHERE messages_fts MATCH ?1
+             ORDER BY rank
+             LIMIT ?2",
+        )?;
+        let hits = stmt
+            .query_map(params![fts, limit as i64], |row| {
+                Ok(SearchHit {
+                    message_id: row.get(0)?,
+                    session_id: row.get(1)?,
+                    session_name: row.get(2)?,
+                    role: row.get(3)?,
+                    created_at: row.get(4)?,
+                    snippet: row.get(5)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(hits)
+    }
+
+    /// Distinct prompts the user sent, across all sessions, newest first.
+    pub fn recent_prompts(&self, limit: usize) -> Result<Vec<String>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        let mut stmt = conn.prepare(
+            "SELECT content FROM messages
+             WHERE role = 'user' AND content != ''
+             GROUP BY content
+             ORDER BY MAX(id) DESC
+             LIMIT ?1",
+        )?;
+        let prompts = stmt
+            .query_map(params![limit as i64], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<String>, _>>()?;
+        Ok(prompts)
+    }
+
     pub fn get_session_messages(&self, session_id: &str) -> Result<Vec<StoredMessage>> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
         let mut stmt = conn.prepare(
-            "SELECT id, session_id, role, content, tool_calls, tool_call_id, name, created_at FROM messages WHERE session_id = ?1 ORDER BY id ASC",
+            "SELECT id, session_id, role, content, tool_calls, tool_call_id, name, reasoning, meta, created_at, content_z
+             FROM messages WHERE session_id = ?1 ORDER BY id ASC",
         )?;
-        let messages = stmt
+        let rows = stmt
             .query_map(params![session_id], |row| {
-                Ok(StoredMessage {
+                let message = StoredMessage {
                     id: row.get(0)?,
                     session_id: row.get(1)?,
                     role: row.get(2)?,
@@ -159,14 +510,376 @@ impl SessionStore {
                     tool_calls: row.get(4)?,
                     tool_call_id: row.get(5)?,
                     name: row.get(6)?,
-                    created_at: row.get(7)?,
+                    reasoning: row.get(7)?,
+                    meta: row.get(8)?,
+                    created_at: row.get(9)?,
+                };
+                Ok((message, row.get::<_, Option<Vec<u8>>>(10)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        rows.into_iter()
+            .map(|(mut message, compressed)| {
+                if let Some(data) = compressed {
+                    message.content = decompress(&data)?;
+                }
+                Ok(message)
+            })
+            .collect()
+    }
+
+    // ── Maintenance ─────────────────────────────────────────────────────
+
+    /// Delete unpinned sessions inactive for more than `max_age_days`, then
+    /// all but the newest `max_sessions` unpinned ones. `keep` (the open
+    /// session) is never deleted. Returns how many sessions were deleted.
+    pub fn prune_sessions(
+        &self,
+        max_age_days: Option<u32>,
+        max_sessions: Option<usize>,
+        keep: Option<&str>,
+    ) -> Result<usize> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        let tx = conn.transaction()?;
+        let mut deleted = 0;
+        if let Some(days) = max_age_days {
+            deleted += tx.execute(
+                &format!(
+                    "DELETE FROM sessions WHERE pinned = 0 AND id IS NOT ?1
+                     AND {} < datetime('now', ?2)",
+                    LAST_ACTIVE_SQL
+                ),
+                params![keep, format!("-{} days", days)],
+            )?;
+        }
+        if let Some(max) = max_sessions {
+            deleted += tx.execute(
+                &format!(
+                    "DELETE FROM sessions WHERE pinned = 0 AND id IS NOT ?1 AND id NOT IN (
+                        SELECT id FROM sessions WHERE pinned = 0
+                        ORDER BY {} DESC LIMIT ?2
+                     )",
+                    LAST_ACTIVE_SQL
+                ),
+                params![keep, max as i64],
+            )?;
+        }
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    /// Compress stored tool results larger than `threshold` bytes that were
+    /// saved uncompressed. Returns how many messages were compressed.
+    pub fn compress_tool_results(&self, threshold: usize) -> Result<usize> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        let tx = conn.transaction()?;
+        let large: Vec<(i64, String)> = tx
+            .prepare(
+                "SELECT id, content FROM messages
+                 WHERE role = 'tool' AND content_z IS NULL AND length(CAST(content AS BLOB)) > ?1",
+            )?
+            .query_map(params![threshold as i64], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        for (id, content) in &large {
+            tx.execute(
+                "UPDATE messages SET content = ?1, content_z = ?2 WHERE id = ?3",
+                params![preview(content), compress(content)?, id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(large.len())
+    }
+
+    /// Storage per session, largest first.
+    pub fn session_sizes(&self) -> Result<Vec<SessionSize>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        let mut stmt = conn.prepare(
+            "SELECT s.id, s.name, s.pinned, COUNT(m.id),
+                    COALESCE(SUM(
+                        COALESCE(length(m.content_z), length(CAST(m.content AS BLOB)))
+                        + COALESCE(length(CAST(m.tool_calls AS BLOB)), 0)
+                        + COALESCE(length(CAST(m.reasoning AS BLOB)), 0)
+                    ), 0) AS bytes
+             FROM sessions s LEFT JOIN messages m ON m.session_id = s.id
+             GROUP BY s.id ORDER BY bytes DESC",
+        )?;
+        let sizes = stmt
+            .query_map([], |row| {
+                Ok(SessionSize {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    pinned: row.get(2)?,
+                    messages: row.get::<_, i64>(3)?.max(0) as u64,
+                    bytes: row.get::<_, i64>(4)?.max(0) as u64,
                 })
             })?
             .collect::<std::result::Result<Vec<_>, _>>()?;
-        Ok(messages)
+        Ok(sizes)
+    }
+
+    /// Size of the database in bytes, including free pages.
+    pub fn database_size(&self) -> Result<u64> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        let pages: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+        let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+        Ok((pages * page_size).max(0) as u64)
+    }
+
+    /// Check the database and the search index for corruption. Returns the
+    /// problems found; empty when everything is fine.
+    pub fn integrity_check(&self) -> Result<Vec<String>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        let mut problems: Vec<String> = conn
+            .prepare("PRAGMA integrity_check")?
+            .query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<String>, _>>()?
+            .into_iter()
+            .filter(|line| line != "ok")
+            .collect();
+        if let Err(e) = conn.execute_batch("INSERT INTO messages_fts(messages_fts) VALUES ('integrity-check')") {
+            problems.push(format!("search index: {}", e));
+        }
+        Ok(problems)
+    }
+
+    /// Rebuild the search index from message content.
+    pub fn rebuild_search_index(&self) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        conn.execute_batch("INSERT INTO messages_fts(messages_fts) VALUES ('rebuild')")?;
+        Ok(())
+    }
+
+    /// Reclaim space left by deleted sessions.
+    pub fn vacuum(&self) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("lock error: {}", e))?;
+        conn.execute_batch("VACUUM")?;
+        Ok(())
     }
 }
 
+fn compress(text: &str) -> Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(text.as_bytes())?;
+    Ok(encoder.finish()?)
+}
+
+fn decompress(data: &[u8]) -> Result<String> {
+    let mut text = String::new();
+    ZlibDecoder::new(data).read_to_string(&mut text)?;
+    Ok(text)
+}
+
+fn preview(text: &str) -> String {
+    text.chars().take(COMPRESSED_PREVIEW_CHARS).collect()
+}
+
+const USAGE_SUMS: &str = "model, COUNT(*), SUM(prompt_tokens), SUM(completion_tokens), \
+     SUM(reasoning_tokens), SUM(latency_ms)";
+
+fn usage_from_row(row: &rusqlite::Row, offset: usize) -> rusqlite::Result<UsageStats> {
+    let count = |i: usize| row.get::<_, i64>(offset + i).map(|v| v.max(0) as u64);
+    Ok(UsageStats {
+        model: row.get(offset)?,
+        requests: count(1)?,
+        prompt_tokens: count(2)?,
+        completion_tokens: count(3)?,
+        reasoning_tokens: count(4)?,
+        latency_ms: count(5)?,
+    })
+}
+
+fn insert_session(conn: &Connection, s: &Session) -> Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT INTO sessions ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            SESSION_COLUMNS
+        ),
+        params![
+            s.id,
+            s.name,
+            s.model,
+            s.created_at,
+            s.updated_at,
+            s.parent_id,
+            s.cwd,
+            s.git_root,
+            s.git_branch,
+            s.pinned
+        ],
+    )?;
+    Ok(())
+}
+
+/// Schema migrations, applied in order on top of the original tables.
+/// `PRAGMA user_version` records how many have run. Append new steps and
+/// never reorder old ones; each step must tolerate partially-upgraded
+/// databases, so columns are added with `add_column_if_missing`.
+const MIGRATIONS: &[fn(&Connection) -> Result<()>] = &[
+    migrate_search_index,
+    migrate_message_reasoning,
+    migrate_session_forks,
+    migrate_session_projects,
+    migrate_session_pins,
+    migrate_usage,
+    migrate_compressed_content,
+    migrate_message_meta,
+];
+
+/// Create the original tables if missing, then bring the schema up to date.
+fn init_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sessions (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            model TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS messages (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT NOT NULL,
+            role TEXT NOT NULL,
+            content TEXT NOT NULL DEFAULT '',
+            tool_calls TEXT,
+            tool_call_id TEXT,
+            name TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        );",
+    )?;
+    migrate(conn)
+}
+
+fn migrate(conn: &Connection) -> Result<()> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (i, step) in MIGRATIONS.iter().enumerate().skip(version) {
+        conn.execute_batch("BEGIN")?;
+        let result = step(conn).and_then(|_| {
+            conn.execute_batch(&format!("PRAGMA user_version = {}", i + 1))?;
+            Ok(())
+        });
+        match result {
+            Ok(()) => conn.execute_batch("COMMIT")?,
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(e.context(format!("session database migration {} failed", i + 1)));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 1: full-text index over message content, kept in sync by triggers.
+fn migrate_search_index(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+            content, content='messages', content_rowid='id'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS messages_fts_insert AFTER INSERT ON messages BEGIN
+            INSERT INTO messages_fts(rowid, content) VALUES (new.id, new.content);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS messages_fts_delete AFTER DELETE ON messages BEGIN
+            INSERT INTO messages_fts(messages_fts, rowid, content) VALUES ('delete', old.id, old.content);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS messages_fts_update AFTER UPDATE OF content ON messages BEGIN
+            INSERT INTO messages_fts(messages_fts, rowid, content) VALUES ('delete', old.id, old.content);
+            INSERT INTO messages_fts(rowid, content) VALUES (new.id, new.content);
+        END;
+
+        INSERT INTO messages_fts(messages_fts) VALUES ('rebuild');",
+    )?;
+    Ok(())
+}
+
+/// 2: model reasoning on assistant messages.
+fn migrate_message_reasoning(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "messages", "reasoning", "TEXT")
+}
+
+/// 3: forked sessions point at their parent.
+fn migrate_session_forks(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "sessions", "parent_id", "TEXT")
+}
+
+/// 4: working directory, git root and branch each session was started in.
+fn migrate_session_projects(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "sessions", "cwd", "TEXT")?;
+    add_column_if_missing(conn, "sessions", "git_root", "TEXT")?;
+    add_column_if_missing(conn, "sessions", "git_branch", "TEXT")?;
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS sessions_project ON sessions (git_root, cwd)",
+    )?;
+    Ok(())
+}
+
+/// 5: pinned sessions.
+fn migrate_session_pins(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "sessions", "pinned", "INTEGER NOT NULL DEFAULT 0")
+}
+
+/// 6: token usage and latency per model request.
+fn migrate_usage(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT NOT NULL,
+            message_id INTEGER,
+            model TEXT NOT NULL,
+            prompt_tokens INTEGER NOT NULL DEFAULT 0,
+            completion_tokens INTEGER NOT NULL DEFAULT 0,
+            reasoning_tokens INTEGER NOT NULL DEFAULT 0,
+            latency_ms INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS usage_session ON usage (session_id);
+        CREATE INDEX IF NOT EXISTS usage_created ON usage (created_at);",
+    )?;
+    Ok(())
+}
+
+/// 7: large tool results stored compressed; `content` keeps a preview.
+fn migrate_compressed_content(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "messages", "content_z", "BLOB")
+}
+
+/// 8: UI metadata per message (tool status and diffs, compression markers).
+fn migrate_message_meta(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "messages", "meta", "TEXT")
+}
+
+/// Add a column to an existing table, for databases created by older versions.
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .any(|name| name == column);
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl))?;
+    }
+    Ok(())
+}
+
+/// Turn free text into an FTS5 query: every word must match, and the last
+/// word also matches as a prefix so results update while typing.
+fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|t| t.replace('"', ""))
+        .filter(|t| !t.is_empty())
+        .map(|t| format!("\"{}\"", t))
+        .collect();
+    if terms.is_empty() {
+        return None;
+    }
+    Some(format!("{}*", terms.join(" ")))
+}
+
 fn chrono_now() -> String {
     // Simple ISO 8601 timestamp without external chrono crate
     let now = std::time::SystemTime::now()
@@ -184,34 +897,18 @@ mod tests {
         // In-memory database for testing
         let conn = Connection::open_in_memory().unwrap();
         conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON;").unwrap();
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS sessions (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                model TEXT NOT NULL,
-                created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
-            );
-            CREATE TABLE IF NOT EXISTS messages (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                session_id TEXT NOT NULL,
-                role TEXT NOT NULL,
-                content TEXT NOT NULL DEFAULT '',
-                tool_calls TEXT,
-                tool_call_id TEXT,
-                name TEXT,
-                created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
-            );"
-        ).unwrap();
-        SessionStore { conn: Mutex::new(conn) }
+        init_schema(&conn).unwrap();
+        SessionStore {
+            conn: Mutex::new(conn),
+            compress_tool_results_over: None,
+        }
     }
 
     #[test]
     fn create_and_list_sessions() {
         let store = test_store();
-        let s1 = store.create_session("MiniMax-M2.5").unwrap();
-        let s2 = store.create_session("MiniMax-M2.5-highspeed").unwrap();
+        let s1 = store.create_session("MiniMax-M2.5", None).unwrap();
+        let s2 = store.create_session("MiniMax-M2.5-highspeed", None).unwrap();
 
         // Touch s2 so it has a later updated_at
         store.rename_session(&s2.id, "Second").unwrap();
@@ -224,10 +921,24 @@ mod tests {
         assert!(ids.contains(&s2.id.as_str()));
     }
 
+    #[test]
+    fn recent_prompts_are_distinct_newest_first() {
+        let store = test_store();
+        let a = store.create_session("MiniMax-M2.5", None).unwrap();
+        let b = store.create_session("MiniMax-M2.5", None).unwrap();
+        store.save_message(&a.id, "user", "run the tests", None, None, None).unwrap();
+        store.save_message(&a.id, "assistant", "done", None, None, None).unwrap();
+        store.save_message(&b.id, "user", "fix the build", None, None, None).unwrap();
+        store.save_message(&b.id, "user", "run the tests", None, None, None).unwrap();
+
+        assert_eq!(store.recent_prompts(10).unwrap(), ["run the tests", "fix the build"]);
+        assert_eq!(store.recent_prompts(1).unwrap(), ["run the tests"]);
+    }
+
     #[test]
     fn rename_session() {
         let store = test_store();
-        let s = store.create_session("MiniMax-M2.5").unwrap();
+        let s = store.create_session("MiniMax-M2.5", None).unwrap();
         assert_eq!(s.name, "New Session");
 
         store.rename_session(&s.id, "My Chat").unwrap();
@@ -235,10 +946,142 @@ mod tests {
         assert_eq!(sessions[0].name, "My Chat");
     }
 
+    #[test]
+    fn pinned_sessions_list_first() {
+        let store = test_store();
+        let old = store.create_session("MiniMax-M2.5", None).unwrap();
+        let new = store.create_session("MiniMax-M2.5", None).unwrap();
+        store.rename_session(&new.id, "Newer").unwrap();
+
+        store.set_session_pinned(&old.id, true).unwrap();
+        let sessions = store.list_sessions().unwrap();
+        assert_eq!(sessions[0].id, old.id);
+        assert!(sessions[0].pinned);
+        assert!(!sessions[1].pinned);
+
+        // Forks start unpinned
+        let fork = store.fork_session(&old.id, None).unwrap();
+        assert!(!fork.pinned);
+
+        store.set_session_pinned(&old.id, false).unwrap();
+        assert!(!store.get_session(&old.id).unwrap().unwrap().pinned);
+    }
+
+    #[test]
+    fn usage_is_summed_per_model() {
+        let store = test_store();
+        let a = store.create_session("MiniMax-M2.5", None).unwrap();
+        let b = store.create_session("MiniMax-M2.5", None).unwrap();
+        let usage = |model: &str, prompt: u64| UsageStats {
+            model: model.to_string(),
+            requests: 1,
+            prompt_tokens: prompt,
+            completion_tokens: 100,
+            reasoning_tokens: 40,
+            latency_ms: 1500,
+        };
+        let msg = store.save_message(&a.id, "assistant", "hi", None, None, None).unwrap();
+        store.record_usage(&a.id, Some(msg), &usage("MiniMax-M2.5", 1000)).unwrap();
+        store.record_usage(&a.id, None, &usage("MiniMax-M2.5", 3000)).unwrap();
+        store.record_usage(&b.id, None, &usage("MiniMax-M2.5-highspeed", 500)).unwrap();
+
+        let report = store.usage_report(&a.id).unwrap();
+        assert_eq!(report.session.len(), 1);
+        let session = &report.session[0];
+        assert_eq!(session.requests, 2);
+        assert_eq!(session.prompt_tokens, 4000);
+        assert_eq!(session.completion_tokens, 200);
+        assert_eq!(session.reasoning_tokens, 80);
+        assert_eq!(session.latency_ms, 3000);
+        assert_eq!(report.today.len(), 2);
+        assert_eq!(report.last_30_days.len(), 2);
+
+        let by_session = store.usage_by_session().unwrap();
+        assert_eq!(by_session.len(), 2);
+
+        // Usage goes with its session
+        store.delete_session(&b.id).unwrap();
+        assert_eq!(store.usage_report(&a.id).unwrap().today.len(), 1);
+    }
+
+    #[test]
+    fn large_tool_results_are_compressed() {
+        let store = test_store().with_tool_result_compression(Some(100));
+        let s = store.create_session("MiniMax-M2.5", None).unwrap();
+        let big = format!("fn main() {{}}\n{}", "let needle = 42;\n".repeat(200));
+        store.save_message(&s.id, "tool", &big, None, Some("tc1"), Some("read_file")).unwrap();
+        store.save_message(&s.id, "assistant", &big, None, None, None).unwrap();
+
+        let messages = store.get_session_messages(&s.id).unwrap();
+        assert_eq!(messages[0].content, big);
+        assert_eq!(messages[1].content, big);
+        {
+            let conn = store.conn.lock().unwrap();
+            let compressed: i64 = conn
+                .query_row("SELECT COUNT(*) FROM messages WHERE content_z IS NOT NULL", [], |r| r.get(0))
+                .unwrap();
+            assert_eq!(compressed, 1, "only tool results are compressed");
+        }
+
+        // The preview is still searchable, and forks keep the full content
+        assert!(!store.search_messages("needle", 10).unwrap().is_empty());
+        let fork = store.fork_session(&s.id, None).unwrap();
+        assert_eq!(store.get_session_messages(&fork.id).unwrap()[0].content, big);
+        assert!(store.integrity_check().unwrap().is_empty());
+    }
+
+    #[test]
+    fn compress_existing_tool_results() {
+        let store = test_store();
+        let s = store.create_session("MiniMax-M2.5", None).unwrap();
+        let big = "x".repeat(5000);
+        store.save_message(&s.id, "tool", &big, None, Some("tc1"), None).unwrap();
+        store.save_message(&s.id, "tool", "small", None, Some("tc2"), None).unwrap();
+        let before = store.session_sizes().unwrap()[0].bytes;
+
+        assert_eq!(store.compress_tool_results(1000).unwrap(), 1);
+        assert_eq!(store.compress_tool_results(1000).unwrap(), 0);
+        assert_eq!(store.get_session_messages(&s.id).unwrap()[0].content, big);
+        assert!(store.session_sizes().unwrap()[0].bytes < before);
+    }
+
+    #[test]
+    fn prune_keeps_pinned_and_current_sessions() {
+        let store = test_store();
+        let ids: Vec<String> = (0..5)
+            .map(|_| store.create_session("MiniMax-M2.5", None).unwrap().id)
+            .collect();
+        store.save_message(&ids[2], "user", "old", None, None, None).unwrap();
+        {
+            // Age sessions 0-2 by 10, 20 and 30 days
+            let conn = store.conn.lock().unwrap();
+            for (i, id) in ids.iter().take(3).enumerate() {
+                conn.execute(
+                    "UPDATE sessions SET updated_at = datetime('now', ?1) WHERE id = ?2",
+                    params![format!("-{} days", (i + 1) * 10), id],
+                )
+                .unwrap();
+            }
+        }
+        store.set_session_pinned(&ids[1], true).unwrap();
+
+        // 20 and 30 days old; the pinned one survives
+        assert_eq!(store.prune_sessions(Some(15), None, None).unwrap(), 1);
+        assert!(store.get_session(&ids[2]).unwrap().is_none());
+        assert!(store.get_session(&ids[1]).unwrap().is_some());
+        assert!(store.search_messages("old", 10).unwrap().is_empty());
+
+        // Keep only the newest unpinned session, but never the open one
+        assert_eq!(store.prune_sessions(None, Some(1), Some(&ids[0])).unwrap(), 1);
+        let left: Vec<String> = store.list_sessions().unwrap().into_iter().map(|s| s.id).collect();
+        assert_eq!(left.len(), 3);
+        assert!(left.contains(&ids[0]) && left.contains(&ids[1]));
+    }
+
     #[test]
     fn delete_session_cascades() {
         let store = test_store();
-        let s = store.create_session("MiniMax-M2.5").unwrap();
+        let s = store.create_session("MiniMax-M2.5", None).unwrap();
         store
             .save_message(&s.id, "user", "hello", None, None, None)
             .unwrap();
@@ -257,7 +1100,7 @@ mod tests {
     #[test]
     fn save_and_retrieve_messages() {
         let store = test_store();
-        let s = store.create_session("MiniMax-M2.5").unwrap();
+        let s = store.create_session("MiniMax-M2.5", None).unwrap();
 
         store
             .save_message(&s.id, "user", "Hello!", None, None, None)
@@ -288,4 +1131,302 @@ mod tests {
         assert_eq!(msgs[3].tool_call_id.as_deref(), Some("tc1"));
         assert_eq!(msgs[3].name.as_deref(), Some("bash"));
     }
+
+    #[test]
+    fn search_finds_messages_across_sessions() {
+        let store = test_store();
+        let a = store.create_session("MiniMax-M2.5", None).unwrap();
+        let b = store.create_session("MiniMax-M2.5", None).unwrap();
+        store.rename_session(&a.id, "Migration debugging").unwrap();
+        store
+            .save_message(&a.id, "user", "The database migration fails on startup", None, None, None)
+            .unwrap();
+        store
+            .save_message(&b.id, "user", "Add a dark theme", None, None, None)
+            .unwrap();
+
+        let hits = store.search_messages("migration", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, a.id);
+        assert_eq!(hits[0].session_name, "Migration debugging");
+        assert!(hits[0].snippet.contains(&format!("{}migration{}", MATCH_START, MATCH_END)));
+
+        // Prefix match on the last word, all words required
+        assert_eq!(store.search_messages("database migr", 10).unwrap().len(), 1);
+        assert!(store.search_messages("dark migration", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn search_index_follows_deletes() {
+        let store = test_store();
+        let s = store.create_session("MiniMax-M2.5", None).unwrap();
+        store
+            .save_message(&s.id, "assistant", "unique-token here", None, None, None)
+            .unwrap();
+        assert_eq!(store.search_messages("unique", 10).unwrap().len(), 1);
+
+        store.delete_session(&s.id).unwrap();
+        assert!(store.search_messages("unique", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn search_ignores_fts_syntax() {
+        let store = test_store();
+        let s = store.create_session("MiniMax-M2.5", None).unwrap();
+        store
+            .save_message(&s.id, "user", "error: NOT found (code 42)", None, None, None)
+            .unwrap();
+        assert_eq!(store.search_messages("\"NOT\" (code", 10).unwrap().len(), 1);
+        assert!(store.search_messages("   ", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reasoning_is_stored_with_message() {
+        let store = test_store();
+        let s = store.create_session("MiniMax-M2.5", None).unwrap();
+        let id = store
+            .save_message(&s.id, "assistant", "Done", None, None, None)
+            .unwrap();
+        store.set_message_reasoning(id, "Thinking it over").unwrap();
+
+        let msgs = store.get_session_messages(&s.id).unwrap();
+        assert_eq!(msgs[0].reasoning.as_deref(), Some("Thinking it over"));
+    }
+
+    #[test]
+    fn find_session_by_prefix() {
+        let store = test_store();
+        let s = store.create_session("MiniMax-M2.5", None).unwrap();
+        let found = store.find_session(&s.id[..8]).unwrap().unwrap();
+        assert_eq!(found.id, s.id);
+        assert!(store.find_session("no-such-session").unwrap().is_none());
+    }
+
+    #[test]
+    fn import_session_keeps_messages() {
+        let store = test_store();
+        let session = Session {
+            id: "imported".to_string(),
+            name: "From elsewhere".to_string(),
+            model: "MiniMax-M2.5".to_string(),
+            created_at: "2026-01-01 09:00:00".to_string(),
+            updated_at: "2026-01-01 10:00:00".to_string(),
+            parent_id: None,
+            cwd: None,
+            git_root: None,
+            git_branch: None,
+            pinned: false,
+        };
+        let message = StoredMessage {
+            id: 0,
+            session_id: String::new(),
+            role: "assistant".to_string(),
+            content: "Hi".to_string(),
+            tool_calls: None,
+            tool_call_id: None,
+            name: None,
+            reasoning: Some("why".to_string()),
+            meta: Some(r#"{"isError":true}"#.to_string()),
+            created_at: "2026-01-01 09:30:00".to_string(),
+        };
+        store.import_session(&session, &[message]).unwrap();
+
+        let loaded = store.get_session("imported").unwrap().unwrap();
+        assert_eq!(loaded.name, "From elsewhere");
+        let msgs = store.get_session_messages("imported").unwrap();
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].reasoning.as_deref(), Some("why"));
+        assert_eq!(msgs[0].meta.as_deref(), Some(r#"{"isError":true}"#));
+        assert_eq!(msgs[0].created_at, "2026-01-01 09:30:00");
+    }
+
+    #[test]
+    fn import_session_compresses_large_tool_results() {
+        let store = test_store().with_tool_result_compression(Some(100));
+        let session = Session {
+            id: "imported".to_string(),
+            name: "From elsewhere".to_string(),
+            model: "MiniMax-M2.5".to_string(),
+            created_at: "2026-01-01 09:00:00".to_string(),
+            updated_at: "2026-01-01 10:00:00".to_string(),
+            parent_id: None,
+            cwd: None,
+            git_root: None,
+            git_branch: None,
+            pinned: false,
+        };
+        let big = "x".repeat(5000);
+        let message = |role: &str| StoredMessage {
+            id: 0,
+            session_id: String::new(),
+            role: role.to_string(),
+            content: big.clone(),
+            tool_calls: None,
+            tool_call_id: None,
+            name: None,
+            reasoning: None,
+            meta: None,
+            created_at: "2026-01-01 09:30:00".to_string(),
+        };
+        store.import_session(&session, &[message("user"), message("tool")]).unwrap();
+
+        let compressed: i64 = store
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM messages WHERE content_z IS NOT NULL", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(compressed, 1, "only the tool result is compressed");
+        let msgs = store.get_session_messages("imported").unwrap();
+        assert!(msgs.iter().all(|m| m.content == big));
+    }
+
+    #[test]
+    fn fork_copies_prefix() {
+        let store = test_store();
+        let s = store.create_session("MiniMax-M2.5", None).unwrap();
+        store.rename_session(&s.id, "Original").unwrap();
+        store.save_message(&s.id, "user", "first", None, None, None).unwrap();
+        store.save_message(&s.id, "assistant", "reply", None, None, None).unwrap();
+        let second = store.save_message(&s.id, "user", "second", None, None, None).unwrap();
+
+        let fork = store.fork_session(&s.id, Some(second)).unwrap();
+        assert_eq!(fork.name, "Original (fork)");
+        assert_eq!(fork.parent_id.as_deref(), Some(s.id.as_str()));
+        let msgs = store.get_session_messages(&fork.id).unwrap();
+        let contents: Vec<&str> = msgs.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["first", "reply"]);
+
+        // Full copy, and the original is untouched
+        let full = store.fork_session(&s.id, None).unwrap();
+        assert_eq!(store.get_session_messages(&full.id).unwrap().len(), 3);
+        assert_eq!(store.get_session_messages(&s.id).unwrap().len(), 3);
+
+        let listed = store.get_session(&fork.id).unwrap().unwrap();
+        assert_eq!(listed.parent_id.as_deref(), Some(s.id.as_str()));
+    }
+
+    #[test]
+    fn most_recent_session_compares_both_timestamp_formats() {
+        let store = test_store();
+        let old = store.create_session("MiniMax-M2.5", None).unwrap();
+        let recent = store.create_session("MiniMax-M2.5", None).unwrap();
+        let pinned = store.create_session("MiniMax-M2.5", None).unwrap();
+        {
+            let conn = store.conn.lock().unwrap();
+            // Unix seconds from older versions sort after any datetime as text
+            let set = |id: &str, at: &str| {
+                conn.execute("UPDATE sessions SET updated_at = ?1 WHERE id = ?2", params![at, id])
+                    .unwrap();
+            };
+            set(&old.id, "2001-01-01 00:00:00");
+            set(&recent.id, "2020-06-01 12:00:00");
+            set(&pinned.id, "1000000000"); // 2001-09-09
+            conn.execute("UPDATE sessions SET pinned = 1 WHERE id = ?1", params![pinned.id]).unwrap();
+        }
+        assert_eq!(store.most_recent_session().unwrap().unwrap().id, recent.id);
+        let ids: Vec<String> = store.list_sessions().unwrap().into_iter().map(|s| s.id).collect();
+        assert_eq!(ids, [pinned.id, recent.id, old.id]);
+    }
+
+    #[test]
+    fn list_project_sessions_filters_by_project() {
+        let store = test_store();
+        let repo = ProjectInfo {
+            cwd: "/work/app/src".to_string(),
+            git_root: Some("/work/app".to_string()),
+            git_branch: Some("main".to_string()),
+        };
+        let other = ProjectInfo {
+            cwd: "/tmp/scratch".to_string(),
+            git_root: None,
+            git_branch: None,
+        };
+        let a = store.create_session("MiniMax-M2.5", Some(&repo)).unwrap();
+        let b = store.create_session("MiniMax-M2.5", Some(&other)).unwrap();
+        // Legacy session without a project: listed in every project
+        let legacy = store.create_session("MiniMax-M2.5", None).unwrap();
+
+        // Same repository from a different subdirectory
+        let repo_root = ProjectInfo { cwd: "/work/app".to_string(), ..repo.clone() };
+        let listed = store.list_project_sessions(&repo_root).unwrap();
+        let ids: Vec<&str> = listed.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&a.id.as_str()) && ids.contains(&legacy.id.as_str()));
+        let listed_a = listed.iter().find(|s| s.id == a.id).unwrap();
+        assert_eq!(listed_a.git_branch.as_deref(), Some("main"));
+        assert_eq!(listed_a.project_name().as_deref(), Some("app"));
+
+        let listed = store.list_project_sessions(&other).unwrap();
+        let ids: Vec<&str> = listed.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&b.id.as_str()) && ids.contains(&legacy.id.as_str()));
+
+        assert_eq!(store.list_sessions().unwrap().len(), 3);
+
+        // Forks stay in the parent's project
+        let fork = store.fork_session(&a.id, None).unwrap();
+        assert_eq!(fork.git_root.as_deref(), Some("/work/app"));
+    }
+
+    #[test]
+    fn migrations_record_version_and_are_idempotent() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("sessions.db");
+        {
+            // Partially upgraded: a column from a later step exists, version is 0
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE sessions (id TEXT PRIMARY KEY, name TEXT NOT NULL, model TEXT NOT NULL,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                    updated_at TEXT NOT NULL DEFAULT (datetime('now')), parent_id TEXT);",
+            )
+            .unwrap();
+        }
+        drop(SessionStore::open_at(path.clone()).unwrap());
+        let store = SessionStore::open_at(path).unwrap();
+
+        let conn = store.conn.lock().unwrap();
+        let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(sessions)")
+            .unwrap()
+            .query_map([], |row| row.get(1))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        for column in ["parent_id", "cwd", "git_root", "git_branch", "pinned"] {
+            assert!(columns.iter().any(|c| c == column), "missing {}", column);
+        }
+    }
+
+    #[test]
+    fn open_at_backfills_existing_database() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("sessions.db");
+        {
+            // A database from before the search index existed
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE sessions (id TEXT PRIMARY KEY, name TEXT NOT NULL, model TEXT NOT NULL,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                    updated_at TEXT NOT NULL DEFAULT (datetime('now')));
+                CREATE TABLE messages (id INTEGER PRIMARY KEY AUTOINCREMENT, session_id TEXT NOT NULL,
+                    role TEXT NOT NULL, content TEXT NOT NULL DEFAULT '', tool_calls TEXT,
+                    tool_call_id TEXT, name TEXT,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now')));
+                INSERT INTO sessions (id, name, model) VALUES ('s1', 'Old', 'MiniMax-M2.5');
+                INSERT INTO messages (session_id, role, content) VALUES ('s1', 'user', 'legacy migration notes');",
+            )
+            .unwrap();
+        }
+
+        let store = SessionStore::open_at(path).unwrap();
+        let hits = store.search_messages("legacy", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_name, "Old");
+        let msgs = store.get_session_messages("s1").unwrap();
+        assert!(msgs[0].reasoning.is_none());
+    }
 }
@@ -0,0 +1,99 @@
+use anyhow::{anyhow, Result};
+
+use crate::core::api::MiniMaxClient;
+use crate::core::parser::parse_model_output;
+
+/// Longest title kept; longer ones are cut at a word boundary.
+const MAX_TITLE_CHARS: usize = 60;
+/// How much of each message is sent to the model.
+const MAX_EXCERPT_CHARS: usize = 1500;
+
+/// Ask the model for a short title describing the first exchange of a session.
+pub async fn generate_title(
+    client: &MiniMaxClient,
+    model: &str,
+    user: &str,
+    assistant: &str,
+) -> Result<String> {
+    let raw = client.simple_completion(model, &title_prompt(user, assistant)).await?;
+    clean_title(&raw).ok_or_else(|| anyhow!("model returned an empty title"))
+}
+
+fn title_prompt(user: &str, assistant: &str) -> String {
+    format!(
+        "Write a short title (3 to 6 words) for this coding assistant conversation. \
+         Reply with the title only: no quotes, no trailing punctuation.\n\n\
+         User: {}\n\nAssistant: {}",
+        excerpt(user),
+        excerpt(assistant)
+    )
+}
+
+fn excerpt(text: &str) -> String {
+    text.chars().take(MAX_EXCERPT_CHARS).collect()
+}
+
+/// Reduce a model reply to a single-line title, dropping reasoning blocks,
+/// labels, quotes and markdown decoration.
+fn clean_title(raw: &str) -> Option<String> {
+    let parsed = parse_model_output(raw);
+    let line = parsed.content.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let line = line.trim_start_matches('#').trim();
+    let line = line
+        .strip_prefix("Title:")
+        .or_else(|| line.strip_prefix("title:"))
+        .unwrap_or(line);
+    let decoration = |c: char| matches!(c, '"' | '\'' | '`' | '*' | '_');
+    let title = line
+        .trim()
+        .trim_matches(decoration)
+        .trim_end_matches(['.', ':', ';'])
+        .trim_matches(decoration)
+        .trim();
+    if title.is_empty() {
+        return None;
+    }
+    if title.chars().count() <= MAX_TITLE_CHARS {
+        return Some(title.to_string());
+    }
+    let cut: String = title.chars().take(MAX_TITLE_CHARS).collect();
+    let cut = match cut.rfind(' ') {
+        Some(space) if space > MAX_TITLE_CHARS / 2 => &cut[..space],
+        _ => cut.as_str(),
+    };
+    Some(format!("{}…", cut.trim_end()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_title_strips_decoration() {
+        assert_eq!(clean_title("\"Fix login redirect loop\"").as_deref(), Some("Fix login redirect loop"));
+        assert_eq!(clean_title("Title: **Add CSV export**.").as_deref(), Some("Add CSV export"));
+        assert_eq!(clean_title("## Refactor parser\nMore text").as_deref(), Some("Refactor parser"));
+        assert_eq!(
+            clean_title("<think>The user wants...</think>\n\nDebug flaky tests").as_deref(),
+            Some("Debug flaky tests")
+        );
+        assert_eq!(clean_title("  \n \"\" "), None);
+    }
+
+    #[test]
+    fn clean_title_truncates_at_word_boundary() {
+        let long = "Investigate why the session database migration fails on older installs with WAL";
+        let title = clean_title(long).unwrap();
+        assert!(title.ends_with('…'));
+        assert!(title.chars().count() <= MAX_TITLE_CHARS + 1);
+        assert!(long.starts_with(title.trim_end_matches('…')));
+        assert!(!title.trim_end_matches('…').ends_with(' '));
+    }
+
+    #[test]
+    fn prompt_limits_excerpts() {
+        let prompt = title_prompt(&"a".repeat(5000), "ok");
+        assert!(prompt.len() < 2000);
+        assert!(prompt.contains("Assistant: ok"));
+    }
+}
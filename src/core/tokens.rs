@@ -0,0 +1,152 @@
+use serde_json::Value;
+
+/// Tokens the chat template adds around each message (role markers, separators).
+const MESSAGE_OVERHEAD: usize = 4;
+/// Bounds for the calibration ratio, so one odd report cannot skew estimates.
+const MIN_RATIO: f64 = 0.5;
+const MAX_RATIO: f64 = 2.0;
+/// Weight of the newest API report in the calibration ratio.
+const CALIBRATION_WEIGHT: f64 = 0.5;
+
+// ── Counting ───────────────────────────────────────────────────────────
+
+/// Number of tokens in `text` under the o200k BPE vocabulary bundled with
+/// `tiktoken-rs`.
+///
+/// MiniMax does not publish its tokenizer; a vocabulary of similar size gets
+/// close, and [`TokenEstimator`] corrects the remaining difference with the
+/// counts the API reports.
+pub fn count_tokens(text: &str) -> usize {
+    if text.is_empty() {
+        return 0;
+    }
+    tiktoken_rs::o200k_base_singleton().encode_ordinary(text).len()
+}
+
+/// Tokens of a chat message: its string values (content, reasoning, tool
+/// call names and arguments) plus the template overhead. Keys are not
+/// counted since the template does not send them as JSON.
+pub fn count_message_tokens(message: &Value) -> usize {
+    fn strings(value: &Value) -> usize {
+        match value {
+            Value::String(s) => count_tokens(s),
+            Value::Array(items) => items.iter().map(strings).sum(),
+            Value::Object(map) => map.values().map(strings).sum(),
+            _ => 0,
+        }
+    }
+    MESSAGE_OVERHEAD + strings(message)
+}
+
+/// Tokens of tool definitions, which reach the model as JSON schema text.
+pub fn count_tool_tokens(tools: &[Value]) -> usize {
+    tools
+        .iter()
+        .map(|t| count_tokens(&serde_json::to_string(t).unwrap_or_default()))
+        .sum()
+}
+
+// ── Calibration ────────────────────────────────────────────────────────
+
+/// Token counts scaled by how far [`count_tokens`] has been from the
+/// `prompt_tokens` the API reported for the same requests.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenEstimator {
+    ratio: f64,
+    samples: u32,
+}
+
+impl Default for TokenEstimator {
+    fn default() -> Self {
+        Self { ratio: 1.0, samples: 0 }
+    }
+}
+
+impl TokenEstimator {
+    /// Calibrated count for a raw [`count_tokens`]-style estimate.
+    pub fn scale(&self, raw: usize) -> usize {
+        (raw as f64 * self.ratio).round() as usize
+    }
+
+    /// Calibrated estimate of a full request.
+    pub fn estimate_request(&self, messages: &[Value], tools: &[Value]) -> usize {
+        self.scale(raw_request_tokens(messages, tools))
+    }
+
+    /// Record that a request estimated at `raw` tokens was billed as
+    /// `reported` prompt tokens.
+    pub fn calibrate(&mut self, raw: usize, reported: u64) {
+        if raw == 0 || reported == 0 {
+            return;
+        }
+        let observed = (reported as f64 / raw as f64).clamp(MIN_RATIO, MAX_RATIO);
+        self.ratio = if self.samples == 0 {
+            observed
+        } else {
+            self.ratio * (1.0 - CALIBRATION_WEIGHT) + observed * CALIBRATION_WEIGHT
+        };
+        self.samples += 1;
+    }
+}
+
+/// Uncalibrated estimate of a request with these messages and tools.
+pub fn raw_request_tokens(messages: &[Value], tools: &[Value]) -> usize {
+    messages.iter().map(count_message_tokens).sum::<usize>() + count_tool_tokens(tools)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn counts_words_and_spaces() {
+        assert_eq!(count_tokens(""), 0);
+        assert_eq!(count_tokens("hello world"), 2);
+        assert_eq!(count_tokens("getUserName"), 3);
+        assert_eq!(count_tokens("internationalization"), 2);
+        assert_eq!(count_tokens("line one\n\nline two"), 5);
+    }
+
+    #[test]
+    fn code_and_json_cost_more_than_chars_over_four() {
+        let json = r#"{"a":1,"b":2}"#;
+        assert_eq!(count_tokens(json), 9);
+        assert!(count_tokens(json) > json.len() / 4);
+        assert_eq!(count_tokens("1234567"), 3);
+    }
+
+    #[test]
+    fn counts_cjk_and_accented_text() {
+        assert_eq!(count_tokens("你好世界"), 2);
+        assert_eq!(count_tokens(&"漢".repeat(100)), 100);
+        assert_eq!(count_tokens("héllo"), 2);
+    }
+
+    #[test]
+    fn messages_include_overhead_and_tool_calls() {
+        let msg = json!({
+            "role": "assistant",
+            "content": "",
+            "tool_calls": [{"id": "1", "function": {"name": "read_file", "arguments": "{\"path\":\"a\"}"}}]
+        });
+        let plain = json!({"role": "user", "content": "hi"});
+        assert_eq!(count_message_tokens(&plain), MESSAGE_OVERHEAD + 2);
+        assert!(count_message_tokens(&msg) > MESSAGE_OVERHEAD + 5);
+        assert!(count_tool_tokens(&[json!({"name": "bash"})]) > 0);
+    }
+
+    #[test]
+    fn calibration_follows_reports() {
+        let mut estimator = TokenEstimator::default();
+        assert_eq!(estimator.scale(100), 100);
+        estimator.calibrate(100, 150);
+        assert_eq!(estimator.scale(100), 150);
+        estimator.calibrate(100, 100);
+        assert_eq!(estimator.scale(100), 125);
+        estimator.calibrate(100, 10_000);
+        assert_eq!(estimator.scale(100), 163);
+        estimator.calibrate(0, 50);
+        assert_eq!(estimator.scale(100), 163);
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use crate::config::settings::ModelPrice;
+use crate::core::session::{UsageReport, UsageStats};
+
+/// Cost in USD of the given usage, or `None` when no listed model has a price.
+pub fn cost(stats: &[UsageStats], prices: &HashMap<String, ModelPrice>) -> Option<f64> {
+    let mut total = None;
+    for s in stats {
+        if let Some(price) = prices.get(&s.model) {
+            let cost = (s.prompt_tokens as f64 * price.input
+                + s.completion_tokens as f64 * price.output)
+                / 1_000_000.0;
+            *total.get_or_insert(0.0) += cost;
+        }
+    }
+    total
+}
+
+/// Cost of each session that used a priced model, keyed by session id.
+pub fn session_costs(
+    rows: Vec<(String, UsageStats)>,
+    prices: &HashMap<String, ModelPrice>,
+) -> HashMap<String, f64> {
+    let mut by_session: HashMap<String, Vec<UsageStats>> = HashMap::new();
+    for (session_id, stats) in rows {
+        by_session.entry(session_id).or_default().push(stats);
+    }
+    by_session
+        .into_iter()
+        .filter_map(|(id, stats)| cost(&stats, prices).map(|c| (id, c)))
+        .collect()
+}
+
+pub fn format_tokens(tokens: u64) -> String {
+    if tokens >= 1_000_000 {
+        format!("{:.1}M", tokens as f64 / 1_000_000.0)
+    } else if tokens >= 1_000 {
+        format!("{:.1}k", tokens as f64 / 1_000.0)
+    } else {
+        format!("{}", tokens)
+    }
+}
+
+pub fn format_cost(usd: f64) -> String {
+    if usd < 0.01 {
+        format!("${:.4}", usd)
+    } else {
+        format!("${:.2}", usd)
+    }
+}
+
+/// Text for `/usage`: one block per period with a line per model.
+pub fn format_report(report: &UsageReport, prices: &HashMap<String, ModelPrice>) -> String {
+    let mut out = String::from("Token usage");
+    for (label, stats) in [
+        ("This session", &report.session),
+        ("Today", &report.today),
+        ("Last 30 days", &report.last_30_days),
+    ] {
+        out.push_str(&format!("\n\n{}:", label));
+        if stats.is_empty() {
+            out.push_str("\n  no requests");
+            continue;
+        }
+        for s in stats {
+            out.push_str(&format!("\n  {}", format_stats(s, prices)));
+        }
+        if stats.len() > 1 {
+            let total = UsageStats {
+                model: "total".to_string(),
+                requests: stats.iter().map(|s| s.requests).sum(),
+                prompt_tokens: stats.iter().map(|s| s.prompt_tokens).sum(),
+                completion_tokens: stats.iter().map(|s| s.completion_tokens).sum(),
+                reasoning_tokens: stats.iter().map(|s| s.reasoning_tokens).sum(),
+                latency_ms: stats.iter().map(|s| s.latency_ms).sum(),
+            };
+            let mut line = format_stats(&total, &HashMap::new());
+            if let Some(c) = cost(stats, prices) {
+                line.push_str(&format!(" · {}", format_cost(c)));
+            }
+            out.push_str(&format!("\n  {}", line));
+        }
+    }
+    if prices.is_empty() {
+        out.push_str("\n\nAdd \"prices\" to the config to see costs.");
+    }
+    out
+}
+
+fn format_stats(s: &UsageStats, prices: &HashMap<String, ModelPrice>) -> String {
+    let avg_latency = s.latency_ms as f64 / s.requests.max(1) as f64 / 1000.0;
+    let mut line = format!(
+        "{}: {} req · in {} · out {} (reasoning {}) · avg {:.1}s",
+        s.model,
+        s.requests,
+        format_tokens(s.prompt_tokens),
+        format_tokens(s.completion_tokens),
+        format_tokens(s.reasoning_tokens),
+        avg_latency
+    );
+    if let Some(c) = cost(std::slice::from_ref(s), prices) {
+        line.push_str(&format!(" · {}", format_cost(c)));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(model: &str, prompt: u64, completion: u64) -> UsageStats {
+        UsageStats {
+            model: model.to_string(),
+            requests: 1,
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            reasoning_tokens: 0,
+            latency_ms: 2000,
+        }
+    }
+
+    fn prices() -> HashMap<String, ModelPrice> {
+        HashMap::from([("MiniMax-M2.5".to_string(), ModelPrice { input: 0.3, output: 1.2 })])
+    }
+
+    #[test]
+    fn format_tokens_small() {
+        assert_eq!(format_tokens(0), "0");
+        assert_eq!(format_tokens(500), "500");
+    }
+
+    #[test]
+    fn format_tokens_thousands() {
+        assert_eq!(format_tokens(1200), "1.2k");
+        assert_eq!(format_tokens(15000), "15.0k");
+    }
+
+    #[test]
+    fn format_tokens_millions() {
+        assert_eq!(format_tokens(1_500_000), "1.5M");
+    }
+
+    #[test]
+    fn cost_uses_priced_models_only() {
+        let usage = [stats("MiniMax-M2.5", 1_000_000, 500_000), stats("unpriced", 10, 10)];
+        let c = cost(&usage, &prices()).unwrap();
+        assert!((c - 0.9).abs() < 1e-9);
+        assert_eq!(cost(&[stats("unpriced", 10, 10)], &prices()), None);
+    }
+
+    #[test]
+    fn session_costs_sum_models() {
+        let rows = vec![
+            ("a".to_string(), stats("MiniMax-M2.5", 1_000_000, 0)),
+            ("a".to_string(), stats("MiniMax-M2.5", 0, 1_000_000)),
+            ("b".to_string(), stats("unpriced", 5, 5)),
+        ];
+        let costs = session_costs(rows, &prices());
+        assert!((costs["a"] - 1.5).abs() < 1e-9);
+        assert!(!costs.contains_key("b"));
+    }
+
+    #[test]
+    fn report_lists_periods() {
+        let report = UsageReport {
+            session: vec![stats("MiniMax-M2.5", 12_000, 800)],
+            today: Vec::new(),
+            last_30_days: vec![stats("MiniMax-M2.5", 12_000, 800), stats("other", 1, 1)],
+        };
+        let text = format_report(&report, &prices());
+        assert!(text.contains("This session:\n  MiniMax-M2.5: 1 req · in 12.0k · out 800"));
+        assert!(text.contains("avg 2.0s"));
+        assert!(text.contains("Today:\n  no requests"));
+        assert!(text.contains("total: 2 req"));
+        assert!(!text.contains("Add \"prices\""));
+    }
+}
@@ -5,7 +5,7 @@ mod core;
 mod tools;
 mod tui;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
 #[command(name = "minmax-code", version, about = "AI-powered terminal coding assistant")]
@@ -21,11 +21,87 @@ struct Args {
     /// Override the theme
     #[arg(long)]
     theme: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run an MCP stdio server exposing the built-in file and shell tools
+    McpServe {
+        /// Expose only read-only tools (PLAN mode restrictions)
+        #[arg(long)]
+        plan: bool,
+    },
+    /// Export a saved session as Markdown or JSON
+    Export {
+        /// Session id or unique id prefix (default: most recent session)
+        session: Option<String>,
+        /// Output format
+        #[arg(long, short, default_value = "md", value_parser = ["md", "markdown", "json"])]
+        format: String,
+        /// Write to a file instead of stdout
+        #[arg(long, short)]
+        output: Option<std::path::PathBuf>,
+        /// Include model reasoning in Markdown output
+        #[arg(long)]
+        reasoning: bool,
+    },
+    /// Import a session from a JSON export
+    Import {
+        /// Path to a file written by `export --format json`
+        file: std::path::PathBuf,
+    },
+    /// Inspect and maintain the session database
+    Db {
+        #[command(subcommand)]
+        action: DbCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum DbCommand {
+    /// Show the database size and the storage used by each session
+    Stats {
+        /// List every session instead of the largest 20
+        #[arg(long)]
+        all: bool,
+    },
+    /// Delete old sessions now; pinned sessions are kept
+    Prune {
+        /// Delete sessions inactive for more than this many days (default: storage.retentionDays)
+        #[arg(long)]
+        days: Option<u32>,
+        /// Keep only this many of the newest sessions (default: storage.maxSessions)
+        #[arg(long)]
+        keep: Option<usize>,
+    },
+    /// Check integrity, compress large tool results and reclaim free space
+    Vacuum,
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let args = Args::parse();
+
+    match args.command {
+        Some(Command::McpServe { plan }) => {
+            let mode = if plan { core::Mode::Plan } else { core::Mode::Builder };
+            return core::mcp_server::McpServer::new(mode).run_stdio().await;
+        }
+        Some(Command::Export { session, format, output, reasoning }) => {
+            return export_session(session, &format, output, reasoning);
+        }
+        Some(Command::Import { file }) => {
+            return import_session(file);
+        }
+        Some(Command::Db { action }) => {
+            return database_command(action);
+        }
+        None => {}
+    }
+
     let mut config = config::settings::load_config();
 
     // Apply CLI overrides
@@ -39,3 +115,119 @@ async fn main() -> anyhow::Result<()> {
     // Launch TUI
     tui::app::run(config).await
 }
+
+fn export_session(
+    session: Option<String>,
+    format: &str,
+    output: Option<std::path::PathBuf>,
+    reasoning: bool,
+) -> anyhow::Result<()> {
+    let store = core::session::SessionStore::open()?;
+    let session = match session {
+        Some(id) => store
+            .find_session(&id)?
+            .ok_or_else(|| anyhow::anyhow!("No session matching \"{}\"", id))?,
+        None => store
+            .most_recent_session()?
+            .ok_or_else(|| anyhow::anyhow!("No saved sessions"))?,
+    };
+    let format = core::export::ExportFormat::parse(format)
+        .ok_or_else(|| anyhow::anyhow!("Unknown format \"{}\"", format))?;
+    let content = core::export::export_session(&store, &session.id, format, reasoning)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, content)?;
+            eprintln!("Exported \"{}\" to {}", session.name, path.display());
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+fn import_session(file: std::path::PathBuf) -> anyhow::Result<()> {
+    let storage = config::settings::load_config().storage;
+    let store = core::session::SessionStore::open()?
+        .with_tool_result_compression(storage.compress_tool_results_over);
+    let json = std::fs::read_to_string(&file)?;
+    let project = core::project::ProjectInfo::current();
+    let session = core::export::import_json(&store, &json, Some(&project))?;
+    println!("Imported \"{}\" as session {}", session.name, session.id);
+    Ok(())
+}
+
+fn database_command(action: DbCommand) -> anyhow::Result<()> {
+    let store = core::session::SessionStore::open()?;
+    let storage = config::settings::load_config().storage;
+    match action {
+        DbCommand::Stats { all } => {
+            let sizes = store.session_sizes()?;
+            let total: u64 = sizes.iter().map(|s| s.bytes).sum();
+            println!(
+                "Database: {} on disk, {} of messages in {} sessions",
+                format_bytes(store.database_size()?),
+                format_bytes(total),
+                sizes.len()
+            );
+            let shown = if all { sizes.len() } else { 20 };
+            for size in sizes.iter().take(shown) {
+                println!(
+                    "{:>10}  {:>5} msgs  {}  {}{}",
+                    format_bytes(size.bytes),
+                    size.messages,
+                    &size.id[..8.min(size.id.len())],
+                    if size.pinned { "★ " } else { "" },
+                    size.name
+                );
+            }
+            if sizes.len() > shown {
+                println!("... {} more (--all to list every session)", sizes.len() - shown);
+            }
+        }
+        DbCommand::Prune { days, keep } => {
+            let days = days.or(storage.retention_days);
+            let keep = keep.or(storage.max_sessions);
+            if days.is_none() && keep.is_none() {
+                anyhow::bail!("Nothing to prune: pass --days or --keep, or set storage.retentionDays / storage.maxSessions");
+            }
+            let deleted = store.prune_sessions(days, keep, None)?;
+            println!("Deleted {} session(s)", deleted);
+        }
+        DbCommand::Vacuum => {
+            let before = store.database_size()?;
+            let problems = store.integrity_check()?;
+            if problems.iter().any(|p| p.starts_with("search index")) {
+                store.rebuild_search_index()?;
+                println!("Rebuilt the search index");
+            }
+            let corrupt: Vec<&String> = problems.iter().filter(|p| !p.starts_with("search index")).collect();
+            if !corrupt.is_empty() {
+                for problem in &corrupt {
+                    eprintln!("{}", problem);
+                }
+                anyhow::bail!("Integrity check failed; back up sessions.db before making changes");
+            }
+            if let Some(threshold) = storage.compress_tool_results_over {
+                let compressed = store.compress_tool_results(threshold)?;
+                println!("Compressed {} tool result(s)", compressed);
+            }
+            store.vacuum()?;
+            println!(
+                "Integrity ok. Database size {} -> {}",
+                format_bytes(before),
+                format_bytes(store.database_size()?)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{} B", bytes)
+    }
+}
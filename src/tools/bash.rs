@@ -31,13 +31,13 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
         .unwrap_or("");
 
     if command.is_empty() {
-        return ToolExecutionResult::text("Error: No command provided".to_string());
+        return ToolExecutionResult::error("Error: No command provided".to_string());
     }
 
     let result = run_command_with_platform_shell(command).await;
     match result {
         Ok(output) => ToolExecutionResult::text(format_output(output)),
-        Err(e) => ToolExecutionResult::text(format!("Error executing command: {}", e)),
+        Err(e) => ToolExecutionResult::error(format!("Error executing command: {}", e)),
     }
 }
 
@@ -37,24 +37,24 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
     let new_str = args.get("new_str").and_then(|v| v.as_str()).unwrap_or("");
 
     if path.is_empty() {
-        return ToolExecutionResult::text("Error: No path provided".to_string());
+        return ToolExecutionResult::error("Error: No path provided".to_string());
     }
 
     if !Path::new(path).exists() {
-        return ToolExecutionResult::text(format!("Error: File not found: {}", path));
+        return ToolExecutionResult::error(format!("Error: File not found: {}", path));
     }
 
     let content = match fs::read_to_string(path).await {
         Ok(c) => c,
-        Err(e) => return ToolExecutionResult::text(format!("Error reading file: {}", e)),
+        Err(e) => return ToolExecutionResult::error(format!("Error reading file: {}", e)),
     };
 
     let occurrences = content.matches(old_str).count();
     if occurrences == 0 {
-        return ToolExecutionResult::text(format!("Error: old_str not found in {}", path));
+        return ToolExecutionResult::error(format!("Error: old_str not found in {}", path));
     }
     if occurrences > 1 {
-        return ToolExecutionResult::text(format!(
+        return ToolExecutionResult::error(format!(
             "Error: old_str found {} times in {}. It must be unique. Add more context to make it unique.",
             occurrences, path
         ));
@@ -70,6 +70,6 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
                 new_str: new_str.to_string(),
             },
         ),
-        Err(e) => ToolExecutionResult::text(format!("Error writing file: {}", e)),
+        Err(e) => ToolExecutionResult::error(format!("Error writing file: {}", e)),
     }
 }
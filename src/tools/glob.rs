@@ -45,12 +45,12 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
         });
 
     if pattern.is_empty() {
-        return ToolExecutionResult::text("Error: No pattern provided".to_string());
+        return ToolExecutionResult::error("Error: No pattern provided".to_string());
     }
 
     let glob = match Glob::new(pattern) {
         Ok(g) => g.compile_matcher(),
-        Err(e) => return ToolExecutionResult::text(format!("Error: Invalid glob pattern: {}", e)),
+        Err(e) => return ToolExecutionResult::error(format!("Error: Invalid glob pattern: {}", e)),
     };
 
     let base = Path::new(&cwd);
@@ -61,13 +61,13 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
         .unwrap_or(0) as usize;
 
     if pattern.is_empty() {
-        return ToolExecutionResult::text("Error: No pattern provided".to_string());
+        return ToolExecutionResult::error("Error: No pattern provided".to_string());
     }
 
     let matcher = match RegexMatcher::new_line_matcher(pattern) {
         Ok(m) => m,
         Err(e) => {
-            return ToolExecutionResult::text(format!("Error: Invalid regex pattern: {}", e))
+            return ToolExecutionResult::error(format!("Error: Invalid regex pattern: {}", e))
         }
     };
 
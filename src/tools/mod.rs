@@ -10,12 +10,15 @@ pub mod web_search;
 pub mod write_file;
 
 use crate::core::Mode;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashSet;
 use std::sync::LazyLock;
 
 /// Metadata about a tool execution result, used for rich UI rendering.
-#[derive(Debug, Clone)]
+/// Stored with the tool message so resumed sessions render the same way.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum ToolResultMeta {
     EditFile {
         path: String,
@@ -27,6 +30,9 @@ pub enum ToolResultMeta {
         content: String,
         is_new: bool,
     },
+    ReadFile {
+        path: String,
+    },
 }
 
 /// Result from executing a tool.
@@ -34,17 +40,33 @@ pub enum ToolResultMeta {
 pub struct ToolExecutionResult {
     pub result: String,
     pub meta: Option<ToolResultMeta>,
+    /// Set when the tool reported failure out-of-band (e.g. MCP `isError`)
+    /// rather than through an "Error:" prefix in `result`.
+    pub is_error: bool,
 }
 
 impl ToolExecutionResult {
     pub fn text(result: String) -> Self {
-        Self { result, meta: None }
+        Self {
+            result,
+            meta: None,
+            is_error: false,
+        }
+    }
+
+    pub fn error(result: String) -> Self {
+        Self {
+            result,
+            meta: None,
+            is_error: true,
+        }
     }
 
     pub fn with_meta(result: String, meta: ToolResultMeta) -> Self {
         Self {
             result,
             meta: Some(meta),
+            is_error: false,
         }
     }
 }
@@ -92,15 +114,16 @@ pub fn get_tool_definitions(mode: Mode) -> Vec<Value> {
     }
 }
 
-/// Execute a tool by name with the given arguments.
+/// Execute a tool by name with the given arguments. `web_search` needs the
+/// API key and is run by the chat engine instead.
 pub async fn execute_tool(
     name: &str,
     args: Value,
     mode: Mode,
 ) -> ToolExecutionResult {
     // PLAN mode enforcement
-    if mode == Mode::Plan && !READ_ONLY_TOOLS.contains(name) && !name.starts_with("mcp__") {
-        return ToolExecutionResult::text(format!(
+    if mode == Mode::Plan && !READ_ONLY_TOOLS.contains(name) {
+        return ToolExecutionResult::error(format!(
             "Error: Tool \"{}\" is not available in PLAN mode. Switch to BUILDER mode (Tab) to use it.",
             name
         ));
@@ -114,7 +137,6 @@ pub async fn execute_tool(
         "glob" => glob::execute(args).await,
         "grep" => grep::execute(args).await,
         "list_directory" => list_dir::execute(args).await,
-        "web_search" => web_search::execute(args).await,
-        _ => ToolExecutionResult::text(format!("Error: Unknown tool \"{}\"", name)),
+        _ => ToolExecutionResult::error(format!("Error: Unknown tool \"{}\"", name)),
     }
 }
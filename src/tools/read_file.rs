@@ -1,4 +1,4 @@
-use super::ToolExecutionResult;
+use super::{ToolExecutionResult, ToolResultMeta};
 use serde_json::Value;
 use std::path::Path;
 use tokio::fs;
@@ -38,16 +38,16 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
         .unwrap_or("");
 
     if path.is_empty() {
-        return ToolExecutionResult::text("Error: No path provided".to_string());
+        return ToolExecutionResult::error("Error: No path provided".to_string());
     }
 
     if !Path::new(path).exists() {
-        return ToolExecutionResult::text(format!("Error: File not found: {}", path));
+        return ToolExecutionResult::error(format!("Error: File not found: {}", path));
     }
 
     let text = match fs::read_to_string(path).await {
         Ok(t) => t,
-        Err(e) => return ToolExecutionResult::text(format!("Error reading file: {}", e)),
+        Err(e) => return ToolExecutionResult::error(format!("Error reading file: {}", e)),
     };
 
     let lines: Vec<&str> = text.split('\n').collect();
@@ -72,7 +72,7 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
             .map(|(i, line)| format!("{}\t{}", start + i + 1, line))
             .collect::<Vec<_>>()
             .join("\n");
-        return ToolExecutionResult::text(result);
+        return ToolExecutionResult::with_meta(result, meta(path));
     }
 
     if lines.len() > 2000 {
@@ -82,11 +82,10 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
             .map(|(i, line)| format!("{}\t{}", i + 1, line))
             .collect::<Vec<_>>()
             .join("\n");
-        return ToolExecutionResult::text(format!(
-            "{}\n...(file has {} lines, showing first 2000)",
-            result,
-            lines.len()
-        ));
+        return ToolExecutionResult::with_meta(
+            format!("{}\n...(file has {} lines, showing first 2000)", result, lines.len()),
+            meta(path),
+        );
     }
 
     let result: String = lines
@@ -95,5 +94,9 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
         .map(|(i, line)| format!("{}\t{}", i + 1, line))
         .collect::<Vec<_>>()
         .join("\n");
-    ToolExecutionResult::text(result)
+    ToolExecutionResult::with_meta(result, meta(path))
+}
+
+fn meta(path: &str) -> ToolResultMeta {
+    ToolResultMeta::ReadFile { path: path.to_string() }
 }
@@ -22,23 +22,27 @@ pub fn definition() -> Value {
     })
 }
 
-pub async fn execute(args: Value) -> ToolExecutionResult {
+/// Search with the MiniMax search API, authenticated with `api_key`.
+pub async fn execute(args: Value, api_key: &str) -> ToolExecutionResult {
     let query = args
         .get("query")
         .and_then(|v| v.as_str())
         .unwrap_or("");
 
     if query.is_empty() {
-        return ToolExecutionResult::text("Error: No query provided".to_string());
+        return ToolExecutionResult::error("Error: No query provided".to_string());
     }
 
-    // Load API key from config
-    let config = crate::config::settings::load_config();
-    if config.api_key.is_empty() {
-        return ToolExecutionResult::text(
+    if api_key.is_empty() {
+        return ToolExecutionResult::error(
             "Error: No API key configured. Run /config to set it.".to_string(),
         );
     }
+    let Ok(authorization) = HeaderValue::from_str(&format!("Bearer {}", api_key)) else {
+        return ToolExecutionResult::error(
+            "Error: The API key contains characters that cannot be sent in a header.".to_string(),
+        );
+    };
 
     let client = reqwest::Client::new();
     let url = "https://api.minimax.io/v1/coding_plan/search";
@@ -46,10 +50,7 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
     let response = match client
         .post(url)
         .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
-        .header(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", config.api_key)).unwrap(),
-        )
+        .header(AUTHORIZATION, authorization)
         .json(&serde_json::json!({ "q": query }))
         .send()
         .await
@@ -57,9 +58,9 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
         Ok(r) => r,
         Err(e) => {
             if e.is_connect() {
-                return ToolExecutionResult::text("Error: No internet connection.".to_string());
+                return ToolExecutionResult::error("Error: No internet connection.".to_string());
             }
-            return ToolExecutionResult::text(format!("Error: {}", e));
+            return ToolExecutionResult::error(format!("Error: {}", e));
         }
     };
 
@@ -71,7 +72,7 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
         } else {
             &text
         };
-        return ToolExecutionResult::text(format!(
+        return ToolExecutionResult::error(format!(
             "Error: Search API returned {}{}",
             status,
             if preview.is_empty() {
@@ -84,7 +85,7 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
 
     let data: Value = match response.json().await {
         Ok(d) => d,
-        Err(e) => return ToolExecutionResult::text(format!("Error parsing response: {}", e)),
+        Err(e) => return ToolExecutionResult::error(format!("Error parsing response: {}", e)),
     };
 
     // Extract results — the API may use different field names
@@ -38,7 +38,7 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
         .unwrap_or("");
 
     if path.is_empty() {
-        return ToolExecutionResult::text("Error: No path provided".to_string());
+        return ToolExecutionResult::error("Error: No path provided".to_string());
     }
 
     let is_new = !Path::new(path).exists();
@@ -47,7 +47,7 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
     if let Some(parent) = Path::new(path).parent() {
         if !parent.exists() {
             if let Err(e) = fs::create_dir_all(parent).await {
-                return ToolExecutionResult::text(format!("Error creating directories: {}", e));
+                return ToolExecutionResult::error(format!("Error creating directories: {}", e));
             }
         }
     }
@@ -61,6 +61,6 @@ pub async fn execute(args: Value) -> ToolExecutionResult {
                 is_new,
             },
         ),
-        Err(e) => ToolExecutionResult::text(format!("Error writing file: {}", e)),
+        Err(e) => ToolExecutionResult::error(format!("Error writing file: {}", e)),
     }
 }
@@ -2,6 +2,7 @@ use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 
+use crate::config::secrets::KeyStore;
 use crate::config::themes::Theme;
 
 // ── State ──────────────────────────────────────────────────────────────
@@ -11,6 +12,8 @@ pub struct ApiKeyPromptState {
     pub input: String,
     pub cursor: usize,
     pub error: Option<String>,
+    /// Where the key will be saved; Tab cycles through `KeyStore::available`.
+    pub store: KeyStore,
 }
 
 impl ApiKeyPromptState {
@@ -19,16 +22,22 @@ impl ApiKeyPromptState {
             input: String::new(),
             cursor: 0,
             error: None,
+            store: KeyStore::default(),
         }
     }
 
     pub fn with_error(error: String) -> Self {
         Self {
-            input: String::new(),
-            cursor: 0,
             error: Some(error),
+            ..Self::new()
         }
     }
+
+    fn next_store(&mut self) {
+        let stores = KeyStore::available();
+        let current = stores.iter().position(|s| *s == self.store).unwrap_or(0);
+        self.store = stores[(current + 1) % stores.len()];
+    }
 }
 
 // ── Action result ──────────────────────────────────────────────────────
@@ -36,7 +45,7 @@ impl ApiKeyPromptState {
 #[derive(Debug, Clone, PartialEq)]
 pub enum ApiKeyAction {
     None,
-    Submit(String),
+    Submit(String, KeyStore),
     Quit,
 }
 
@@ -54,9 +63,13 @@ pub fn handle_key(state: &mut ApiKeyPromptState, key: KeyEvent) -> ApiKeyAction
                 state.error = Some("API key must be at least 10 characters.".to_string());
                 ApiKeyAction::None
             } else {
-                ApiKeyAction::Submit(key)
+                ApiKeyAction::Submit(key, state.store)
             }
         }
+        KeyCode::Tab => {
+            state.next_store();
+            ApiKeyAction::None
+        }
         KeyCode::Char(c) => {
             state.input.insert(state.cursor, c);
             state.cursor += 1;
@@ -111,7 +124,7 @@ pub fn render(frame: &mut Frame, area: Rect, state: &ApiKeyPromptState, theme: &
     );
 
     // Center the prompt content
-    let box_height = 14u16;
+    let box_height = 16u16;
     let box_width = 60u16.min(area.width.saturating_sub(4));
     let x = (area.width.saturating_sub(box_width)) / 2;
     let y = (area.height.saturating_sub(box_height)) / 2;
@@ -166,13 +179,26 @@ pub fn render(frame: &mut Frame, area: Rect, state: &ApiKeyPromptState, theme: &
         lines.push(Line::from(""));
     }
 
+    // Storage choice
+    let mut store_line = vec![Span::styled("  Save in: ", Style::default().fg(dim))];
+    for store in KeyStore::available() {
+        let style = if store == state.store {
+            Style::default().fg(accent).bold()
+        } else {
+            Style::default().fg(dim)
+        };
+        store_line.push(Span::styled(format!("[{}] ", store.label()), style));
+    }
+    lines.push(Line::from(store_line));
+    lines.push(Line::from(""));
+
     // Help text
     lines.push(Line::from(Span::styled(
         "  Get your key at: https://platform.minimaxi.com",
         Style::default().fg(dim),
     )));
     lines.push(Line::from(Span::styled(
-        "  Press Enter to submit, Esc to quit",
+        "  Enter to submit, Tab to change where it is saved, Esc to quit",
         Style::default().fg(dim),
     )));
 
@@ -250,7 +276,21 @@ mod tests {
         let mut state = ApiKeyPromptState::new();
         state.input = "abcdefghijklmnop".to_string();
         let action = handle_key(&mut state, KeyEvent::from(KeyCode::Enter));
-        assert_eq!(action, ApiKeyAction::Submit("abcdefghijklmnop".to_string()));
+        assert_eq!(
+            action,
+            ApiKeyAction::Submit("abcdefghijklmnop".to_string(), KeyStore::Config)
+        );
+    }
+
+    #[test]
+    fn tab_cycles_key_stores() {
+        let mut state = ApiKeyPromptState::new();
+        handle_key(&mut state, KeyEvent::from(KeyCode::Tab));
+        assert_eq!(state.store, KeyStore::SecretsFile);
+        for _ in 1..KeyStore::available().len() {
+            handle_key(&mut state, KeyEvent::from(KeyCode::Tab));
+        }
+        assert_eq!(state.store, KeyStore::Config);
     }
 
     #[test]
@@ -1,30 +1,56 @@
 use anyhow::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent, MouseEventKind};
 use ratatui::prelude::*;
+use std::collections::HashSet;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, oneshot};
 use tokio_util::sync::CancellationToken;
 
-use crate::config::settings::{save_config, AppConfig};
+use crate::config::secrets;
+use crate::config::themes::get_theme;
+use crate::config::settings::{config_dir, trust_project, update_config, AppConfig};
 use crate::core::api::{AccumulatedToolCall, MiniMaxClient, QuotaInfo};
-use crate::core::chat::{ChatEngine, ChatEvent, ResponseChannel, TodoItem};
-use crate::core::commands::{handle_command, CommandResult};
+use crate::core::chat::{self, ChatEngine, ChatEvent, MessageMeta, ResponseChannel, TodoItem};
+use crate::core::commands::{handle_command, CommandResult, CopyTarget};
+use crate::core::export;
+use crate::core::instructions::Instructions;
 use crate::core::mcp::McpManager;
-use crate::core::session::SessionStore;
+use crate::core::project::ProjectInfo;
+use crate::core::session::{SessionStore, DEFAULT_SESSION_NAME};
+use crate::core::title;
+use crate::core::usage;
 use crate::core::Mode;
+use crate::tools::ToolResultMeta;
 use crate::tui::agent_question::{self, AgentQuestionState, QuestionAction};
 use crate::tui::api_key_prompt::{self, ApiKeyAction, ApiKeyPromptState};
+use crate::tui::chat_view;
+use crate::tui::clipboard::{self, Copied};
 use crate::tui::command_palette::{self, CommandPaletteState, PaletteAction};
 use crate::tui::config_menu::{self, ConfigAction, ConfigMenuState};
+use crate::tui::confirm::{self, ConfirmAction, ConfirmState};
+use crate::tui::editor::InputEditor;
+use crate::tui::external_editor::{self, EditorRequest};
 use crate::tui::file_picker::{self, FilePickerAction, FilePickerState};
+use crate::tui::hyperlink::{self, Hyperlink};
 use crate::tui::layout as tui_layout;
+use crate::tui::markdown;
+use crate::tui::pager::{self, PagerAction, PagerState};
+use crate::tui::session_list::{self, SessionListAction, SessionListState};
+use crate::tui::tool_view::{self, ToolDetail};
+use crate::tui::transcript_search::TranscriptSearch;
 
 // ── Token limit constants ──────────────────────────────────────────────
 
-const TOKEN_WARNING_THRESHOLD: u64 = 180_000;
-const TOKEN_LIMIT: u64 = 200_000;
+/// Share of the model's context window at which a warning is shown.
+const TOKEN_WARNING_RATIO: f64 = 0.9;
+/// Share of the context window at which continuing in a new session is offered.
+const HANDOFF_OFFER_RATIO: f64 = 0.95;
 const SYSTEM_MESSAGE_TTL_SECONDS: u64 = 10;
+const SEARCH_RESULT_LIMIT: usize = 50;
+/// Earlier prompts loaded for Up/Down recall and Ctrl+R.
+const PROMPT_HISTORY_LIMIT: usize = 500;
 
 // ── System message types ───────────────────────────────────────────────
 
@@ -45,6 +71,12 @@ pub struct DisplayMessage {
     pub is_streaming: bool,
     pub tool_status: Option<ToolStatus>,
     pub tool_name: Option<String>,
+    /// Diff data of edit/write tool results.
+    pub tool_meta: Option<ToolResultMeta>,
+    /// Call a tool result answers, to find the call's arguments.
+    pub tool_call_id: Option<String>,
+    /// Row id in the session database, once saved; branching forks before it.
+    pub stored_id: Option<i64>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -78,8 +110,25 @@ pub enum Overlay {
     None,
     CommandPalette,
     FilePicker,
-    SessionList { selected: usize },
+    SessionList,
     AgentQuestion,
+    Confirm(ConfirmKind),
+    Pager,
+}
+
+/// Scroll to a user message relative to the one at the top of the chat,
+/// resolved by the event loop once the chat area is known.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessageJump {
+    PreviousUser,
+    NextUser,
+}
+
+/// What a yes/no dialog is asking about.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfirmKind {
+    /// Continue in a new session from a summary, with optional focus text.
+    Handoff(Option<String>),
 }
 
 // ── Application state ───────────────────────────────────────────────────
@@ -88,12 +137,27 @@ pub struct App {
     pub config: AppConfig,
     pub mode: Mode,
     pub messages: Vec<DisplayMessage>,
-    pub input_text: String,
-    pub input_cursor: usize,
+    pub input: InputEditor,
     pub scroll_offset: u16,
+    /// Message index to bring into view on the next frame (resolved by the event loop).
+    pub scroll_anchor: Option<usize>,
+    /// User message selected in "branch from here" mode (index into `messages`).
+    pub branch_select: Option<usize>,
+    /// Jump between user messages waiting for the event loop.
+    pub message_jump: Option<MessageJump>,
+    /// Ctrl+F search over the transcript, while open.
+    pub transcript_search: Option<TranscriptSearch>,
+    /// Tool result selected for expanding (index into `messages`).
+    pub tool_select: Option<usize>,
+    /// Tool results shown in full (indices into `messages`).
+    pub expanded_tools: HashSet<usize>,
+    /// Whether tool results that are not expanded show their header only.
+    pub tools_collapsed: bool,
     pub total_tokens: u64,
     pub prompt_tokens: u64,
     pub completion_tokens: u64,
+    /// Tokens in the model's context as of the last request.
+    pub context_tokens: u64,
     pub quota: Option<QuotaInfo>,
     pub session_name: String,
     pub screen: AppScreen,
@@ -107,10 +171,15 @@ pub struct App {
     // Overlay states
     pub palette_state: CommandPaletteState,
     pub file_picker_state: FilePickerState,
+    pub session_list_state: SessionListState,
     pub config_menu_state: ConfigMenuState,
     pub api_key_state: ApiKeyPromptState,
     pub agent_question_state: Option<AgentQuestionState>,
+    pub confirm_state: Option<ConfirmState>,
+    pub pager_state: Option<PagerState>,
     pub todo_items: Vec<TodoItem>,
+    /// Project (directory, git root, branch) new sessions are filed under.
+    pub project: ProjectInfo,
 
     // Internal
     agent_question_tx: Option<ResponseChannel>,
@@ -121,6 +190,20 @@ pub struct App {
     engine_return_rx: Option<oneshot::Receiver<ChatEngine>>,
     quota_refresh_rx: Option<oneshot::Receiver<Result<QuotaInfo, String>>>,
     update_check_rx: Option<oneshot::Receiver<Option<String>>>,
+    /// Pending automatic title: session id and the generated title.
+    title_rx: Option<oneshot::Receiver<(String, Result<String, String>)>>,
+    /// Last session an automatic title was requested for, so a failed
+    /// request is not retried on every turn.
+    titled_session: Option<String>,
+    /// Whether a handoff was already offered for the current session.
+    handoff_offered: bool,
+    /// The context limit was reached during a turn; the handoff is offered
+    /// once the turn has finished and the engine is back.
+    handoff_due: bool,
+    /// Summary and summarized message count waiting for the engine to return.
+    pending_handoff: Option<(String, usize)>,
+    /// Editor to run once the event loop has handed over the terminal.
+    pub pending_editor: Option<EditorRequest>,
     system_message_expires_at: Option<Instant>,
     cancel_token: CancellationToken,
     #[allow(dead_code)]
@@ -129,20 +212,26 @@ pub struct App {
 
 impl App {
     pub fn new(config: AppConfig) -> Self {
-        let needs_api_key = config.api_key.is_empty()
-            && std::env::var("MINIMAX_API_KEY").unwrap_or_default().is_empty();
+        let needs_api_key = config.api_key.is_empty();
 
         Self {
             mode: Mode::Builder,
             messages: Vec::new(),
-            input_text: String::new(),
-            input_cursor: 0,
+            input: InputEditor::new(),
             scroll_offset: 0,
+            scroll_anchor: None,
+            branch_select: None,
+            message_jump: None,
+            transcript_search: None,
+            tool_select: None,
+            expanded_tools: HashSet::new(),
+            tools_collapsed: false,
             total_tokens: 0,
             prompt_tokens: 0,
             completion_tokens: 0,
+            context_tokens: 0,
             quota: None,
-            session_name: "New Session".to_string(),
+            session_name: DEFAULT_SESSION_NAME.to_string(),
             screen: if needs_api_key {
                 AppScreen::ApiKeyPrompt
             } else {
@@ -156,10 +245,14 @@ impl App {
             tick: 0,
             palette_state: CommandPaletteState::new(),
             file_picker_state: FilePickerState::new(),
+            session_list_state: SessionListState::new(Vec::new()),
             config_menu_state: ConfigMenuState::new(),
             api_key_state: ApiKeyPromptState::new(),
             agent_question_state: None,
+            confirm_state: None,
+            pager_state: None,
             todo_items: Vec::new(),
+            project: ProjectInfo::current(),
             agent_question_tx: None,
             engine: None,
             session_store: None,
@@ -167,6 +260,12 @@ impl App {
             chat_event_rx: None,
             engine_return_rx: None,
             quota_refresh_rx: None,
+            title_rx: None,
+            titled_session: None,
+            handoff_offered: false,
+            handoff_due: false,
+            pending_handoff: None,
+            pending_editor: None,
             update_check_rx: None,
             system_message_expires_at: None,
             cancel_token: CancellationToken::new(),
@@ -177,12 +276,10 @@ impl App {
 
     /// Initialize the chat engine and session store.
     pub async fn initialize(&mut self) -> Result<()> {
-        // Try env var fallback for API key
-        if self.config.api_key.is_empty() {
-            if let Ok(key) = std::env::var("MINIMAX_API_KEY") {
-                self.config.api_key = key;
-                self.screen = AppScreen::Chat;
-            }
+        // The API key comes from the environment, a key store or config.json
+        let warnings = std::mem::take(&mut self.config.warnings);
+        if !warnings.is_empty() {
+            self.set_system_message(warnings.join("; "));
         }
 
         if self.config.api_key.is_empty() {
@@ -197,11 +294,24 @@ impl App {
         let client = MiniMaxClient::new(&self.config.api_key);
         self.start_quota_refresh();
         let mut engine = ChatEngine::new(client, &self.config.model, self.mode);
+        engine.set_model(self.config.model_info());
+        engine.set_instructions(self.config.instructions.clone());
+        engine.set_context_policy(self.config.context.clone());
 
         // Initialize session store
         if let Ok(store) = SessionStore::open() {
+            let storage = &self.config.storage;
+            let store = store.with_tool_result_compression(storage.compress_tool_results_over);
+            if storage.retention_days.is_some() || storage.max_sessions.is_some() {
+                match store.prune_sessions(storage.retention_days, storage.max_sessions, None) {
+                    Ok(0) => {}
+                    Ok(n) => self.set_system_message(format!("Deleted {} old session(s) per storage retention", n)),
+                    Err(e) => self.set_system_message(format!("Session pruning failed: {}", e)),
+                }
+            }
+            self.input.set_history(store.recent_prompts(PROMPT_HISTORY_LIMIT).unwrap_or_default());
             let store = Arc::new(store);
-            if let Ok(session) = store.create_session(&self.config.model) {
+            if let Ok(session) = store.create_session(&self.config.model, Some(&self.project)) {
                 self.session_id = Some(session.id.clone());
                 self.session_name = session.name.clone();
                 engine.set_session(session.id, store.clone());
@@ -239,6 +349,7 @@ impl App {
         match event {
             Event::Key(key) => self.handle_key(key),
             Event::Mouse(mouse) => self.handle_mouse(mouse),
+            Event::Paste(text) => self.handle_paste(&text),
             Event::Resize(_, _) => {}
             _ => {}
         }
@@ -271,9 +382,13 @@ impl App {
     fn handle_api_key_key(&mut self, key: KeyEvent) {
         let action = api_key_prompt::handle_key(&mut self.api_key_state, key);
         match action {
-            ApiKeyAction::Submit(api_key) => {
+            ApiKeyAction::Submit(api_key, store) => {
+                // The key is still used for this run if it could not be saved
+                match secrets::store_api_key(&api_key, store) {
+                    Ok(()) => self.config.api_key_store = store,
+                    Err(e) => self.set_system_message(format!("Could not save the API key: {}", e)),
+                }
                 self.config.api_key = api_key;
-                let _ = save_config(&self.config);
                 self.screen = AppScreen::Chat;
             }
             ApiKeyAction::Quit => {
@@ -296,24 +411,29 @@ impl App {
                 self.screen = AppScreen::Chat;
             }
             ConfigAction::SetApiKey(api_key) => {
+                match secrets::store_api_key(&api_key, self.config.api_key_store) {
+                    Ok(()) => self.set_system_message(format!(
+                        "API key updated in the {}.",
+                        self.config.api_key_store.label()
+                    )),
+                    Err(e) => self.set_system_message(format!("Could not save the API key: {}", e)),
+                }
                 self.config.api_key = api_key;
-                let _ = save_config(&self.config);
                 self.screen = AppScreen::Chat;
-                self.set_system_message("API key updated.");
                 self.engine = None; // Will be re-initialized
             }
             ConfigAction::SetTheme(theme) => {
                 self.config.theme = theme.clone();
-                let _ = save_config(&self.config);
+                let _ = update_config(serde_json::json!({ "theme": theme }));
                 self.screen = AppScreen::Chat;
                 self.set_system_message(format!("Theme changed to {}", theme));
             }
             ConfigAction::SetModel(model) => {
                 self.config.model = model.clone();
                 if let Some(engine) = &mut self.engine {
-                    engine.set_model(&model);
+                    engine.set_model(self.config.model_info());
                 }
-                let _ = save_config(&self.config);
+                let _ = update_config(serde_json::json!({ "model": model }));
                 self.screen = AppScreen::Chat;
                 self.set_system_message(format!("Model changed to {}", model));
             }
@@ -328,6 +448,52 @@ impl App {
             return;
         }
 
+        if self.branch_select.is_some() {
+            self.handle_branch_key(key);
+            return;
+        }
+
+        if self.tool_select.is_some() {
+            self.handle_tool_select_key(key);
+            return;
+        }
+
+        if self.transcript_search.is_some() {
+            self.handle_transcript_search_key(key);
+            return;
+        }
+
+        // Ctrl+R search of earlier prompts takes every key until it ends
+        if self.input.search().is_some() {
+            self.handle_history_search_key(key);
+            return;
+        }
+
+        // Ctrl+B: pick a message to branch from
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('b') {
+            if !self.is_streaming {
+                self.branch_select = self.user_message_indices().last().copied();
+                self.scroll_anchor = self.branch_select;
+            }
+            return;
+        }
+
+        // Ctrl+F: search the transcript
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('f') {
+            self.transcript_search = Some(TranscriptSearch::new(self.scroll_offset));
+            return;
+        }
+
+        // Ctrl+T: pick tool output to expand
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('t') {
+            self.tool_select = self.tool_message_indices().last().copied();
+            match self.tool_select {
+                Some(_) => self.scroll_anchor = self.tool_select,
+                None => self.set_system_message("No tool output yet"),
+            }
+            return;
+        }
+
         // Escape: cancel streaming or clear system message
         if key.code == KeyCode::Esc {
             if self.is_streaming {
@@ -344,24 +510,49 @@ impl App {
             return;
         }
 
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        // Ctrl+R: search earlier prompts
+        if ctrl && key.code == KeyCode::Char('r') {
+            self.input.start_search();
+            return;
+        }
+
         // Scrolling
         match key.code {
-            KeyCode::Up => {
-                if key.modifiers.contains(KeyModifiers::NONE) && self.input_text.is_empty() {
-                    self.scroll_up(5);
+            KeyCode::PageUp => {
+                self.scroll_up(20);
+                return;
+            }
+            KeyCode::PageDown => {
+                self.scroll_down(20);
+                return;
+            }
+            _ => {}
+        }
+        if ctrl || alt {
+            match key.code {
+                KeyCode::Up => {
+                    self.message_jump = Some(MessageJump::PreviousUser);
                     return;
                 }
-            }
-            KeyCode::Down => {
-                if key.modifiers.contains(KeyModifiers::NONE) && self.input_text.is_empty() {
-                    self.scroll_down(5);
+                KeyCode::Down => {
+                    self.message_jump = Some(MessageJump::NextUser);
                     return;
                 }
+                _ => {}
             }
-            _ => {}
         }
-        if key.modifiers.contains(KeyModifiers::CONTROL) {
+        if ctrl {
             match key.code {
+                KeyCode::Home => {
+                    self.scroll_anchor = Some(0);
+                    return;
+                }
+                KeyCode::End => {
+                    self.scroll_offset = 0;
+                    return;
+                }
                 KeyCode::Char('u') => {
                     self.scroll_up(20);
                     return;
@@ -376,20 +567,30 @@ impl App {
 
         // Input handling
         match key.code {
-            KeyCode::Enter => {
-                if !self.is_streaming {
-                    self.submit_input();
+            KeyCode::Enter if key.modifiers.intersects(KeyModifiers::SHIFT | KeyModifiers::ALT) => {
+                self.input.insert_char('\n');
+            }
+            KeyCode::Enter if !self.is_streaming => self.submit_input(),
+            KeyCode::Char('j') if ctrl => self.input.insert_char('\n'),
+            KeyCode::Char('g') if ctrl => self.pending_editor = Some(EditorRequest::Input),
+            KeyCode::Char('w') if ctrl => self.input.delete_word_back(),
+            KeyCode::Char('o') if ctrl => {
+                let expanded = self.input.expand_paste_at_cursor();
+                if !expanded {
+                    self.set_system_message("Move the cursor next to a pasted block to expand it");
                 }
             }
-            KeyCode::Char(c) => {
-                self.input_text.insert(self.input_cursor, c);
-                self.input_cursor += c.len_utf8();
+            KeyCode::Char('b') if alt => self.input.word_left(),
+            KeyCode::Char('f') if alt => self.input.word_right(),
+            KeyCode::Backspace if alt || ctrl => self.input.delete_word_back(),
+            KeyCode::Char(c) if !ctrl => {
+                self.input.insert_char(c);
 
                 // Check for '/' at start of input → open command palette
-                if self.input_text == "/" {
-                    self.input_text.clear();
-                    self.input_cursor = 0;
+                if self.input.text == "/" {
+                    self.input.clear();
                     self.palette_state = CommandPaletteState::new();
+                    self.palette_state.models = self.config.models();
                     self.overlay = Overlay::CommandPalette;
                 }
                 // Check for '@' → open file picker
@@ -397,57 +598,259 @@ impl App {
                     self.file_picker_state = FilePickerState::new();
                     self.overlay = Overlay::FilePicker;
                     // Remove the '@' we just inserted
-                    self.input_cursor -= 1;
-                    self.input_text.remove(self.input_cursor);
+                    self.input.backspace();
                 }
             }
-            KeyCode::Backspace => {
-                if self.input_cursor > 0 {
-                    // Find the previous char boundary
-                    let prev = self.input_text[..self.input_cursor]
-                        .char_indices()
-                        .next_back()
-                        .map(|(i, _)| i)
-                        .unwrap_or(0);
-                    self.input_text.remove(prev);
-                    self.input_cursor = prev;
-                }
+            KeyCode::Backspace => self.input.backspace(),
+            KeyCode::Delete => self.input.delete(),
+            KeyCode::Left if ctrl || alt => self.input.word_left(),
+            KeyCode::Right if ctrl || alt => self.input.word_right(),
+            KeyCode::Left => self.input.left(),
+            KeyCode::Right => self.input.right(),
+            // Up/Down move between lines, and past the first or last line
+            // through earlier prompts
+            KeyCode::Up => {
+                self.input.up_or_history();
+            }
+            KeyCode::Down => {
+                self.input.down_or_history();
+            }
+            KeyCode::Home => self.input.line_start(),
+            KeyCode::End => self.input.line_end(),
+            _ => {}
+        }
+    }
+
+    fn handle_history_search_key(&mut self, key: KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('r') if ctrl => self.input.search_older(),
+            KeyCode::Char('g') if ctrl => self.input.cancel_search(),
+            KeyCode::Esc => self.input.cancel_search(),
+            KeyCode::Char(c) if !ctrl => self.input.search_push(c),
+            KeyCode::Backspace => self.input.search_pop(),
+            _ => self.input.accept_search(),
+        }
+    }
+
+    /// Bracketed paste: insert as typed text, or as a collapsed block when large.
+    fn handle_paste(&mut self, text: &str) {
+        match self.screen {
+            AppScreen::Chat
+                if self.overlay == Overlay::None
+                    && self.branch_select.is_none()
+                    && self.tool_select.is_none()
+                    && self.transcript_search.is_none() =>
+            {
+                self.input.paste(text);
             }
-            KeyCode::Delete => {
-                if self.input_cursor < self.input_text.len() {
-                    self.input_text.remove(self.input_cursor);
+            // Other screens take pastes as keystrokes
+            _ => {
+                for c in text.chars().filter(|c| !c.is_control()) {
+                    self.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
                 }
             }
-            KeyCode::Left => {
-                if self.input_cursor > 0 {
-                    // Move to previous char boundary
-                    self.input_cursor = self.input_text[..self.input_cursor]
-                        .char_indices()
-                        .next_back()
-                        .map(|(i, _)| i)
-                        .unwrap_or(0);
+        }
+    }
+
+    /// Keys while choosing a message to branch from: ↑↓ move between your
+    /// messages, Enter forks before the selected one, Esc cancels.
+    fn handle_branch_key(&mut self, key: KeyEvent) {
+        let Some(current) = self.branch_select else {
+            return;
+        };
+        let users = self.user_message_indices();
+        let pos = users.iter().position(|&i| i == current).unwrap_or(0);
+        match key.code {
+            KeyCode::Up => {
+                self.branch_select = users.get(pos.saturating_sub(1)).copied();
+                self.scroll_anchor = self.branch_select;
+            }
+            KeyCode::Down => {
+                self.branch_select = users.get((pos + 1).min(users.len().saturating_sub(1))).copied();
+                self.scroll_anchor = self.branch_select;
+            }
+            KeyCode::Enter => {
+                self.branch_select = None;
+                let msg = &self.messages[current];
+                match msg.stored_id {
+                    Some(id) => self.fork_before(Some((id, msg.content.clone()))),
+                    None => self.set_system_message("This message is not saved yet"),
                 }
             }
-            KeyCode::Right => {
-                if self.input_cursor < self.input_text.len() {
-                    // Move to next char boundary
-                    self.input_cursor = self.input_text[self.input_cursor..]
-                        .char_indices()
-                        .nth(1)
-                        .map(|(i, _)| self.input_cursor + i)
-                        .unwrap_or(self.input_text.len());
+            KeyCode::Esc => {
+                self.branch_select = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Keys while choosing tool output: ↑↓ move between tool results, Enter
+    /// or Space expands or collapses the selected one, `o` opens it in the
+    /// pager, `y` copies it, `c` collapses or restores all tool output, Esc
+    /// leaves.
+    fn handle_tool_select_key(&mut self, key: KeyEvent) {
+        let Some(current) = self.tool_select else {
+            return;
+        };
+        let tools = self.tool_message_indices();
+        let pos = tools.iter().position(|&i| i == current).unwrap_or(0);
+        match key.code {
+            KeyCode::Up => {
+                self.tool_select = tools.get(pos.saturating_sub(1)).copied();
+                self.scroll_anchor = self.tool_select;
+            }
+            KeyCode::Down => {
+                self.tool_select = tools.get((pos + 1).min(tools.len().saturating_sub(1))).copied();
+                self.scroll_anchor = self.tool_select;
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if !self.expanded_tools.remove(&current) {
+                    self.expanded_tools.insert(current);
                 }
+                self.scroll_anchor = Some(current);
             }
-            KeyCode::Home => {
-                self.input_cursor = 0;
+            KeyCode::Char('o') => self.open_tool_pager(current),
+            KeyCode::Char('y') => {
+                let text = self.messages[current].content.clone();
+                self.copy_to_clipboard(&text, "tool output");
             }
-            KeyCode::End => {
-                self.input_cursor = self.input_text.len();
+            KeyCode::Char('c') => {
+                self.tools_collapsed = !self.tools_collapsed;
+                self.expanded_tools.clear();
+                self.scroll_anchor = Some(current);
             }
+            KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => self.tool_select = None,
+            KeyCode::Esc => self.tool_select = None,
             _ => {}
         }
     }
 
+    /// Keys while searching the transcript: typing refines the query and
+    /// shows the last match, ↑/Ctrl+P/Ctrl+F go to earlier matches and
+    /// ↓/Ctrl+N to later ones, Enter stays at the match, Esc goes back.
+    /// Matches are found and scrolled to by `show_transcript_match`.
+    fn handle_transcript_search_key(&mut self, key: KeyEvent) {
+        let Some(search) = &mut self.transcript_search else {
+            return;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => {
+                self.scroll_offset = search.saved_offset;
+                self.transcript_search = None;
+                return;
+            }
+            KeyCode::Enter => {
+                self.transcript_search = None;
+                return;
+            }
+            KeyCode::Up => search.older(),
+            KeyCode::Char('p' | 'f') if ctrl => search.older(),
+            KeyCode::Down => search.newer(),
+            KeyCode::Char('n') if ctrl => search.newer(),
+            KeyCode::Backspace => {
+                search.query.pop();
+                search.stale = true;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                search.query.push(c);
+                search.stale = true;
+            }
+            _ => return,
+        }
+        search.reveal = true;
+    }
+
+    /// Find the transcript search query in `lines`, the transcript as drawn,
+    /// if it changed, and scroll the current match to the top of a chat area
+    /// `height` rows tall.
+    pub fn show_transcript_match(&mut self, lines: &[String], height: u16) {
+        let Some(search) = &mut self.transcript_search else {
+            return;
+        };
+        if search.stale {
+            search.update(lines);
+        }
+        search.reveal = false;
+        match search.current_match() {
+            Some((line, _)) => {
+                let offset = lines.len().saturating_sub(line + height as usize);
+                self.scroll_offset = offset.min(u16::MAX as usize) as u16;
+            }
+            None if search.query.is_empty() => self.scroll_offset = search.saved_offset,
+            None => {}
+        }
+    }
+
+    /// User message to bring to the top for `jump`, from the message now at
+    /// the top. `None` with nothing further in that direction.
+    pub fn user_message_from(&self, top: usize, jump: MessageJump) -> Option<usize> {
+        let users = self.user_message_indices();
+        match jump {
+            MessageJump::PreviousUser => users.into_iter().rev().find(|&i| i < top),
+            MessageJump::NextUser => users.into_iter().find(|&i| i > top),
+        }
+    }
+
+    fn copy_to_clipboard(&mut self, text: &str, what: &str) {
+        match clipboard::copy(text, self.config.clipboard) {
+            Ok(copied) => {
+                let lines = text.lines().count();
+                let plural = if lines == 1 { "" } else { "s" };
+                self.set_system_message(match copied {
+                    Copied::Tool(tool) => format!("Copied {} ({} line{}) via {}", what, lines, plural, tool),
+                    Copied::Terminal => format!("Sent {} ({} line{}) to the terminal", what, lines, plural),
+                });
+            }
+            Err(e) => self.set_system_message(format!("Could not copy {}: {}", what, e)),
+        }
+    }
+
+    /// How much of the tool result at `index` the transcript shows.
+    pub fn tool_detail(&self, index: usize) -> ToolDetail {
+        if self.expanded_tools.contains(&index) {
+            ToolDetail::Full
+        } else if self.tools_collapsed {
+            ToolDetail::Collapsed
+        } else {
+            ToolDetail::Preview
+        }
+    }
+
+    /// Show the tool result at `index`, with its call's arguments, in the
+    /// full-screen pager.
+    fn open_tool_pager(&mut self, index: usize) {
+        let Some(msg) = self.messages.get(index) else {
+            return;
+        };
+        let theme = get_theme(self.theme_name());
+        let args = tool_view::call_arguments(&self.messages, index);
+        // Wide enough that nothing is truncated; the pager pans instead
+        let lines = tool_view::render_tool_result_lines(msg, args, theme, u16::MAX, ToolDetail::Full, false);
+        let title = msg.tool_name.clone().unwrap_or_else(|| "tool".to_string());
+        self.pager_state = Some(PagerState::new(title, lines.into_iter().skip(1).collect()));
+        self.overlay = Overlay::Pager;
+    }
+
+    fn tool_message_indices(&self) -> Vec<usize> {
+        self.messages
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.role == MessageRole::Tool)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    fn user_message_indices(&self) -> Vec<usize> {
+        self.messages
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.role == MessageRole::User)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
     fn handle_overlay_key(&mut self, key: KeyEvent) {
         match self.overlay.clone() {
             Overlay::CommandPalette => {
@@ -458,22 +861,22 @@ impl App {
                     }
                     PaletteAction::Execute(cmd) => {
                         self.overlay = Overlay::None;
-                        let result = handle_command(&cmd);
+                        let result = handle_command(&cmd, &self.config.models());
                         self.apply_command_result(result);
                     }
                     PaletteAction::SetTheme(theme) => {
                         self.overlay = Overlay::None;
                         self.config.theme = theme.clone();
-                        let _ = save_config(&self.config);
+                        let _ = update_config(serde_json::json!({ "theme": theme }));
                         self.set_system_message(format!("Theme changed to {}", theme));
                     }
                     PaletteAction::SetModel(model) => {
                         self.overlay = Overlay::None;
                         self.config.model = model.clone();
                         if let Some(engine) = &mut self.engine {
-                            engine.set_model(&model);
+                            engine.set_model(self.config.model_info());
                         }
-                        let _ = save_config(&self.config);
+                        let _ = update_config(serde_json::json!({ "model": model }));
                         self.set_system_message(format!("Model changed to {}", model));
                     }
                     PaletteAction::None => {}
@@ -487,40 +890,40 @@ impl App {
                     }
                     FilePickerAction::Select(path) => {
                         self.overlay = Overlay::None;
-                        let insert = format!("@{} ", path);
-                        self.input_text.insert_str(self.input_cursor, &insert);
-                        self.input_cursor += insert.len();
+                        self.input.insert_str(&format!("@{} ", path));
                     }
                     FilePickerAction::TabComplete(_) => {}
                     FilePickerAction::None => {}
                 }
             }
-            Overlay::SessionList { mut selected } => {
-                if key.code == KeyCode::Esc {
-                    self.overlay = Overlay::None;
-                    return;
-                }
-                let sessions = self.list_sessions();
-                match key.code {
-                    KeyCode::Up => {
-                        if selected > 0 {
-                            selected -= 1;
-                        }
-                        self.overlay = Overlay::SessionList { selected };
+            Overlay::SessionList => {
+                let action = session_list::handle_key(&mut self.session_list_state, key);
+                match action {
+                    SessionListAction::Close => {
+                        self.overlay = Overlay::None;
                     }
-                    KeyCode::Down => {
-                        if selected < sessions.len().saturating_sub(1) {
-                            selected += 1;
-                        }
-                        self.overlay = Overlay::SessionList { selected };
+                    SessionListAction::Open(session_id) => {
+                        self.overlay = Overlay::None;
+                        self.load_session(&session_id, None);
                     }
-                    KeyCode::Enter => {
-                        if let Some(session) = sessions.get(selected) {
-                            self.load_session(&session.0.clone());
-                        }
+                    SessionListAction::OpenAt { session_id, message_id } => {
                         self.overlay = Overlay::None;
+                        self.load_session(&session_id, Some(message_id));
+                    }
+                    SessionListAction::QueryChanged => {
+                        self.refresh_session_search();
+                    }
+                    SessionListAction::ToggleScope => {
+                        let sessions = self.load_session_list(self.session_list_state.all_projects);
+                        self.session_list_state.set_sessions(sessions);
+                    }
+                    SessionListAction::Rename { session_id, name } => {
+                        self.rename_session(&session_id, &name);
                     }
-                    _ => {}
+                    SessionListAction::TogglePin(session_id) => {
+                        self.toggle_session_pin(&session_id);
+                    }
+                    SessionListAction::None => {}
                 }
             }
             Overlay::AgentQuestion => {
@@ -557,6 +960,29 @@ impl App {
                     }
                 }
             }
+            Overlay::Pager => {
+                if let Some(ref mut state) = self.pager_state {
+                    if pager::handle_key(state, key) == PagerAction::Close {
+                        self.overlay = Overlay::None;
+                        self.pager_state = None;
+                    }
+                }
+            }
+            Overlay::Confirm(kind) => match confirm::handle_key(key) {
+                ConfirmAction::Yes => {
+                    self.overlay = Overlay::None;
+                    self.confirm_state = None;
+                    match kind {
+                        ConfirmKind::Handoff(focus) => self.start_handoff(focus),
+                    }
+                }
+                ConfirmAction::No => {
+                    self.overlay = Overlay::None;
+                    self.confirm_state = None;
+                    self.set_system_message("Staying in this session. Use /compact or /handoff when ready");
+                }
+                ConfirmAction::None => {}
+            },
             Overlay::None => {}
         }
     }
@@ -597,16 +1023,15 @@ impl App {
     }
 
     fn submit_input(&mut self) {
-        let text = self.input_text.trim().to_string();
+        let text = self.input.expanded_text().trim().to_string();
         if text.is_empty() {
             return;
         }
+        self.input.take();
 
         // Check for slash commands
         if text.starts_with('/') {
-            let result = handle_command(&text);
-            self.input_text.clear();
-            self.input_cursor = 0;
+            let result = handle_command(&text, &self.config.models());
             self.apply_command_result(result);
             return;
         }
@@ -624,29 +1049,22 @@ impl App {
             is_streaming: false,
             tool_status: None,
             tool_name: None,
+            tool_meta: None,
+            tool_call_id: None,
+            stored_id: None,
         });
 
         // Reset scroll to bottom
         self.scroll_offset = 0;
 
-        // Clear input
-        self.input_text.clear();
-        self.input_cursor = 0;
-
         // Start streaming
         self.start_streaming(text, file_context);
     }
 
     fn start_streaming(&mut self, user_input: String, file_context: Option<String>) {
-        let Some(engine) = self.engine.take() else {
+        if self.engine.is_none() {
             return;
-        };
-
-        self.is_streaming = true;
-        self.cancel_token = CancellationToken::new();
-
-        let (event_tx, event_rx) = mpsc::unbounded_channel();
-        self.chat_event_rx = Some(event_rx);
+        }
 
         // Add placeholder assistant message
         self.messages.push(DisplayMessage {
@@ -657,23 +1075,101 @@ impl App {
             is_streaming: true,
             tool_status: None,
             tool_name: None,
+            tool_meta: None,
+            tool_call_id: None,
+            stored_id: None,
+        });
+
+        self.run_engine_task(move |mut engine, event_tx| async move {
+            let _ = engine
+                .send_message(&user_input, file_context.as_deref(), event_tx)
+                .await;
+            engine
         });
+    }
+
+    /// Hand the engine to a background task that reports through chat events
+    /// and returns the engine via oneshot when done.
+    fn run_engine_task<F, Fut>(&mut self, task: F)
+    where
+        F: FnOnce(ChatEngine, mpsc::UnboundedSender<ChatEvent>) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = ChatEngine> + Send + 'static,
+    {
+        let Some(mut engine) = self.engine.take() else {
+            return;
+        };
+
+        self.is_streaming = true;
+        self.cancel_token = CancellationToken::new();
+        engine.set_cancel_token(self.cancel_token.clone());
+
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        self.chat_event_rx = Some(event_rx);
 
-        // Spawn the streaming task and return the engine via oneshot
         let (engine_tx, engine_rx) = oneshot::channel();
         self.engine_return_rx = Some(engine_rx);
 
-        let mut engine_owned = engine;
-        engine_owned.set_cancel_token(self.cancel_token.clone());
-        let file_ctx = file_context;
         tokio::spawn(async move {
-            let _ = engine_owned
-                .send_message(&user_input, file_ctx.as_deref(), event_tx)
-                .await;
-            let _ = engine_tx.send(engine_owned);
+            let engine = task(engine, event_tx).await;
+            let _ = engine_tx.send(engine);
+        });
+    }
+
+    /// Summarize older history now, keeping the recent messages.
+    fn start_compaction(&mut self, focus: Option<String>) {
+        if self.is_streaming || self.engine.is_none() {
+            self.set_system_message("Wait for the response to finish before compacting");
+            return;
+        }
+        self.set_system_message("Compacting context...");
+        self.run_engine_task(move |mut engine, event_tx| async move {
+            let error = match engine.compact(focus.as_deref(), &event_tx).await {
+                Ok(true) => None,
+                Ok(false) => Some("Nothing to compact yet".to_string()),
+                Err(e) => Some(format!("Compaction failed: {}", e)),
+            };
+            if let Some(msg) = error {
+                let _ = event_tx.send(ChatEvent::Error(msg));
+            }
+            engine
+        });
+    }
+
+    /// Ask whether to continue in a new session that starts from a summary.
+    fn offer_handoff(&mut self, focus: Option<String>, message: &str) {
+        self.confirm_state = Some(ConfirmState::new("Continue in a new session?", message));
+        self.overlay = Overlay::Confirm(ConfirmKind::Handoff(focus));
+    }
+
+    /// Summarize the conversation; the new session is started once the
+    /// summary arrives and the engine is back.
+    fn start_handoff(&mut self, focus: Option<String>) {
+        if self.is_streaming || self.engine.is_none() {
+            self.set_system_message("Wait for the response to finish before starting a new session");
+            return;
+        }
+        self.set_system_message("Summarizing the conversation...");
+        self.run_engine_task(move |engine, event_tx| async move {
+            let event = match engine.summarize_for_handoff(focus.as_deref()).await {
+                Ok((summary, summarized)) => ChatEvent::HandoffReady { summary, summarized },
+                Err(e) => ChatEvent::Error(format!("Could not summarize the session: {}", e)),
+            };
+            let _ = event_tx.send(event);
+            engine
         });
     }
 
+    /// Switch to a new session seeded with the summary from `start_handoff`.
+    fn finish_handoff(&mut self, summary: String, summarized: usize) {
+        self.new_session();
+        if let Some(engine) = &mut self.engine {
+            engine.start_from_summary(summary, summarized);
+            self.context_tokens = engine.context_tokens() as u64;
+        }
+        self.apply_command_result(CommandResult::Message(chat::handoff_notice(summarized)));
+        self.set_system_message("Continued in a new session from a summary");
+    }
+
     /// Poll for chat events from the streaming task.
     pub fn poll_chat_events(&mut self) {
         // Drain any pending chat events
@@ -703,6 +1199,7 @@ impl App {
                 self.chat_event_rx = None;
                 // Refresh quota in background after streaming completes
                 self.start_quota_refresh();
+                self.start_title_generation();
             }
         }
 
@@ -714,6 +1211,9 @@ impl App {
                 match rx.try_recv() {
                     Ok(engine) => {
                         self.engine = Some(engine);
+                        if let Some((summary, summarized)) = self.pending_handoff.take() {
+                            self.finish_handoff(summary, summarized);
+                        }
                     }
                     Err(oneshot::error::TryRecvError::Empty) => {
                         // Engine hasn't been returned yet — put receiver back, retry next tick.
@@ -725,6 +1225,10 @@ impl App {
                 }
             }
         }
+
+        if self.handoff_due {
+            self.offer_due_handoff();
+        }
     }
 
     /// Poll for a completed background quota refresh.
@@ -763,6 +1267,80 @@ impl App {
         });
     }
 
+    /// Name a still-untitled session from its first exchange, in the background.
+    fn start_title_generation(&mut self) {
+        if !self.config.auto_title
+            || self.config.api_key.is_empty()
+            || self.title_rx.is_some()
+            || self.session_name != DEFAULT_SESSION_NAME
+        {
+            return;
+        }
+        let Some(session_id) = self.session_id.clone() else {
+            return;
+        };
+        if self.titled_session.as_deref() == Some(session_id.as_str()) {
+            return;
+        }
+        let first = |role: MessageRole| {
+            self.messages
+                .iter()
+                .find(|m| m.role == role && !m.content.trim().is_empty())
+                .map(|m| m.content.clone())
+        };
+        let (Some(user), Some(assistant)) = (first(MessageRole::User), first(MessageRole::Assistant)) else {
+            return;
+        };
+
+        self.titled_session = Some(session_id.clone());
+        let client = MiniMaxClient::new(&self.config.api_key);
+        let model = self.config.title_model.clone().unwrap_or_else(|| self.config.model.clone());
+        let (tx, rx) = oneshot::channel();
+        self.title_rx = Some(rx);
+
+        tokio::spawn(async move {
+            let result = match tokio::time::timeout(
+                Duration::from_secs(30),
+                title::generate_title(&client, &model, &user, &assistant),
+            )
+            .await
+            {
+                Ok(inner) => inner.map_err(|e| e.to_string()),
+                Err(_) => Err("Title generation timed out after 30s".to_string()),
+            };
+            let _ = tx.send((session_id, result));
+        });
+    }
+
+    /// Apply a completed automatic title, unless the session was renamed
+    /// in the meantime. Failures are ignored: the default name stays.
+    pub fn poll_title(&mut self) {
+        let Some(mut rx) = self.title_rx.take() else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok((session_id, Ok(name))) => {
+                let Some(store) = &self.session_store else {
+                    return;
+                };
+                let untitled = matches!(
+                    store.get_session(&session_id),
+                    Ok(Some(session)) if session.name == DEFAULT_SESSION_NAME
+                );
+                if untitled && store.rename_session(&session_id, &name).is_ok()
+                    && self.session_id.as_deref() == Some(session_id.as_str())
+                {
+                    self.session_name = name;
+                }
+            }
+            Ok((_, Err(_))) => {}
+            Err(oneshot::error::TryRecvError::Empty) => {
+                self.title_rx = Some(rx);
+            }
+            Err(_) => {}
+        }
+    }
+
     fn start_update_check(&mut self) {
         if self.update_check_rx.is_some() {
             return;
@@ -861,6 +1439,9 @@ impl App {
                         is_streaming: true,
                         tool_status: None,
                         tool_name: None,
+                        tool_meta: None,
+                        tool_call_id: None,
+                        stored_id: None,
                     });
                 }
             }
@@ -914,7 +1495,7 @@ impl App {
                     msg.is_streaming = false;
                 }
             }
-            ChatEvent::ToolExecutionStart { id: _, name } => {
+            ChatEvent::ToolExecutionStart { id, name } => {
                 self.messages.push(DisplayMessage {
                     role: MessageRole::Tool,
                     content: String::new(),
@@ -923,12 +1504,17 @@ impl App {
                     is_streaming: false,
                     tool_status: Some(ToolStatus::Running),
                     tool_name: Some(name),
+                    tool_meta: None,
+                    tool_call_id: Some(id),
+                    stored_id: None,
                 });
             }
             ChatEvent::ToolExecutionDone {
                 id: _,
                 name,
                 result,
+                is_error,
+                meta,
             } => {
                 if let Some(msg) = self
                     .messages
@@ -940,8 +1526,9 @@ impl App {
                             && m.tool_status == Some(ToolStatus::Running)
                     })
                 {
-                    let is_error = result.starts_with("Error:");
+                    let is_error = is_error || result.starts_with("Error:");
                     msg.content = result;
+                    msg.tool_meta = meta;
                     msg.tool_status = Some(if is_error {
                         ToolStatus::Error
                     } else {
@@ -960,6 +1547,7 @@ impl App {
                 self.prompt_tokens = prompt_tokens;
                 self.completion_tokens += completion_tokens;
                 self.total_tokens = self.prompt_tokens + self.completion_tokens;
+                self.context_tokens = prompt_tokens + completion_tokens;
                 self.check_token_limits();
             }
             ChatEvent::Error(msg) => {
@@ -982,27 +1570,84 @@ impl App {
                 original_tokens,
                 compressed_tokens,
             } => {
-                self.set_system_message(format!(
-                    "Context compressed: ~{}K → ~{}K tokens",
-                    original_tokens / 1000,
-                    compressed_tokens / 1000
-                ));
+                self.context_tokens = compressed_tokens as u64;
+                // Shown in the transcript, like the marker a resumed session restores
+                self.messages.push(DisplayMessage {
+                    role: MessageRole::System,
+                    content: chat::compression_notice(original_tokens, compressed_tokens),
+                    reasoning: None,
+                    tool_calls: Vec::new(),
+                    is_streaming: false,
+                    tool_status: None,
+                    tool_name: None,
+                    tool_meta: None,
+                    tool_call_id: None,
+                    stored_id: None,
+                });
+            }
+            ChatEvent::ContextEstimate(tokens) => {
+                self.context_tokens = tokens as u64;
+            }
+            ChatEvent::UserMessageSaved(id) => {
+                if let Some(msg) = self
+                    .messages
+                    .iter_mut()
+                    .rev()
+                    .find(|m| m.role == MessageRole::User && m.stored_id.is_none())
+                {
+                    msg.stored_id = Some(id);
+                }
+            }
+            ChatEvent::HandoffReady { summary, summarized } => {
+                // The engine may already be back if the task returned first
+                if self.engine.is_some() {
+                    self.finish_handoff(summary, summarized);
+                } else {
+                    self.pending_handoff = Some((summary, summarized));
+                }
             }
         }
     }
 
-    /// Check token limits and show warning or auto-new-session.
+    /// Warn as the context window fills up, and note that a handoff is due
+    /// once it is nearly full. Usage arrives mid-turn, so the offer itself
+    /// waits for `offer_due_handoff`.
     fn check_token_limits(&mut self) {
-        if self.total_tokens >= TOKEN_LIMIT {
-            self.set_system_message("Token limit reached. Starting new session...");
-            self.new_session();
-        } else if self.total_tokens >= TOKEN_WARNING_THRESHOLD {
+        let limit = self.config.model_info().context_window as f64;
+        let used = self.context_tokens as f64;
+        if used >= limit * HANDOFF_OFFER_RATIO && !self.handoff_offered {
+            self.handoff_due = true;
+        } else if used >= limit * TOKEN_WARNING_RATIO {
             self.set_system_message(
-                "Warning: Approaching token limit. Consider starting a new session with /new"
+                "Warning: Context window almost full. Use /compact to summarize older messages or /handoff to continue in a new session"
             );
         }
     }
 
+    /// Offer the handoff noted by `check_token_limits` once the turn has
+    /// finished: the stream is closed, the engine is back and no overlay is
+    /// open. Dropped if compaction has since made room.
+    fn offer_due_handoff(&mut self) {
+        let turn_finished = !self.is_streaming
+            && self.chat_event_rx.is_none()
+            && self.engine.is_some()
+            && self.pending_handoff.is_none();
+        if !turn_finished || self.overlay != Overlay::None {
+            return;
+        }
+        self.handoff_due = false;
+        let limit = self.config.model_info().context_window as f64;
+        if (self.context_tokens as f64) < limit * HANDOFF_OFFER_RATIO {
+            return;
+        }
+        self.handoff_offered = true;
+        self.offer_handoff(
+            None,
+            "The context window is almost full.\n\
+             Summarize this conversation and continue in a new session?",
+        );
+    }
+
     fn apply_command_result(&mut self, result: CommandResult) {
         match result {
             CommandResult::Message(msg) => {
@@ -1014,6 +1659,9 @@ impl App {
                     is_streaming: false,
                     tool_status: None,
                     tool_name: None,
+                    tool_meta: None,
+                    tool_call_id: None,
+                    stored_id: None,
                 });
             }
             CommandResult::NewSession => {
@@ -1021,6 +1669,8 @@ impl App {
             }
             CommandResult::Clear => {
                 self.messages.clear();
+                self.expanded_tools.clear();
+                self.tool_select = None;
                 if let Some(engine) = &mut self.engine {
                     engine.clear();
                 }
@@ -1029,23 +1679,152 @@ impl App {
                 self.should_quit = true;
             }
             CommandResult::Sessions => {
-                self.overlay = Overlay::SessionList { selected: 0 };
+                self.open_session_list(String::new());
+            }
+            CommandResult::Search(query) => {
+                self.open_session_list(query);
             }
             CommandResult::Config => {
                 self.config_menu_state = ConfigMenuState::new();
+                self.config_menu_state.models = self.config.models();
                 self.screen = AppScreen::ConfigMenu;
             }
             CommandResult::SetModel(model) => {
                 self.config.model = model.clone();
                 if let Some(engine) = &mut self.engine {
-                    engine.set_model(&model);
+                    engine.set_model(self.config.model_info());
                 }
-                let _ = save_config(&self.config);
+                let _ = update_config(serde_json::json!({ "model": model }));
                 self.set_system_message(format!("Model changed to {}", model));
             }
+            CommandResult::Export { format, include_reasoning, path } => {
+                let msg = match self.export_current_session(format, include_reasoning, path) {
+                    Ok(path) => format!("Exported session to {}", path),
+                    Err(e) => format!("Export failed: {}", e),
+                };
+                self.set_system_message(msg);
+            }
+            CommandResult::Fork(user_turn) => {
+                self.fork_current_session(user_turn);
+            }
+            CommandResult::Usage => {
+                let (Some(store), Some(session_id)) = (&self.session_store, &self.session_id) else {
+                    return;
+                };
+                let msg = match store.usage_report(session_id) {
+                    Ok(report) => usage::format_report(&report, &self.config.model_prices()),
+                    Err(e) => format!("Could not read usage: {}", e),
+                };
+                self.apply_command_result(CommandResult::Message(msg));
+            }
+            CommandResult::Context => {
+                let cwd = std::env::current_dir().unwrap_or_default();
+                let config = &self.config.instructions;
+                let loaded = Instructions::load(&cwd, &config_dir(), config);
+                let summary = loaded.summary(&config.file_names);
+                if let Some(engine) = &mut self.engine {
+                    engine.reload_instructions();
+                    self.context_tokens = engine.context_tokens() as u64;
+                }
+                self.apply_command_result(CommandResult::Message(summary));
+            }
+            CommandResult::Trust => {
+                if self.config.untrusted_mcp_servers.is_empty() {
+                    self.set_system_message("No checked-in MCP servers are waiting to be trusted");
+                    return;
+                }
+                let root = PathBuf::from(self.project.key());
+                match trust_project(&root) {
+                    Ok(()) => {
+                        let mut names: Vec<&str> =
+                            self.config.untrusted_mcp_servers.keys().map(String::as_str).collect();
+                        names.sort();
+                        let msg = format!(
+                            "Trusted {}. MCP servers {} start the next time minmax-code is started here",
+                            root.display(),
+                            names.join(", ")
+                        );
+                        self.set_system_message(msg);
+                    }
+                    Err(e) => self.set_system_message(format!("Could not save trusted project: {}", e)),
+                }
+            }
+            CommandResult::Open(arg) => {
+                let location = match arg {
+                    Some(arg) => Some(external_editor::parse_location(&arg)),
+                    None => self
+                        .messages
+                        .iter()
+                        .rev()
+                        .find_map(|m| m.tool_meta.as_ref().and_then(external_editor::edit_location)),
+                };
+                match location {
+                    Some((path, line)) => self.pending_editor = Some(EditorRequest::File { path, line }),
+                    None => self.set_system_message("No file has been edited in this session yet"),
+                }
+            }
+            CommandResult::Copy(target) => {
+                let (role, what) = match target {
+                    CopyTarget::Reply => (MessageRole::Assistant, "reply"),
+                    CopyTarget::ToolOutput => (MessageRole::Tool, "tool output"),
+                };
+                let text = self
+                    .messages
+                    .iter()
+                    .rev()
+                    .find(|m| m.role == role && !m.content.is_empty())
+                    .map(|m| m.content.clone());
+                match text {
+                    Some(text) => self.copy_to_clipboard(&text, what),
+                    None => self.set_system_message(format!("No {} to copy yet", what)),
+                }
+            }
+            CommandResult::Yank(n) => {
+                let blocks: Vec<String> = self
+                    .messages
+                    .iter()
+                    .filter(|m| m.role == MessageRole::Assistant)
+                    .flat_map(|m| markdown::code_blocks(&m.content))
+                    .collect();
+                let n = n.unwrap_or(blocks.len());
+                match n.checked_sub(1).and_then(|i| blocks.get(i)) {
+                    Some(code) => {
+                        let code = code.clone();
+                        self.copy_to_clipboard(&code, &format!("code block {}", n));
+                    }
+                    None if blocks.is_empty() => self.set_system_message("No code blocks in this conversation"),
+                    None => self.set_system_message(format!(
+                        "No code block {}; the chat has {}",
+                        n,
+                        blocks.len()
+                    )),
+                }
+            }
+            CommandResult::Compact(focus) => {
+                self.start_compaction(focus);
+            }
+            CommandResult::Handoff(focus) => {
+                self.offer_handoff(focus, "Summarize this conversation and continue in a new session?");
+            }
+            CommandResult::Import(path) => {
+                let Some(store) = self.session_store.clone() else {
+                    return;
+                };
+                let imported = std::fs::read_to_string(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|json| export::import_json(&store, &json, Some(&self.project)));
+                match imported {
+                    Ok(session) => {
+                        self.load_session(&session.id, None);
+                        self.session_name = session.name;
+                        self.set_system_message(format!("Imported session from {}", path));
+                    }
+                    Err(e) => self.set_system_message(format!("Import failed: {}", e)),
+                }
+            }
             CommandResult::SetTheme(theme) => {
                 self.config.theme = theme.clone();
-                let _ = save_config(&self.config);
+                let _ = update_config(serde_json::json!({ "theme": theme }));
                 self.set_system_message(format!("Theme changed to {}", theme));
             }
             CommandResult::None => {}
@@ -1054,17 +1833,22 @@ impl App {
 
     fn new_session(&mut self) {
         self.messages.clear();
+        self.expanded_tools.clear();
+        self.tool_select = None;
         self.todo_items.clear();
         self.total_tokens = 0;
         self.prompt_tokens = 0;
         self.completion_tokens = 0;
+        self.context_tokens = 0;
+        self.handoff_offered = false;
+        self.handoff_due = false;
         self.scroll_offset = 0;
 
         // Refresh quota in background (account-level, not session-level)
         self.start_quota_refresh();
 
         if let Some(store) = &self.session_store {
-            if let Ok(session) = store.create_session(&self.config.model) {
+            if let Ok(session) = store.create_session(&self.config.model, Some(&self.project)) {
                 self.session_id = Some(session.id.clone());
                 self.session_name = session.name.clone();
                 if let Some(engine) = &mut self.engine {
@@ -1075,27 +1859,153 @@ impl App {
         }
     }
 
-    pub fn list_sessions(&self) -> Vec<(String, String, String)> {
-        if let Some(store) = &self.session_store {
-            store
-                .list_sessions()
-                .unwrap_or_default()
-                .into_iter()
-                .map(|s| (s.id, s.name, s.model))
-                .collect()
+    /// Fork the current session and switch to the fork. With `user_turn`, the
+    /// fork stops before that user message (0-based) and its text is put back
+    /// in the input for editing.
+    fn fork_current_session(&mut self, user_turn: Option<usize>) {
+        let Some(turn) = user_turn else {
+            self.fork_before(None);
+            return;
+        };
+        let (Some(store), Some(session_id)) = (&self.session_store, &self.session_id) else {
+            return;
+        };
+        let msgs = store.get_session_messages(session_id).unwrap_or_default();
+        match msgs.iter().filter(|m| m.role == "user").nth(turn) {
+            Some(m) => self.fork_before(Some((m.id, m.content.clone()))),
+            None => self.set_system_message("No such message to branch from"),
+        }
+    }
+
+    /// Fork the session, keeping the stored messages before `before`, a
+    /// message id and the text to put back in the input; all of them when
+    /// `None`.
+    fn fork_before(&mut self, before: Option<(i64, String)>) {
+        if self.is_streaming {
+            self.set_system_message("Wait for the response to finish before forking");
+            return;
+        }
+        let (Some(store), Some(session_id)) = (self.session_store.clone(), self.session_id.clone()) else {
+            return;
+        };
+        let (before, prefill) = before.unzip();
+
+        match store.fork_session(&session_id, before) {
+            Ok(fork) => {
+                self.load_session(&fork.id, None);
+                self.session_name = fork.name;
+                if let Some(text) = prefill {
+                    self.input.set_text(text);
+                }
+                self.set_system_message("Forked session — the original is kept in /sessions");
+            }
+            Err(e) => self.set_system_message(format!("Fork failed: {}", e)),
+        }
+    }
+
+    /// Write the current session to `path` (default: a file named after the
+    /// session in the working directory). Returns the path written.
+    fn export_current_session(
+        &self,
+        format: export::ExportFormat,
+        include_reasoning: bool,
+        path: Option<String>,
+    ) -> Result<String> {
+        let (Some(store), Some(session_id)) = (&self.session_store, &self.session_id) else {
+            anyhow::bail!("no active session");
+        };
+        let session = store
+            .get_session(session_id)?
+            .ok_or_else(|| anyhow::anyhow!("session not found"))?;
+        let content = export::export_session(store, session_id, format, include_reasoning)?;
+        let path = path.unwrap_or_else(|| export::default_file_name(&session, format));
+        std::fs::write(&path, content)?;
+        Ok(path)
+    }
+
+    /// Sessions for the list overlay: this project's, or every project's.
+    fn load_session_list(&self, all_projects: bool) -> Vec<crate::core::session::Session> {
+        let Some(store) = &self.session_store else {
+            return Vec::new();
+        };
+        let sessions = if all_projects {
+            store.list_sessions()
         } else {
-            Vec::new()
+            store.list_project_sessions(&self.project)
+        };
+        sessions.unwrap_or_default()
+    }
+
+    fn rename_session(&mut self, session_id: &str, name: &str) {
+        let Some(store) = &self.session_store else {
+            return;
+        };
+        if let Err(e) = store.rename_session(session_id, name) {
+            self.set_system_message(format!("Rename failed: {}", e));
+            return;
+        }
+        if self.session_id.as_deref() == Some(session_id) {
+            self.session_name = name.to_string();
         }
+        let sessions = self.load_session_list(self.session_list_state.all_projects);
+        self.session_list_state.set_sessions(sessions);
     }
 
-    fn load_session(&mut self, session_id: &str) {
+    fn toggle_session_pin(&mut self, session_id: &str) {
+        let Some(store) = &self.session_store else {
+            return;
+        };
+        let pinned = self
+            .session_list_state
+            .sessions
+            .iter()
+            .any(|s| s.id == session_id && s.pinned);
+        if let Err(e) = store.set_session_pinned(session_id, !pinned) {
+            self.set_system_message(format!("Pin failed: {}", e));
+            return;
+        }
+        let sessions = self.load_session_list(self.session_list_state.all_projects);
+        self.session_list_state.set_sessions(sessions);
+    }
+
+    /// Open the session list overlay (current project first), optionally with
+    /// a search query filled in.
+    fn open_session_list(&mut self, query: String) {
+        self.session_list_state = SessionListState::new(self.load_session_list(false));
+        self.session_list_state.project_name = self.project.name();
+        if let Some(store) = &self.session_store {
+            let rows = store.usage_by_session().unwrap_or_default();
+            self.session_list_state.costs = usage::session_costs(rows, &self.config.model_prices());
+        }
+        self.session_list_state.query = query;
+        self.refresh_session_search();
+        self.overlay = Overlay::SessionList;
+    }
+
+    fn refresh_session_search(&mut self) {
+        let state = &mut self.session_list_state;
+        let hits = match &self.session_store {
+            Some(store) if state.is_searching() => {
+                store.search_messages(&state.query, SEARCH_RESULT_LIMIT).unwrap_or_default()
+            }
+            _ => Vec::new(),
+        };
+        state.set_hits(hits);
+    }
+
+    /// Load a stored session. With `focus_message`, the view scrolls to that message.
+    fn load_session(&mut self, session_id: &str, focus_message: Option<i64>) {
         let Some(store) = &self.session_store else {
             return;
         };
         let msgs = store.get_session_messages(session_id).unwrap_or_default();
 
         self.messages.clear();
+        self.expanded_tools.clear();
+        self.tool_select = None;
         self.session_id = Some(session_id.to_string());
+        self.handoff_offered = false;
+        self.handoff_due = false;
 
         for msg in &msgs {
             let role = match msg.role.as_str() {
@@ -1104,47 +2014,44 @@ impl App {
                 "tool" => MessageRole::Tool,
                 _ => MessageRole::System,
             };
+            let meta = MessageMeta::parse(msg.meta.as_deref());
+            let tool_status = (role == MessageRole::Tool).then(|| {
+                if meta.is_error || msg.content.starts_with("Error:") {
+                    ToolStatus::Error
+                } else {
+                    ToolStatus::Done
+                }
+            });
             self.messages.push(DisplayMessage {
                 role,
                 content: msg.content.clone(),
-                reasoning: None,
-                tool_calls: Vec::new(),
+                reasoning: msg.reasoning.clone().filter(|r| !r.is_empty()),
+                tool_calls: msg
+                    .tool_calls
+                    .as_deref()
+                    .and_then(|tc| serde_json::from_str(tc).ok())
+                    .unwrap_or_default(),
                 is_streaming: false,
-                tool_status: if msg.role == "tool" {
-                    Some(ToolStatus::Done)
-                } else {
-                    None
-                },
+                tool_status,
                 tool_name: msg.name.clone(),
+                tool_meta: meta.tool,
+                tool_call_id: msg.tool_call_id.clone(),
+                stored_id: Some(msg.id),
             });
         }
+        self.todo_items = chat::todo_items_from_stored(&msgs);
 
         // Rebuild engine history
         if let Some(engine) = &mut self.engine {
             engine.clear();
-            let history: Vec<serde_json::Value> = msgs
-                .iter()
-                .map(|m| {
-                    let mut v = serde_json::json!({
-                        "role": m.role,
-                        "content": m.content
-                    });
-                    if let Some(tc) = &m.tool_calls {
-                        if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(tc) {
-                            v["tool_calls"] = parsed;
-                        }
-                    }
-                    if let Some(id) = &m.tool_call_id {
-                        v["tool_call_id"] = serde_json::json!(id);
-                    }
-                    v
-                })
-                .collect();
+            let history = chat::history_from_stored(&msgs, self.config.model_info().reasoning);
             engine.load_history(history);
             engine.set_session(session_id.to_string(), store.clone());
+            self.context_tokens = engine.context_tokens() as u64;
         }
 
         self.scroll_offset = 0;
+        self.scroll_anchor = focus_message.and_then(|id| msgs.iter().position(|m| m.id == id));
     }
 
     /// Check if the engine needs to be initialized (after API key is set).
@@ -1156,11 +2063,9 @@ impl App {
 /// The main run loop.
 pub async fn run(config: AppConfig) -> Result<()> {
     crossterm::terminal::enable_raw_mode()?;
-    crossterm::execute!(
-        std::io::stdout(),
-        crossterm::terminal::EnterAlternateScreen,
-        crossterm::event::EnableMouseCapture,
-    )?;
+    // Lets Shift+Enter be told apart from Enter where the terminal supports it
+    let keyboard_enhanced = crossterm::terminal::supports_keyboard_enhancement().unwrap_or(false);
+    enter_terminal(keyboard_enhanced)?;
 
     let backend = CrosstermBackend::new(std::io::stdout());
     let mut terminal = Terminal::new(backend)?;
@@ -1168,7 +2073,7 @@ pub async fn run(config: AppConfig) -> Result<()> {
     let mut app = App::new(config);
     app.initialize().await?;
 
-    let result = event_loop(&mut terminal, &mut app).await;
+    let result = event_loop(&mut terminal, &mut app, keyboard_enhanced).await;
 
     // Shutdown MCP servers
     if let Some(mcp) = &app.mcp_manager {
@@ -1176,30 +2081,124 @@ pub async fn run(config: AppConfig) -> Result<()> {
         manager.shutdown().await;
     }
 
+    leave_terminal(keyboard_enhanced)?;
+    terminal.show_cursor()?;
+
+    result
+}
+
+fn enter_terminal(keyboard_enhanced: bool) -> Result<()> {
+    crossterm::terminal::enable_raw_mode()?;
+    crossterm::execute!(
+        std::io::stdout(),
+        crossterm::terminal::EnterAlternateScreen,
+        crossterm::event::EnableMouseCapture,
+        crossterm::event::EnableBracketedPaste,
+    )?;
+    if keyboard_enhanced {
+        crossterm::execute!(
+            std::io::stdout(),
+            crossterm::event::PushKeyboardEnhancementFlags(
+                crossterm::event::KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+fn leave_terminal(keyboard_enhanced: bool) -> Result<()> {
+    if keyboard_enhanced {
+        crossterm::execute!(std::io::stdout(), crossterm::event::PopKeyboardEnhancementFlags)?;
+    }
     crossterm::terminal::disable_raw_mode()?;
     crossterm::execute!(
         std::io::stdout(),
         crossterm::terminal::LeaveAlternateScreen,
         crossterm::event::DisableMouseCapture,
+        crossterm::event::DisableBracketedPaste,
     )?;
-    terminal.show_cursor()?;
+    Ok(())
+}
 
-    result
+/// Suspend the TUI, run the editor in the terminal, then restore the TUI
+/// and apply the result.
+fn run_external_editor(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    app: &mut App,
+    request: EditorRequest,
+    keyboard_enhanced: bool,
+) -> Result<()> {
+    leave_terminal(keyboard_enhanced)?;
+    let result = match &request {
+        EditorRequest::Input => external_editor::edit_text(&app.input.expanded_text()).map(Some),
+        EditorRequest::File { path, line } => external_editor::open(path, Some(*line)).map(|_| None),
+    };
+    enter_terminal(keyboard_enhanced)?;
+    terminal.clear()?;
+
+    match (result, request) {
+        (Ok(Some(text)), _) => app.input.set_text(text),
+        (Ok(None), EditorRequest::File { path, .. }) => {
+            app.set_system_message(format!("Closed {}", path.display()));
+        }
+        (Ok(None), EditorRequest::Input) => {}
+        (Err(e), _) => app.set_system_message(format!("Editor failed: {}", e)),
+    }
+    Ok(())
 }
 
 async fn event_loop(
     terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
     app: &mut App,
+    keyboard_enhanced: bool,
 ) -> Result<()> {
+    // Links printed after the last draw; reprinted only when they change
+    let mut printed_links: Vec<Hyperlink> = Vec::new();
     loop {
+        if let Some(request) = app.pending_editor.take() {
+            run_external_editor(terminal, app, request, keyboard_enhanced)?;
+            printed_links.clear();
+        }
+
         // Check if engine needs initialization (after API key prompt)
         if app.needs_engine_init() {
             app.init_engine().await?;
         }
 
-        terminal.draw(|frame| {
-            tui_layout::draw(frame, app);
+        if let Some(jump) = app.message_jump.take() {
+            let size = terminal.size()?;
+            let chat_area = tui_layout::chat_area(app, Rect::new(0, 0, size.width, size.height));
+            let top = chat_view::message_at_top(app, chat_area);
+            match app.user_message_from(top, jump) {
+                Some(index) => app.scroll_anchor = Some(index),
+                // Past the last user message: the end of the conversation
+                None if jump == MessageJump::NextUser => app.scroll_offset = 0,
+                None => {}
+            }
+        }
+
+        if app.transcript_search.as_ref().is_some_and(|s| s.stale || s.reveal) {
+            let size = terminal.size()?;
+            let chat_area = tui_layout::chat_area(app, Rect::new(0, 0, size.width, size.height));
+            let lines = chat_view::transcript_lines(app, chat_area);
+            app.show_transcript_match(&lines, chat_area.height);
+        }
+
+        if let Some(index) = app.scroll_anchor.take() {
+            let size = terminal.size()?;
+            let chat_area = tui_layout::chat_area(app, Rect::new(0, 0, size.width, size.height));
+            app.scroll_offset = chat_view::scroll_offset_for_message(app, chat_area, index);
+        }
+
+        let mut links = Vec::new();
+        let completed = terminal.draw(|frame| {
+            links = tui_layout::draw(frame, app);
         })?;
+        let shown = hyperlink::visible(&links, completed.buffer);
+        if shown != printed_links {
+            hyperlink::print(&shown, completed.buffer)?;
+            printed_links = shown;
+        }
 
         if app.should_quit {
             break;
@@ -1207,14 +2206,188 @@ async fn event_loop(
 
         app.poll_chat_events();
         app.poll_quota();
+        app.poll_title();
         app.poll_update_check();
         app.poll_system_message_expiry();
         app.tick = app.tick.wrapping_add(1);
 
         if crossterm::event::poll(Duration::from_millis(16))? {
             let event = event::read()?;
+            if matches!(event, Event::Resize(..)) {
+                printed_links.clear();
+            }
             app.handle_event(event);
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine() -> ChatEngine {
+        ChatEngine::new(MiniMaxClient::new("test"), "MiniMax-M2.5", Mode::Builder)
+    }
+
+    /// An app whose engine is out on a task, such as a turn or a handoff,
+    /// with the channels the task would use.
+    fn app_with_engine_out() -> (App, mpsc::UnboundedSender<ChatEvent>, oneshot::Sender<ChatEngine>) {
+        let mut app = App::new(AppConfig::default());
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let (engine_tx, engine_rx) = oneshot::channel();
+        app.chat_event_rx = Some(event_rx);
+        app.engine_return_rx = Some(engine_rx);
+        app.is_streaming = true;
+        (app, event_tx, engine_tx)
+    }
+
+    #[test]
+    fn transcript_search_scrolls_to_the_matched_line() {
+        let mut app = App::new(AppConfig::default());
+        app.scroll_offset = 3;
+        app.transcript_search = Some(TranscriptSearch::new(3));
+        for c in "needle".chars() {
+            app.handle_transcript_search_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        let lines: Vec<String> = (0..40)
+            .map(|i| if i % 10 == 5 { format!("  needle {i} needle") } else { String::new() })
+            .collect();
+        app.show_transcript_match(&lines, 10);
+        let search = app.transcript_search.as_ref().unwrap();
+        assert_eq!(search.matches.len(), 8);
+        assert_eq!(search.current_match(), Some((35, 12)));
+        assert_eq!(app.scroll_offset, 0);
+
+        app.handle_transcript_search_key(KeyEvent::from(KeyCode::Up));
+        app.handle_transcript_search_key(KeyEvent::from(KeyCode::Up));
+        app.show_transcript_match(&lines, 10);
+        assert_eq!(app.transcript_search.as_ref().unwrap().current_match(), Some((25, 12)));
+        assert_eq!(app.scroll_offset, 5);
+
+        app.handle_transcript_search_key(KeyEvent::from(KeyCode::Esc));
+        assert_eq!(app.scroll_offset, 3);
+    }
+
+    #[test]
+    fn escape_cancels_history_search_and_restores_the_draft() {
+        let mut app = App::new(AppConfig::default());
+        app.input.set_history(vec!["cargo test".to_string()]);
+        app.input.set_text("draft".to_string());
+        let mode = app.mode;
+
+        app.handle_chat_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        for c in "cargo".chars() {
+            app.handle_chat_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        assert_eq!(app.input.search_match(), Some("cargo test"));
+
+        app.handle_chat_key(KeyEvent::from(KeyCode::Esc));
+        assert!(app.input.search().is_none());
+        assert_eq!(app.input.expanded_text(), "draft");
+        assert_eq!(app.mode, mode);
+    }
+
+    #[test]
+    fn branching_after_clear_forks_before_the_selected_message() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = Arc::new(SessionStore::open_at(dir.path().join("sessions.db")).unwrap());
+        let session = store.create_session("MiniMax-M2.5", None).unwrap();
+        for (role, content) in [("user", "first"), ("assistant", "one"), ("user", "second"), ("assistant", "two")] {
+            store.save_message(&session.id, role, content, None, None, None).unwrap();
+        }
+
+        let mut app = App::new(AppConfig::default());
+        app.session_store = Some(store.clone());
+        app.load_session(&session.id, None);
+        app.apply_command_result(CommandResult::Clear);
+
+        // A new turn after /clear: the only user message on screen
+        let third = store.save_message(&session.id, "user", "third", None, None, None).unwrap();
+        app.messages.push(DisplayMessage {
+            role: MessageRole::User,
+            content: "third".to_string(),
+            reasoning: None,
+            tool_calls: Vec::new(),
+            is_streaming: false,
+            tool_status: None,
+            tool_name: None,
+            tool_meta: None,
+            tool_call_id: None,
+            stored_id: None,
+        });
+        app.process_chat_event(ChatEvent::UserMessageSaved(third));
+        assert_eq!(app.messages[0].stored_id, Some(third));
+
+        app.branch_select = Some(0);
+        app.handle_branch_key(KeyEvent::from(KeyCode::Enter));
+        let fork_id = app.session_id.clone().unwrap();
+        assert_ne!(fork_id, session.id);
+        let kept: Vec<String> = store
+            .get_session_messages(&fork_id)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.content)
+            .collect();
+        assert_eq!(kept, ["first", "one", "second", "two"]);
+        assert_eq!(app.input.expanded_text(), "third");
+    }
+
+    fn handoff_ready() -> ChatEvent {
+        ChatEvent::HandoffReady { summary: "Earlier work".to_string(), summarized: 7 }
+    }
+
+    fn assert_handed_off(app: &App) {
+        assert!(app.pending_handoff.is_none());
+        assert_eq!(app.messages.last().unwrap().content, chat::handoff_notice(7));
+        assert!(app.context_tokens > 0);
+    }
+
+    #[test]
+    fn handoff_finishes_when_the_summary_arrives_first() {
+        let (mut app, event_tx, engine_tx) = app_with_engine_out();
+        event_tx.send(handoff_ready()).unwrap();
+        app.poll_chat_events();
+        assert!(app.pending_handoff.is_some());
+
+        let _ = engine_tx.send(engine());
+        drop(event_tx);
+        app.poll_chat_events();
+        assert_handed_off(&app);
+    }
+
+    #[test]
+    fn handoff_finishes_when_the_engine_returns_first() {
+        let (mut app, event_tx, engine_tx) = app_with_engine_out();
+        let _ = engine_tx.send(engine());
+        app.poll_chat_events();
+        assert!(app.engine.is_some());
+
+        event_tx.send(handoff_ready()).unwrap();
+        drop(event_tx);
+        app.poll_chat_events();
+        assert_handed_off(&app);
+    }
+
+    #[test]
+    fn handoff_is_offered_once_the_turn_finishes() {
+        let (mut app, event_tx, engine_tx) = app_with_engine_out();
+        let window = app.config.model_info().context_window;
+        event_tx
+            .send(ChatEvent::TokenUsage { prompt_tokens: window, completion_tokens: 0, total_tokens: window })
+            .unwrap();
+        app.poll_chat_events();
+        assert!(app.handoff_due);
+        assert_eq!(app.overlay, Overlay::None);
+
+        let _ = engine_tx.send(engine());
+        app.poll_chat_events();
+        assert_eq!(app.overlay, Overlay::None);
+
+        drop(event_tx);
+        app.poll_chat_events();
+        assert!(matches!(app.overlay, Overlay::Confirm(ConfirmKind::Handoff(None))));
+        assert!(app.handoff_offered);
+        assert!(!app.handoff_due);
+    }
+}
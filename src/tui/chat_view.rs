@@ -2,19 +2,22 @@ use ratatui::prelude::*;
 use ratatui::text::{Line as TuiLine, Span};
 use ratatui::widgets::Paragraph;
 
-use crate::config::themes::Theme;
+use crate::config::themes::{get_theme, Theme};
 use crate::tui::app::{App, DisplayMessage, MessageRole};
+use crate::tui::hyperlink::{self, Hyperlink};
 use crate::tui::markdown;
 use crate::tui::tool_view;
+use crate::tui::transcript_search;
 
-/// Render the chat message area with virtual scrolling.
-pub fn render(frame: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+/// Render the chat message area with virtual scrolling. Returns the links
+/// on screen, to be printed as terminal hyperlinks after the draw.
+pub fn render(frame: &mut Frame, area: Rect, app: &App, theme: &Theme) -> Vec<Hyperlink> {
     let bg = Color::Rgb(theme.bg.r, theme.bg.g, theme.bg.b);
     let inner_width = area.width.saturating_sub(2);
     let visible_height = area.height as usize;
 
     // Pre-render all messages into flat lines
-    let all_lines = render_all_messages(&app.messages, theme, inner_width, app.is_streaming, app.tick);
+    let all_lines = render_all_messages(app, theme, inner_width);
 
     let total = all_lines.len();
     let max_scroll = total.saturating_sub(visible_height);
@@ -24,28 +27,90 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App, theme: &Theme) {
     let end = total.saturating_sub(clamped_offset);
     let start = end.saturating_sub(visible_height);
 
-    let visible: Vec<TuiLine> = all_lines[start..end].to_vec();
+    // Matches of a transcript search are highlighted
+    let query = app.transcript_search.as_ref().map(|s| s.query.as_str()).filter(|q| !q.is_empty());
+    let warning = Color::Rgb(theme.warning.r, theme.warning.g, theme.warning.b);
+    let match_style = Style::default().fg(bg).bg(warning);
+
+    let mut visible: Vec<TuiLine> = Vec::with_capacity(end - start);
+    let mut link_cells = Vec::new();
+    for (row, line) in all_lines[start..end].iter().enumerate() {
+        let (mut line, links) = hyperlink::extract(line.clone());
+        link_cells.extend(links.into_iter().map(|(col, width, url)| (row as u16, col, width, url)));
+        if let Some(query) = query {
+            line = transcript_search::highlight(line, query, match_style);
+        }
+        visible.push(line);
+    }
 
     let paragraph = Paragraph::new(visible)
         .style(Style::default().bg(bg));
 
     frame.render_widget(paragraph, area);
+
+    let buffer = frame.buffer_mut();
+    link_cells
+        .into_iter()
+        .filter(|(row, col, width, _)| row < &area.height && col + width <= area.width)
+        .map(|(row, col, width, url)| Hyperlink::at(buffer, area.x + col, area.y + row, width, url))
+        .collect()
+}
+
+/// Scroll offset (lines from the bottom) that puts message `index` at the top
+/// of `area`, clamped to the scrollable range.
+pub fn scroll_offset_for_message(app: &App, area: Rect, index: usize) -> u16 {
+    let theme = get_theme(app.theme_name());
+    let inner_width = area.width.saturating_sub(2);
+    let total = render_all_messages(app, theme, inner_width).len();
+    let start: usize = (0..index.min(app.messages.len()))
+        // Code block numbers do not change the line count
+        .map(|i| render_message(app, i, theme, inner_width, false, 1).len() + 1)
+        .sum();
+    let offset = total.saturating_sub(start + area.height as usize);
+    offset.min(u16::MAX as usize) as u16
+}
+
+/// Message shown in the top line of `area` at the current scroll offset.
+pub fn message_at_top(app: &App, area: Rect) -> usize {
+    let theme = get_theme(app.theme_name());
+    let inner_width = area.width.saturating_sub(2);
+    let height = area.height as usize;
+    let total = render_all_messages(app, theme, inner_width).len();
+    let offset = (app.scroll_offset as usize).min(total.saturating_sub(height));
+    let first_line = total.saturating_sub(offset + height);
+    let mut end = 0;
+    for i in 0..app.messages.len() {
+        end += render_message(app, i, theme, inner_width, false, 1).len() + 1;
+        if end > first_line {
+            return i;
+        }
+    }
+    app.messages.len().saturating_sub(1)
+}
+
+/// Text of each line of the transcript as drawn in `area`, for searching it.
+pub fn transcript_lines(app: &App, area: Rect) -> Vec<String> {
+    let theme = get_theme(app.theme_name());
+    render_all_messages(app, theme, area.width.saturating_sub(2))
+        .into_iter()
+        .map(|line| hyperlink::extract(line).0.to_string())
+        .collect()
 }
 
 /// Render all messages into a flat list of styled Lines.
-fn render_all_messages<'a>(
-    messages: &[DisplayMessage],
-    theme: &Theme,
-    width: u16,
-    is_streaming: bool,
-    tick: u64,
-) -> Vec<TuiLine<'a>> {
+fn render_all_messages<'a>(app: &App, theme: &Theme, width: u16) -> Vec<TuiLine<'a>> {
+    let messages = &app.messages;
     let mut lines: Vec<TuiLine<'a>> = Vec::new();
+    // Code blocks are numbered through the conversation, for `/yank`
+    let mut code_block = 1;
 
-    for (i, msg) in messages.iter().enumerate() {
+    for i in 0..messages.len() {
         let is_last = i == messages.len() - 1;
-        let msg_lines = render_message(msg, theme, width, is_last && is_streaming, tick);
+        let msg_lines = render_message(app, i, theme, width, is_last && app.is_streaming, code_block);
         lines.extend(msg_lines);
+        if messages[i].role == MessageRole::Assistant {
+            code_block += markdown::code_blocks(&messages[i].content).len();
+        }
         // Blank line separator between messages
         lines.push(TuiLine::from(""));
     }
@@ -91,11 +156,23 @@ fn render_welcome<'a>(theme: &Theme) -> Vec<TuiLine<'a>> {
             Style::default().fg(dim),
         )),
         TuiLine::from(Span::styled(
-            "    ↑↓      — Scroll chat history",
+            "    ↑↓      — Earlier prompts (in the input)",
             Style::default().fg(dim),
         )),
         TuiLine::from(Span::styled(
-            "    Ctrl+U/D — Page up/down",
+            "    PgUp/PgDn, Ctrl+U/D — Scroll chat history",
+            Style::default().fg(dim),
+        )),
+        TuiLine::from(Span::styled(
+            "    Ctrl+B  — Branch from an earlier message",
+            Style::default().fg(dim),
+        )),
+        TuiLine::from(Span::styled(
+            "    Ctrl+T  — Expand tool output",
+            Style::default().fg(dim),
+        )),
+        TuiLine::from(Span::styled(
+            "    Ctrl+F  — Search the conversation",
             Style::default().fg(dim),
         )),
         TuiLine::from(Span::styled(
@@ -111,31 +188,54 @@ fn render_welcome<'a>(theme: &Theme) -> Vec<TuiLine<'a>> {
 }
 
 fn render_message<'a>(
-    msg: &DisplayMessage,
+    app: &App,
+    index: usize,
     theme: &Theme,
     width: u16,
     is_active_stream: bool,
-    tick: u64,
+    first_code_block: usize,
 ) -> Vec<TuiLine<'a>> {
+    let msg = &app.messages[index];
     match msg.role {
-        MessageRole::User => render_user_message(msg, theme, width),
-        MessageRole::Assistant => render_assistant_message(msg, theme, width, is_active_stream, tick),
-        MessageRole::Tool => tool_view::render_tool_result_lines(msg, theme, width),
+        MessageRole::User => render_user_message(msg, theme, width, app.branch_select == Some(index)),
+        MessageRole::Assistant => {
+            render_assistant_message(msg, theme, width, is_active_stream, app.tick, first_code_block)
+        }
+        MessageRole::Tool => tool_view::render_tool_result_lines(
+            msg,
+            tool_view::call_arguments(&app.messages, index),
+            theme,
+            width,
+            app.tool_detail(index),
+            app.tool_select == Some(index),
+        ),
         MessageRole::System => render_system_message(msg, theme, width),
     }
 }
 
-fn render_user_message<'a>(msg: &DisplayMessage, theme: &Theme, width: u16) -> Vec<TuiLine<'a>> {
+fn render_user_message<'a>(msg: &DisplayMessage, theme: &Theme, width: u16, is_selected: bool) -> Vec<TuiLine<'a>> {
     let accent = Color::Rgb(theme.accent.r, theme.accent.g, theme.accent.b);
     let text_color = Color::Rgb(theme.text.r, theme.text.g, theme.text.b);
 
     let mut lines = Vec::new();
 
-    // Header
-    lines.push(TuiLine::from(vec![Span::styled(
-        "  > You",
-        Style::default().fg(accent).bold(),
-    )]));
+    // Header — highlighted while picking a message to branch from
+    if is_selected {
+        let bg = Color::Rgb(theme.bg.r, theme.bg.g, theme.bg.b);
+        let dim = Color::Rgb(theme.dim_text.r, theme.dim_text.g, theme.dim_text.b);
+        lines.push(TuiLine::from(vec![
+            Span::styled("  ▸ You ", Style::default().fg(bg).bg(accent).bold()),
+            Span::styled(
+                "  ⎇ Enter: branch from here · ↑↓ choose · Esc cancel",
+                Style::default().fg(dim),
+            ),
+        ]));
+    } else {
+        lines.push(TuiLine::from(vec![Span::styled(
+            "  > You",
+            Style::default().fg(accent).bold(),
+        )]));
+    }
 
     // Content — word-wrapped with indent
     let content_width = width.saturating_sub(4) as usize;
@@ -158,6 +258,7 @@ fn render_assistant_message<'a>(
     width: u16,
     is_active_stream: bool,
     tick: u64,
+    first_code_block: usize,
 ) -> Vec<TuiLine<'a>> {
     let purple = Color::Rgb(theme.purple.r, theme.purple.g, theme.purple.b);
     let dim = Color::Rgb(theme.dim_text.r, theme.dim_text.g, theme.dim_text.b);
@@ -226,7 +327,7 @@ fn render_assistant_message<'a>(
 
     // Content (markdown formatted)
     if !msg.content.is_empty() {
-        let md_lines = markdown::markdown_to_lines(&msg.content, theme, width);
+        let md_lines = markdown::markdown_to_lines(&msg.content, theme, width, first_code_block);
         lines.extend(md_lines);
     }
 
@@ -0,0 +1,117 @@
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use crate::config::settings::ClipboardBackend;
+
+/// Largest text sent with OSC 52; many terminals drop longer sequences.
+const OSC52_MAX_BYTES: usize = 100_000;
+
+/// How text was copied, for the status message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Copied {
+    /// Taken by this clipboard tool.
+    Tool(&'static str),
+    /// Sent to the terminal with OSC 52, which it may ignore.
+    Terminal,
+}
+
+/// Copy `text` with `backend`.
+pub fn copy(text: &str, backend: ClipboardBackend) -> Result<Copied> {
+    // Over SSH or without a clipboard tool, the terminal may still take it
+    if backend == ClipboardBackend::System {
+        if let Some(tool) = copy_with_tools(text, &system_tools()) {
+            return Ok(Copied::Tool(tool));
+        }
+    }
+    if text.len() > OSC52_MAX_BYTES {
+        return Err(anyhow!(
+            "{} KB is too large to copy through the terminal; set \"clipboard\": \"system\"",
+            text.len() / 1024
+        ));
+    }
+    let mut out = io::stdout();
+    out.write_all(osc52(text, std::env::var_os("TMUX").is_some()).as_bytes())?;
+    out.flush()?;
+    Ok(Copied::Terminal)
+}
+
+/// The OSC 52 sequence that sets the clipboard to `text`, wrapped for tmux
+/// to pass through when `tmux` is set.
+fn osc52(text: &str, tmux: bool) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let sequence = format!("\x1b]52;c;{}\x07", encoded);
+    if tmux {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+/// Clipboard tools tried in order, with their arguments.
+fn system_tools() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        vec![("pbcopy", &[])]
+    } else if cfg!(windows) {
+        vec![("clip", &[])]
+    } else {
+        let mut tools: Vec<(&'static str, &'static [&'static str])> = Vec::new();
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            tools.push(("wl-copy", &[]));
+        }
+        tools.push(("xclip", &["-selection", "clipboard"]));
+        tools.push(("xsel", &["--clipboard", "--input"]));
+        // WSL
+        tools.push(("clip.exe", &[]));
+        tools
+    }
+}
+
+/// Give `text` to the first of `tools` that takes it. A tool that cannot be
+/// started, stops reading or exits with an error is skipped.
+fn copy_with_tools(text: &str, tools: &[(&'static str, &[&str])]) -> Option<&'static str> {
+    for &(program, args) in tools {
+        let Ok(mut child) = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        // stdin is closed here, so the tool sees the end of the text
+        let written = child.stdin.take().is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+        if !written {
+            let _ = child.kill();
+        }
+        // Always reaped, even when the text did not get through
+        let exited_ok = child.wait().is_ok_and(|status| status.success());
+        if written && exited_ok {
+            return Some(program);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc52_sequences() {
+        assert_eq!(osc52("hi", false), "\x1b]52;c;aGk=\x07");
+        assert_eq!(osc52("hi", true), "\x1bPtmux;\x1b\x1b]52;c;aGk=\x07\x1b\\");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_tools_fall_through_to_the_next() {
+        // `true` exits without reading, so writing this much to it fails
+        let text = "x".repeat(1 << 20);
+        let tools: &[(&'static str, &[&str])] = &[("minmax-code-no-such-tool", &[]), ("true", &[]), ("cat", &[])];
+        assert_eq!(copy_with_tools(&text, tools), Some("cat"));
+        assert_eq!(copy_with_tools("hi", &[("false", &[])]), None);
+    }
+}
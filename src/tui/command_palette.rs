@@ -2,7 +2,7 @@ use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 
-use crate::config::settings::{AVAILABLE_MODELS, MODEL_IDS};
+use crate::config::settings::{builtin_models, ModelInfo};
 use crate::config::themes::{self, Theme};
 
 // ── State ──────────────────────────────────────────────────────────────
@@ -18,6 +18,9 @@ pub enum PaletteView {
 pub struct CommandPaletteState {
     pub view: PaletteView,
     pub selected: usize,
+    /// Models offered by /model; the built-in ones until the app sets the
+    /// configured registry.
+    pub models: Vec<ModelInfo>,
 }
 
 impl CommandPaletteState {
@@ -25,6 +28,7 @@ impl CommandPaletteState {
         Self {
             view: PaletteView::Main,
             selected: 0,
+            models: builtin_models(),
         }
     }
 }
@@ -55,6 +59,18 @@ fn commands() -> Vec<PaletteCommand> {
         PaletteCommand { cmd: "/model", desc: "Change model", has_submenu: true },
         PaletteCommand { cmd: "/theme", desc: "Change theme", has_submenu: true },
         PaletteCommand { cmd: "/config", desc: "Open configuration", has_submenu: false },
+        PaletteCommand { cmd: "/search", desc: "Search all sessions", has_submenu: false },
+        PaletteCommand { cmd: "/export", desc: "Export this session as Markdown", has_submenu: false },
+        PaletteCommand { cmd: "/import", desc: "Import a session from a JSON export", has_submenu: false },
+        PaletteCommand { cmd: "/fork", desc: "Fork this session (Ctrl+B branches from a message)", has_submenu: false },
+        PaletteCommand { cmd: "/usage", desc: "Token usage and cost", has_submenu: false },
+        PaletteCommand { cmd: "/context", desc: "Loaded instruction files", has_submenu: false },
+        PaletteCommand { cmd: "/trust", desc: "Allow this project's MCP servers", has_submenu: false },
+        PaletteCommand { cmd: "/compact", desc: "Summarize older messages now", has_submenu: false },
+        PaletteCommand { cmd: "/handoff", desc: "Continue in a new session from a summary", has_submenu: false },
+        PaletteCommand { cmd: "/open", desc: "Open the last edited file in $EDITOR", has_submenu: false },
+        PaletteCommand { cmd: "/copy", desc: "Copy the last reply to the clipboard", has_submenu: false },
+        PaletteCommand { cmd: "/yank", desc: "Copy the last code block to the clipboard", has_submenu: false },
         PaletteCommand { cmd: "/init", desc: "Create agent.md template", has_submenu: false },
         PaletteCommand { cmd: "/clear", desc: "Clear current chat", has_submenu: false },
         PaletteCommand { cmd: "/exit", desc: "Exit the terminal", has_submenu: false },
@@ -84,7 +100,7 @@ pub fn handle_key(state: &mut CommandPaletteState, key: KeyEvent) -> PaletteActi
             let max = match &state.view {
                 PaletteView::Main => commands().len(),
                 PaletteView::ThemeList => themes::theme_names().len(),
-                PaletteView::ModelList => MODEL_IDS.len(),
+                PaletteView::ModelList => state.models.len(),
             };
             if state.selected < max.saturating_sub(1) {
                 state.selected += 1;
@@ -126,8 +142,8 @@ pub fn handle_key(state: &mut CommandPaletteState, key: KeyEvent) -> PaletteActi
                     }
                 }
                 PaletteView::ModelList => {
-                    if let Some(id) = MODEL_IDS.get(state.selected) {
-                        PaletteAction::SetModel(id.to_string())
+                    if let Some(model) = state.models.get(state.selected) {
+                        PaletteAction::SetModel(model.id.clone())
                     } else {
                         PaletteAction::None
                     }
@@ -144,7 +160,7 @@ pub fn render(frame: &mut Frame, area: Rect, state: &CommandPaletteState, theme:
     match &state.view {
         PaletteView::Main => render_main(frame, area, state.selected, theme),
         PaletteView::ThemeList => render_theme_list(frame, area, state.selected, theme, current_theme),
-        PaletteView::ModelList => render_model_list(frame, area, state.selected, theme, current_model),
+        PaletteView::ModelList => render_model_list(frame, area, state.selected, theme, current_model, &state.models),
     }
 }
 
@@ -251,9 +267,16 @@ fn render_theme_list(frame: &mut Frame, area: Rect, selected: usize, theme: &The
     frame.render_widget(list, palette_area);
 }
 
-fn render_model_list(frame: &mut Frame, area: Rect, selected: usize, theme: &Theme, current_model: &str) {
-    let palette_height = (AVAILABLE_MODELS.len() as u16 + 3).min(area.height.saturating_sub(4));
-    let palette_width = 55.min(area.width.saturating_sub(4));
+fn render_model_list(
+    frame: &mut Frame,
+    area: Rect,
+    selected: usize,
+    theme: &Theme,
+    current_model: &str,
+    models: &[ModelInfo],
+) {
+    let palette_height = (models.len() as u16 + 3).min(area.height.saturating_sub(4));
+    let palette_width = 80.min(area.width.saturating_sub(4));
     let x = (area.width.saturating_sub(palette_width)) / 2;
     let y = (area.height.saturating_sub(palette_height)) / 2;
     let palette_area = Rect::new(x, y, palette_width, palette_height);
@@ -267,12 +290,12 @@ fn render_model_list(frame: &mut Frame, area: Rect, selected: usize, theme: &The
     let dim = Color::Rgb(theme.dim_text.r, theme.dim_text.g, theme.dim_text.b);
     let success = Color::Rgb(theme.success.r, theme.success.g, theme.success.b);
 
-    let list_items: Vec<ListItem> = AVAILABLE_MODELS
+    let list_items: Vec<ListItem> = models
         .iter()
         .enumerate()
-        .map(|(i, (id, desc))| {
+        .map(|(i, model)| {
             let indicator = if i == selected { "▸ " } else { "  " };
-            let is_current = *id == current_model;
+            let is_current = model.id == current_model;
             let style = if i == selected {
                 Style::default().fg(bg).bg(accent).bold()
             } else {
@@ -291,8 +314,8 @@ fn render_model_list(frame: &mut Frame, area: Rect, selected: usize, theme: &The
             };
             ListItem::new(Line::from(vec![
                 Span::styled(indicator, style),
-                Span::styled(format!("{:<28}", id), style),
-                Span::styled(format!(" {}", desc), desc_style),
+                Span::styled(format!("{:<28}", model.id), style),
+                Span::styled(format!(" {}", model.description()), desc_style),
                 Span::styled(current_marker, current_style),
             ]))
         })
@@ -403,6 +426,6 @@ mod tests {
         state.view = PaletteView::ModelList;
         state.selected = 0;
         let action = handle_key(&mut state, KeyEvent::from(KeyCode::Enter));
-        assert_eq!(action, PaletteAction::SetModel(MODEL_IDS[0].to_string()));
+        assert_eq!(action, PaletteAction::SetModel(builtin_models()[0].id.clone()));
     }
 }
@@ -2,7 +2,7 @@ use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 
-use crate::config::settings::AVAILABLE_MODELS;
+use crate::config::settings::{builtin_models, ModelInfo};
 use crate::config::themes::{self, Theme};
 
 // ── State ──────────────────────────────────────────────────────────────
@@ -22,6 +22,9 @@ pub struct ConfigMenuState {
     pub api_key_input: String,
     pub api_key_cursor: usize,
     pub error: Option<String>,
+    /// Models offered under "Model"; the built-in ones until the app sets
+    /// the configured registry.
+    pub models: Vec<ModelInfo>,
 }
 
 impl ConfigMenuState {
@@ -32,6 +35,7 @@ impl ConfigMenuState {
             api_key_input: String::new(),
             api_key_cursor: 0,
             error: None,
+            models: builtin_models(),
         }
     }
 }
@@ -213,14 +217,14 @@ fn handle_model_key(state: &mut ConfigMenuState, key: KeyEvent, _current: &str)
             ConfigAction::None
         }
         KeyCode::Down => {
-            if state.selected < AVAILABLE_MODELS.len().saturating_sub(1) {
+            if state.selected < state.models.len().saturating_sub(1) {
                 state.selected += 1;
             }
             ConfigAction::None
         }
         KeyCode::Enter => {
-            if let Some((id, _)) = AVAILABLE_MODELS.get(state.selected) {
-                ConfigAction::SetModel(id.to_string())
+            if let Some(model) = state.models.get(state.selected) {
+                ConfigAction::SetModel(model.id.clone())
             } else {
                 ConfigAction::None
             }
@@ -251,7 +255,7 @@ pub fn render(
         ConfigView::Main => render_main(frame, area, state.selected, theme, current_api_key, current_theme, current_model),
         ConfigView::ApiKey => render_api_key(frame, area, state, theme, current_api_key),
         ConfigView::ThemeSelect => render_theme_select(frame, area, state.selected, theme, current_theme),
-        ConfigView::ModelSelect => render_model_select(frame, area, state.selected, theme, current_model),
+        ConfigView::ModelSelect => render_model_select(frame, area, state.selected, theme, current_model, &state.models),
     }
 }
 
@@ -458,6 +462,7 @@ fn render_model_select(
     selected: usize,
     theme: &Theme,
     current_model: &str,
+    models: &[ModelInfo],
 ) {
     let accent = Color::Rgb(theme.accent.r, theme.accent.g, theme.accent.b);
     let bg = Color::Rgb(theme.bg.r, theme.bg.g, theme.bg.b);
@@ -466,20 +471,20 @@ fn render_model_select(
     let dim = Color::Rgb(theme.dim_text.r, theme.dim_text.g, theme.dim_text.b);
     let success = Color::Rgb(theme.success.r, theme.success.g, theme.success.b);
 
-    let box_height = (AVAILABLE_MODELS.len() as u16 + 3).min(area.height.saturating_sub(4));
-    let box_width = 55u16.min(area.width.saturating_sub(4));
+    let box_height = (models.len() as u16 + 3).min(area.height.saturating_sub(4));
+    let box_width = 80u16.min(area.width.saturating_sub(4));
     let x = (area.width.saturating_sub(box_width)) / 2;
     let y = (area.height.saturating_sub(box_height)) / 2;
     let box_area = Rect::new(x, y, box_width, box_height);
 
     frame.render_widget(Clear, box_area);
 
-    let list_items: Vec<ListItem> = AVAILABLE_MODELS
+    let list_items: Vec<ListItem> = models
         .iter()
         .enumerate()
-        .map(|(i, (id, desc))| {
+        .map(|(i, model)| {
             let indicator = if i == selected { "▸ " } else { "  " };
-            let is_current = *id == current_model;
+            let is_current = model.id == current_model;
             let style = if i == selected {
                 Style::default().fg(bg).bg(accent).bold()
             } else {
@@ -490,8 +495,8 @@ fn render_model_select(
             let current_style = if i == selected { style } else { Style::default().fg(success) };
             ListItem::new(Line::from(vec![
                 Span::styled(indicator, style),
-                Span::styled(format!("{:<28}", id), style),
-                Span::styled(format!(" {}", desc), desc_style),
+                Span::styled(format!("{:<28}", model.id), style),
+                Span::styled(format!(" {}", model.description()), desc_style),
                 Span::styled(current_marker, current_style),
             ]))
         })
@@ -0,0 +1,89 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+use crate::config::themes::Theme;
+
+// ── State ──────────────────────────────────────────────────────────────
+
+/// A yes/no question shown over the chat.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfirmState {
+    pub title: String,
+    pub message: String,
+}
+
+impl ConfirmState {
+    pub fn new(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+        }
+    }
+}
+
+// ── Action result ──────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfirmAction {
+    None,
+    Yes,
+    No,
+}
+
+// ── Key handling ───────────────────────────────────────────────────────
+
+pub fn handle_key(key: KeyEvent) -> ConfirmAction {
+    match key.code {
+        KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('Y') => ConfirmAction::Yes,
+        KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') => ConfirmAction::No,
+        _ => ConfirmAction::None,
+    }
+}
+
+// ── Rendering ──────────────────────────────────────────────────────────
+
+pub fn render(frame: &mut Frame, area: Rect, state: &ConfirmState, theme: &Theme) {
+    let accent = Color::Rgb(theme.accent.r, theme.accent.g, theme.accent.b);
+    let surface = Color::Rgb(theme.surface.r, theme.surface.g, theme.surface.b);
+    let text_color = Color::Rgb(theme.text.r, theme.text.g, theme.text.b);
+    let dim = Color::Rgb(theme.dim_text.r, theme.dim_text.g, theme.dim_text.b);
+
+    let width = 64u16.min(area.width.saturating_sub(4));
+    let inner_width = width.saturating_sub(4).max(1) as usize;
+    let message_lines: u16 = state
+        .message
+        .lines()
+        .map(|l| (l.chars().count() / inner_width + 1) as u16)
+        .sum();
+    let height = (message_lines + 5).min(area.height.saturating_sub(2));
+    let x = (area.width.saturating_sub(width)) / 2;
+    let y = (area.height.saturating_sub(height)) / 2;
+    let box_area = Rect::new(x, y, width, height);
+
+    frame.render_widget(Clear, box_area);
+
+    let mut lines: Vec<Line> = state
+        .message
+        .lines()
+        .map(|l| Line::from(Span::styled(l.to_string(), Style::default().fg(text_color))))
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("Enter/y", Style::default().fg(accent).bold()),
+        Span::styled(" yes   ", Style::default().fg(dim)),
+        Span::styled("Esc/n", Style::default().fg(accent).bold()),
+        Span::styled(" no", Style::default().fg(dim)),
+    ]));
+
+    let block = Block::default()
+        .title(format!(" {} ", state.title))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(accent))
+        .padding(Padding::horizontal(1))
+        .style(Style::default().bg(surface));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, box_area);
+}
@@ -0,0 +1,563 @@
+use std::ops::Range;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// Pastes with more lines than this are shown as a single placeholder.
+const PASTE_COLLAPSE_LINES: usize = 10;
+/// Pastes longer than this (in characters) are collapsed as well.
+const PASTE_COLLAPSE_CHARS: usize = 2000;
+
+static PASTE_PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[Pasted text #(\d+) \+\d+ lines\]").unwrap());
+
+// ── History search ─────────────────────────────────────────────────────
+
+/// Reverse search through prompt history (Ctrl+R).
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistorySearch {
+    pub query: String,
+    /// Index into the history of the current match.
+    pub matched: Option<usize>,
+    /// Text and cursor to restore when the search is cancelled.
+    saved: (String, usize),
+}
+
+// ── Editor state ───────────────────────────────────────────────────────
+
+/// The chat input: multiline text with a byte-offset cursor, collapsed
+/// pastes, and recall of earlier prompts.
+#[derive(Debug, Clone, Default)]
+pub struct InputEditor {
+    pub text: String,
+    pub cursor: usize,
+    /// Contents of collapsed pastes; placeholder `#n` refers to `pastes[n - 1]`.
+    pastes: Vec<String>,
+    /// Earlier prompts, newest first.
+    history: Vec<String>,
+    /// Position in `history` while recalling with Up/Down.
+    history_index: Option<usize>,
+    /// What was being typed before history recall started.
+    draft: String,
+    search: Option<HistorySearch>,
+}
+
+impl InputEditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+        self.pastes.clear();
+        self.history_index = None;
+    }
+
+    /// Replace the text and put the cursor at its end.
+    pub fn set_text(&mut self, text: String) {
+        self.clear();
+        self.cursor = text.len();
+        self.text = text;
+    }
+
+    pub fn set_history(&mut self, history: Vec<String>) {
+        self.history = history;
+    }
+
+    /// The text with collapsed pastes expanded.
+    pub fn expanded_text(&self) -> String {
+        PASTE_PLACEHOLDER
+            .replace_all(&self.text, |caps: &regex::Captures| {
+                self.paste_content(caps).unwrap_or(&caps[0]).to_string()
+            })
+            .into_owned()
+    }
+
+    /// Take the expanded text for sending, remember it in the history and
+    /// clear the editor.
+    pub fn take(&mut self) -> String {
+        let text = self.expanded_text();
+        self.history.retain(|h| *h != text);
+        self.history.insert(0, text.clone());
+        self.clear();
+        text
+    }
+
+    // ── Editing ─────────────────────────────────────────────────────────
+
+    pub fn insert_char(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        self.history_index = None;
+    }
+
+    pub fn insert_str(&mut self, s: &str) {
+        self.text.insert_str(self.cursor, s);
+        self.cursor += s.len();
+        self.history_index = None;
+    }
+
+    /// Insert pasted text. Large pastes become a placeholder that is
+    /// expanded when the prompt is sent.
+    pub fn paste(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let lines = text.lines().count();
+        if lines > PASTE_COLLAPSE_LINES || text.chars().count() > PASTE_COLLAPSE_CHARS {
+            self.pastes.push(text);
+            let placeholder = format!("[Pasted text #{} +{} lines]", self.pastes.len(), lines);
+            self.insert_str(&placeholder);
+        } else {
+            self.insert_str(&text);
+        }
+    }
+
+    /// Expand the collapsed paste at or just before the cursor in place.
+    /// Returns false when there is none.
+    pub fn expand_paste_at_cursor(&mut self) -> bool {
+        let Some(range) = self
+            .paste_ranges()
+            .into_iter()
+            .find(|r| r.start <= self.cursor && self.cursor <= r.end)
+        else {
+            return false;
+        };
+        let expanded = PASTE_PLACEHOLDER
+            .captures(&self.text[range.clone()])
+            .and_then(|caps| self.paste_content(&caps).map(str::to_string))
+            .unwrap_or_default();
+        self.text.replace_range(range.clone(), &expanded);
+        self.cursor = range.start + expanded.len();
+        true
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        let start = match self.paste_ranges().into_iter().find(|r| r.end == self.cursor) {
+            Some(r) => r.start,
+            None => self.prev_boundary(),
+        };
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+        self.history_index = None;
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor >= self.text.len() {
+            return;
+        }
+        let end = match self.paste_ranges().into_iter().find(|r| r.start == self.cursor) {
+            Some(r) => r.end,
+            None => self.next_boundary(),
+        };
+        self.text.replace_range(self.cursor..end, "");
+        self.history_index = None;
+    }
+
+    /// Delete back to the start of the previous word (Ctrl+W).
+    pub fn delete_word_back(&mut self) {
+        let start = self.word_start_before(self.cursor);
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+        self.history_index = None;
+    }
+
+    // ── Movement ────────────────────────────────────────────────────────
+
+    pub fn left(&mut self) {
+        self.cursor = match self.paste_ranges().into_iter().find(|r| r.end == self.cursor) {
+            Some(r) => r.start,
+            None => self.prev_boundary(),
+        };
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = match self.paste_ranges().into_iter().find(|r| r.start == self.cursor) {
+            Some(r) => r.end,
+            None => self.next_boundary(),
+        };
+    }
+
+    pub fn word_left(&mut self) {
+        self.cursor = self.word_start_before(self.cursor);
+    }
+
+    pub fn word_right(&mut self) {
+        let rest = &self.text[self.cursor..];
+        let word_start = rest.find(is_word_char).unwrap_or(rest.len());
+        let word_len = rest[word_start..]
+            .find(|c: char| !is_word_char(c))
+            .unwrap_or(rest.len() - word_start);
+        self.cursor += word_start + word_len;
+    }
+
+    pub fn line_start(&mut self) {
+        self.cursor = self.current_line_start();
+    }
+
+    pub fn line_end(&mut self) {
+        self.cursor = self.text[self.cursor..]
+            .find('\n')
+            .map(|i| self.cursor + i)
+            .unwrap_or(self.text.len());
+    }
+
+    /// Move to the same column on the previous line. Returns false on the
+    /// first line.
+    pub fn up(&mut self) -> bool {
+        let start = self.current_line_start();
+        if start == 0 {
+            return false;
+        }
+        let column = self.text[start..self.cursor].chars().count();
+        let prev_start = self.text[..start - 1].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        self.cursor = column_offset(&self.text, prev_start, start - 1, column);
+        true
+    }
+
+    /// Move to the same column on the next line. Returns false on the last
+    /// line.
+    pub fn down(&mut self) -> bool {
+        let Some(newline) = self.text[self.cursor..].find('\n').map(|i| self.cursor + i) else {
+            return false;
+        };
+        let column = self.text[self.current_line_start()..self.cursor].chars().count();
+        let next_start = newline + 1;
+        let next_end = self.text[next_start..]
+            .find('\n')
+            .map(|i| next_start + i)
+            .unwrap_or(self.text.len());
+        self.cursor = column_offset(&self.text, next_start, next_end, column);
+        true
+    }
+
+    /// Up: the previous line, or past the first line the previous prompt.
+    pub fn up_or_history(&mut self) -> bool {
+        self.up() || self.history_prev()
+    }
+
+    /// Down: the next line, or past the last line the next prompt.
+    pub fn down_or_history(&mut self) -> bool {
+        self.down() || self.history_next()
+    }
+
+    // ── History ─────────────────────────────────────────────────────────
+
+    /// Replace the text with the previous (older) prompt. Returns false when
+    /// there is none.
+    pub fn history_prev(&mut self) -> bool {
+        let next = self.history_index.map(|i| i + 1).unwrap_or(0);
+        let Some(entry) = self.history.get(next).cloned() else {
+            return false;
+        };
+        if self.history_index.is_none() {
+            self.draft = std::mem::take(&mut self.text);
+        }
+        self.recall(entry, Some(next));
+        true
+    }
+
+    /// Replace the text with the next (newer) prompt, or the draft after the
+    /// newest. Returns false when not recalling history.
+    pub fn history_next(&mut self) -> bool {
+        match self.history_index {
+            None => false,
+            Some(0) => {
+                let draft = std::mem::take(&mut self.draft);
+                self.recall(draft, None);
+                true
+            }
+            Some(i) => {
+                let entry = self.history[i - 1].clone();
+                self.recall(entry, Some(i - 1));
+                true
+            }
+        }
+    }
+
+    fn recall(&mut self, text: String, index: Option<usize>) {
+        self.cursor = text.len();
+        self.text = text;
+        self.history_index = index;
+    }
+
+    // ── Reverse search ──────────────────────────────────────────────────
+
+    pub fn search(&self) -> Option<&HistorySearch> {
+        self.search.as_ref()
+    }
+
+    /// The history entry the search currently points at.
+    pub fn search_match(&self) -> Option<&str> {
+        self.search
+            .as_ref()
+            .and_then(|s| s.matched)
+            .and_then(|i| self.history.get(i))
+            .map(String::as_str)
+    }
+
+    pub fn start_search(&mut self) {
+        if self.search.is_none() {
+            self.search = Some(HistorySearch {
+                query: String::new(),
+                matched: None,
+                saved: (self.text.clone(), self.cursor),
+            });
+        }
+    }
+
+    pub fn search_push(&mut self, c: char) {
+        if let Some(search) = &mut self.search {
+            search.query.push(c);
+        }
+        self.find_match(0);
+    }
+
+    pub fn search_pop(&mut self) {
+        if let Some(search) = &mut self.search {
+            search.query.pop();
+        }
+        self.find_match(0);
+    }
+
+    /// Move to the next older match (Ctrl+R again).
+    pub fn search_older(&mut self) {
+        let from = self.search.as_ref().and_then(|s| s.matched).map(|i| i + 1).unwrap_or(0);
+        self.find_match(from);
+    }
+
+    /// Put the current match in the editor and end the search.
+    pub fn accept_search(&mut self) {
+        let matched = self.search_match().map(str::to_string);
+        if let Some(search) = self.search.take() {
+            match matched {
+                Some(text) => self.set_text(text),
+                None => (self.text, self.cursor) = search.saved,
+            }
+        }
+    }
+
+    /// End the search and restore what was typed before it.
+    pub fn cancel_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            (self.text, self.cursor) = search.saved;
+        }
+    }
+
+    fn find_match(&mut self, from: usize) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+        if search.query.is_empty() {
+            search.matched = None;
+            return;
+        }
+        let query = search.query.to_lowercase();
+        let found = self
+            .history
+            .iter()
+            .enumerate()
+            .skip(from)
+            .find(|(_, h)| h.to_lowercase().contains(&query))
+            .map(|(i, _)| i);
+        // Keep the current match when there is no older one
+        if found.is_some() || from == 0 {
+            search.matched = found;
+        }
+    }
+
+    // ── Helpers ─────────────────────────────────────────────────────────
+
+    /// Byte ranges of paste placeholders in the text.
+    pub fn paste_ranges(&self) -> Vec<Range<usize>> {
+        PASTE_PLACEHOLDER
+            .captures_iter(&self.text)
+            .filter(|caps| self.paste_content(caps).is_some())
+            .map(|caps| caps.get(0).unwrap().range())
+            .collect()
+    }
+
+    fn paste_content(&self, caps: &regex::Captures) -> Option<&str> {
+        let n: usize = caps[1].parse().ok()?;
+        self.pastes.get(n.checked_sub(1)?).map(String::as_str)
+    }
+
+    fn current_line_start(&self) -> usize {
+        self.text[..self.cursor].rfind('\n').map(|i| i + 1).unwrap_or(0)
+    }
+
+    fn prev_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .char_indices()
+            .next_back()
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map(|c| self.cursor + c.len_utf8())
+            .unwrap_or(self.text.len())
+    }
+
+    fn word_start_before(&self, pos: usize) -> usize {
+        let before = &self.text[..pos];
+        let word_end = before.rfind(is_word_char).map(|i| i + next_len(before, i)).unwrap_or(0);
+        before[..word_end]
+            .rfind(|c: char| !is_word_char(c))
+            .map(|i| i + next_len(before, i))
+            .unwrap_or(0)
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Length of the character starting at byte `i`.
+fn next_len(s: &str, i: usize) -> usize {
+    s[i..].chars().next().map(char::len_utf8).unwrap_or(0)
+}
+
+/// Byte offset of `column` characters into the line `start..end`, or its end.
+fn column_offset(text: &str, start: usize, end: usize, column: usize) -> usize {
+    text[start..end]
+        .char_indices()
+        .nth(column)
+        .map(|(i, _)| start + i)
+        .unwrap_or(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(text: &str, cursor: usize) -> InputEditor {
+        InputEditor { text: text.to_string(), cursor, ..InputEditor::default() }
+    }
+
+    #[test]
+    fn word_movement_and_deletion() {
+        let mut e = editor("cargo test --lib parser", 23);
+        e.word_left();
+        assert_eq!(e.cursor, 17);
+        e.word_left();
+        assert_eq!(e.cursor, 13);
+        e.word_right();
+        assert_eq!(e.cursor, 16);
+
+        let mut e = editor("cargo test --lib parser", 16);
+        e.delete_word_back();
+        assert_eq!(e.text, "cargo test -- parser");
+        e.delete_word_back();
+        assert_eq!(e.text, "cargo  parser");
+    }
+
+    #[test]
+    fn up_down_keep_the_column() {
+        let mut e = editor("first line\nab\nthird line", 0);
+        assert!(!e.up());
+        e.line_end();
+        assert!(e.down());
+        assert_eq!(e.cursor, 13); // end of "ab"
+        assert!(e.down());
+        assert_eq!(e.cursor, 16);
+        assert!(!e.down());
+        assert!(e.up());
+        assert_eq!(e.cursor, 13);
+        e.line_start();
+        assert_eq!(e.cursor, 11);
+    }
+
+    #[test]
+    fn history_recall_restores_draft() {
+        let mut e = InputEditor::new();
+        e.set_history(vec!["newest".to_string(), "older".to_string()]);
+        e.insert_str("draft");
+        assert!(e.history_prev());
+        assert_eq!(e.text, "newest");
+        assert!(e.history_prev());
+        assert_eq!(e.text, "older");
+        assert!(!e.history_prev());
+        assert!(e.history_next());
+        assert!(e.history_next());
+        assert_eq!(e.text, "draft");
+        assert!(!e.history_next());
+
+        e.set_text("newest".to_string());
+        assert_eq!(e.take(), "newest");
+        assert_eq!(e.history, ["newest", "older"]);
+    }
+
+    #[test]
+    fn up_leaves_the_first_line_for_history() {
+        let mut e = InputEditor::new();
+        e.set_history(vec!["earlier".to_string()]);
+        e.insert_str("one\ntwo");
+        assert!(e.up_or_history());
+        assert_eq!(e.text, "one\ntwo");
+        assert!(e.up_or_history());
+        assert_eq!(e.text, "earlier");
+        assert!(e.down_or_history());
+        assert_eq!(e.text, "one\ntwo");
+    }
+
+    #[test]
+    fn large_paste_collapses_and_expands() {
+        let pasted: String = (1..=20).map(|i| format!("at frame {}\r\n", i)).collect();
+        let mut e = InputEditor::new();
+        e.insert_str("why? ");
+        e.paste(&pasted);
+        assert_eq!(e.text, "why? [Pasted text #1 +20 lines]");
+        assert_eq!(e.paste_ranges(), vec![5..31]);
+        assert!(e.expanded_text().starts_with("why? at frame 1\nat frame 2\n"));
+
+        // The placeholder moves and deletes as one unit
+        e.left();
+        assert_eq!(e.cursor, 5);
+        e.right();
+        e.backspace();
+        assert_eq!(e.text, "why? ");
+
+        e.paste(&pasted);
+        assert!(e.expand_paste_at_cursor());
+        assert!(e.text.contains("at frame 20\n"));
+        assert!(e.paste_ranges().is_empty());
+
+        let mut small = InputEditor::new();
+        small.paste("one\r\ntwo");
+        assert_eq!(small.text, "one\ntwo");
+    }
+
+    #[test]
+    fn reverse_search() {
+        let mut e = InputEditor::new();
+        e.set_history(vec!["run tests".to_string(), "fix build".to_string(), "run lint".to_string()]);
+        e.insert_str("typing");
+        e.start_search();
+        e.search_push('r');
+        e.search_push('u');
+        assert_eq!(e.search_match(), Some("run tests"));
+        e.search_older();
+        assert_eq!(e.search_match(), Some("run lint"));
+        e.search_older();
+        assert_eq!(e.search_match(), Some("run lint"));
+        e.accept_search();
+        assert_eq!(e.text, "run lint");
+        assert!(e.search().is_none());
+
+        e.start_search();
+        e.search_push('z');
+        assert_eq!(e.search_match(), None);
+        e.cancel_search();
+        assert_eq!(e.text, "run lint");
+    }
+}
@@ -0,0 +1,158 @@
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::tools::ToolResultMeta;
+
+/// What to open once the TUI has been suspended.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditorRequest {
+    /// The input buffer, loaded back into the input when the editor exits.
+    Input,
+    /// A file, at a 1-based line.
+    File { path: PathBuf, line: usize },
+}
+
+/// The user's editor command: `$VISUAL`, then `$EDITOR`, then a platform
+/// default.
+pub fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|cmd| !cmd.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+}
+
+/// Program and arguments to open `path` with `editor`, at `line` when given.
+/// GUI editors are asked to wait, so the edit is finished when they exit.
+pub fn editor_args(editor: &str, path: &Path, line: Option<usize>) -> (String, Vec<String>) {
+    let mut words = editor.split_whitespace().map(str::to_string);
+    let program = words.next().unwrap_or_else(|| "vi".to_string());
+    let mut args: Vec<String> = words.collect();
+    let name = Path::new(&program)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let file = path.to_string_lossy().to_string();
+    let wait = |args: &mut Vec<String>, flag: &str| {
+        if !args.iter().any(|a| a == flag || a == "-w") {
+            args.push(flag.to_string());
+        }
+    };
+
+    match name.as_str() {
+        "code" | "code-insiders" | "codium" | "cursor" => {
+            wait(&mut args, "--wait");
+            match line {
+                Some(line) => args.extend(["--goto".to_string(), format!("{}:{}", file, line)]),
+                None => args.push(file),
+            }
+        }
+        "subl" | "zed" => {
+            wait(&mut args, "--wait");
+            args.push(line.map(|l| format!("{}:{}", file, l)).unwrap_or(file));
+        }
+        "notepad" => args.push(file),
+        // vi, vim, nvim, nano, emacs, micro, kak, hx and most others take `+N`
+        _ => {
+            if let Some(line) = line {
+                args.push(format!("+{}", line));
+            }
+            args.push(file);
+        }
+    }
+    (program, args)
+}
+
+/// Run the editor on `path` and wait for it. The terminal must already be
+/// handed over.
+pub fn open(path: &Path, line: Option<usize>) -> Result<()> {
+    let editor = editor_command();
+    let (program, args) = editor_args(&editor, path, line);
+    let status = Command::new(&program)
+        .args(&args)
+        .status()
+        .with_context(|| format!("could not start editor `{}`", program))?;
+    if !status.success() {
+        return Err(anyhow!("editor `{}` exited with {}", program, status));
+    }
+    Ok(())
+}
+
+/// Edit `text` in a temporary file and return the result.
+pub fn edit_text(text: &str) -> Result<String> {
+    let path = std::env::temp_dir().join(format!("minmax-prompt-{}.md", uuid::Uuid::new_v4()));
+    fs::write(&path, text)?;
+    let result = open(&path, None).and_then(|_| Ok(fs::read_to_string(&path)?));
+    let _ = fs::remove_file(&path);
+    // Editors add a final newline on save
+    Ok(result?.trim_end_matches(['\n', '\r']).to_string())
+}
+
+/// The file an edit touched and the line its new text starts on, or
+/// `None` for results that changed nothing.
+pub fn edit_location(meta: &ToolResultMeta) -> Option<(PathBuf, usize)> {
+    match meta {
+        ToolResultMeta::EditFile { path, new_str, .. } => {
+            let line = fs::read_to_string(path)
+                .ok()
+                .and_then(|content| line_of(&content, new_str))
+                .unwrap_or(1);
+            Some((PathBuf::from(path), line))
+        }
+        ToolResultMeta::WriteFile { path, .. } => Some((PathBuf::from(path), 1)),
+        ToolResultMeta::ReadFile { .. } => None,
+    }
+}
+
+fn line_of(content: &str, needle: &str) -> Option<usize> {
+    if needle.is_empty() {
+        return None;
+    }
+    let offset = content.find(needle)?;
+    Some(content[..offset].matches('\n').count() + 1)
+}
+
+/// Parse `path` or `path:line`.
+pub fn parse_location(arg: &str) -> (PathBuf, usize) {
+    match arg.rsplit_once(':') {
+        Some((path, line)) if !path.is_empty() => match line.parse::<usize>() {
+            Ok(line) => (PathBuf::from(path), line.max(1)),
+            Err(_) => (PathBuf::from(arg), 1),
+        },
+        _ => (PathBuf::from(arg), 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn args_per_editor() {
+        let path = Path::new("src/main.rs");
+        assert_eq!(
+            editor_args("nvim", path, Some(12)),
+            ("nvim".to_string(), vec!["+12".to_string(), "src/main.rs".to_string()])
+        );
+        assert_eq!(
+            editor_args("code", path, Some(3)),
+            ("code".to_string(), vec!["--wait".into(), "--goto".into(), "src/main.rs:3".into()])
+        );
+        assert_eq!(
+            editor_args("subl -w", path, None),
+            ("subl".to_string(), vec!["-w".to_string(), "src/main.rs".to_string()])
+        );
+        assert_eq!(editor_args("emacs -nw", path, None).1, ["-nw", "src/main.rs"]);
+    }
+
+    #[test]
+    fn locations() {
+        assert_eq!(parse_location("src/lib.rs:40"), (PathBuf::from("src/lib.rs"), 40));
+        assert_eq!(parse_location("src/lib.rs"), (PathBuf::from("src/lib.rs"), 1));
+        assert_eq!(parse_location("C:notes"), (PathBuf::from("C:notes"), 1));
+        assert_eq!(line_of("a\nb\nfn main() {}\n", "fn main"), Some(3));
+        assert_eq!(line_of("a\n", ""), None);
+    }
+}
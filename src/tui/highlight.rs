@@ -0,0 +1,742 @@
+use ratatui::prelude::*;
+use ratatui::text::Span;
+use regex::Regex;
+use std::sync::LazyLock;
+
+use crate::config::themes::Theme;
+
+/// `key:` at the start of a YAML line, optionally as a list item.
+static YAML_KEY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^(\s*(?:- )?)([^\s#'"{\[][^:#]*?|"[^"]*"|'[^']*')(:)(?:\s|$)"#).unwrap());
+
+// ── Languages ──────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    TypeScript,
+    Python,
+    Go,
+    Shell,
+    Json,
+    Yaml,
+    Sql,
+    Diff,
+    /// Unknown languages: strings, numbers and common comment forms only.
+    Plain,
+}
+
+impl Language {
+    /// Language of a code fence info string such as `rust`, `ts title=x` or
+    /// `rust,ignore`.
+    pub fn from_tag(tag: &str) -> Self {
+        let tag = tag
+            .split([',', ' ', '{'])
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_lowercase();
+        match tag.as_str() {
+            "rust" | "rs" => Language::Rust,
+            "typescript" | "ts" | "tsx" | "javascript" | "js" | "jsx" | "mjs" | "cjs" => {
+                Language::TypeScript
+            }
+            "python" | "py" | "python3" | "py3" => Language::Python,
+            "go" | "golang" => Language::Go,
+            "sh" | "bash" | "zsh" | "shell" | "console" | "shellscript" | "fish" => Language::Shell,
+            "json" | "jsonc" | "json5" => Language::Json,
+            "yaml" | "yml" => Language::Yaml,
+            "sql" | "sqlite" | "postgres" | "postgresql" | "psql" | "mysql" => Language::Sql,
+            "diff" | "patch" => Language::Diff,
+            _ => Language::Plain,
+        }
+    }
+
+    /// Language of a file, from its extension.
+    pub fn from_path(path: &str) -> Self {
+        match path.rsplit_once('.') {
+            Some((_, ext)) if !ext.contains('/') => match ext.to_lowercase().as_str() {
+                "pyi" => Language::Python,
+                "bash" | "zsh" => Language::Shell,
+                "mjs" | "cjs" => Language::TypeScript,
+                other => Language::from_tag(other),
+            },
+            _ => Language::Plain,
+        }
+    }
+
+    fn syntax(self) -> &'static Syntax {
+        match self {
+            Language::Rust => &RUST,
+            Language::TypeScript => &TYPESCRIPT,
+            Language::Python => &PYTHON,
+            Language::Go => &GO,
+            Language::Shell => &SHELL,
+            Language::Json => &JSON,
+            Language::Yaml => &YAML,
+            Language::Sql => &SQL,
+            Language::Diff | Language::Plain => &PLAIN,
+        }
+    }
+}
+
+/// Lexical rules of a language.
+struct Syntax {
+    keywords: &'static [&'static str],
+    /// Built-in types.
+    types: &'static [&'static str],
+    /// `true`, `null` and the like; colored as numbers.
+    literals: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    /// String delimiters, longest first.
+    quotes: &'static [&'static str],
+    /// Delimiters whose strings may continue on the next line.
+    multiline_quotes: &'static [&'static str],
+    /// `#` only starts a comment at the start of a line or after whitespace.
+    hash_needs_space: bool,
+    /// Capitalized identifiers are types, ALL_CAPS ones constants.
+    capitalized_types: bool,
+    case_insensitive: bool,
+}
+
+const RUST: Syntax = Syntax {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "type",
+        "unsafe", "use", "where", "while",
+    ],
+    types: &[
+        "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+        "f32", "f64", "bool", "char", "str",
+    ],
+    literals: &["true", "false"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &["\""],
+    multiline_quotes: &["\""],
+    hash_needs_space: false,
+    capitalized_types: true,
+    case_insensitive: false,
+};
+
+const TYPESCRIPT: Syntax = Syntax {
+    keywords: &[
+        "abstract", "as", "async", "await", "break", "case", "catch", "class", "const",
+        "continue", "debugger", "declare", "default", "delete", "do", "else", "enum", "export",
+        "extends", "finally", "for", "from", "function", "get", "if", "implements", "import",
+        "in", "instanceof", "interface", "keyof", "let", "namespace", "new", "of", "private",
+        "protected", "public", "readonly", "return", "set", "static", "super", "switch", "this",
+        "throw", "try", "type", "typeof", "var", "void", "while", "yield",
+    ],
+    types: &["string", "number", "boolean", "any", "unknown", "never", "object", "symbol", "bigint"],
+    literals: &["true", "false", "null", "undefined", "NaN", "Infinity"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &["\"", "'", "`"],
+    multiline_quotes: &["`"],
+    hash_needs_space: false,
+    capitalized_types: true,
+    case_insensitive: false,
+};
+
+const PYTHON: Syntax = Syntax {
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "case", "class", "continue", "def",
+        "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in",
+        "is", "lambda", "match", "nonlocal", "not", "or", "pass", "raise", "return", "self", "try",
+        "while", "with", "yield",
+    ],
+    types: &["int", "str", "float", "bool", "list", "dict", "set", "tuple", "bytes", "object"],
+    literals: &["True", "False", "None"],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &["\"\"\"", "'''", "\"", "'"],
+    multiline_quotes: &["\"\"\"", "'''"],
+    hash_needs_space: false,
+    capitalized_types: true,
+    case_insensitive: false,
+};
+
+const GO: Syntax = Syntax {
+    keywords: &[
+        "break", "case", "chan", "const", "continue", "default", "defer", "else", "fallthrough",
+        "for", "func", "go", "goto", "if", "import", "interface", "map", "package", "range",
+        "return", "select", "struct", "switch", "type", "var",
+    ],
+    types: &[
+        "any", "bool", "byte", "complex64", "complex128", "error", "float32", "float64", "int",
+        "int8", "int16", "int32", "int64", "rune", "string", "uint", "uint8", "uint16", "uint32",
+        "uint64", "uintptr",
+    ],
+    literals: &["true", "false", "nil", "iota"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &["\"", "`", "'"],
+    multiline_quotes: &["`"],
+    hash_needs_space: false,
+    capitalized_types: true,
+    case_insensitive: false,
+};
+
+const SHELL: Syntax = Syntax {
+    keywords: &[
+        "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac",
+        "in", "function", "select", "return", "local", "export", "readonly", "declare", "unset",
+        "source", "alias", "exit", "break", "continue", "shift", "trap", "eval", "exec",
+    ],
+    types: &[],
+    literals: &["true", "false"],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &["\"", "'"],
+    multiline_quotes: &["\"", "'"],
+    hash_needs_space: true,
+    capitalized_types: false,
+    case_insensitive: false,
+};
+
+const JSON: Syntax = Syntax {
+    keywords: &[],
+    types: &[],
+    literals: &["true", "false", "null"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &["\""],
+    multiline_quotes: &[],
+    hash_needs_space: false,
+    capitalized_types: false,
+    case_insensitive: false,
+};
+
+const YAML: Syntax = Syntax {
+    keywords: &[],
+    types: &[],
+    literals: &["true", "false", "null", "yes", "no", "on", "off", "~"],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &["\"", "'"],
+    multiline_quotes: &[],
+    hash_needs_space: true,
+    capitalized_types: false,
+    case_insensitive: true,
+};
+
+const SQL: Syntax = Syntax {
+    keywords: &[
+        "add", "all", "alter", "and", "as", "asc", "begin", "between", "by", "case", "check",
+        "commit", "constraint", "create", "cross", "default", "delete", "desc", "distinct", "drop",
+        "else", "end", "exists", "foreign", "from", "full", "group", "having", "if", "in", "index",
+        "inner", "insert", "into", "is", "join", "key", "left", "like", "limit", "not", "offset",
+        "on", "or", "order", "outer", "primary", "references", "returning", "right", "rollback",
+        "select", "set", "table", "then", "transaction", "trigger", "union", "unique", "update",
+        "using", "values", "view", "when", "where", "with",
+    ],
+    types: &[
+        "bigint", "blob", "bool", "boolean", "char", "date", "decimal", "double", "float", "int",
+        "integer", "json", "jsonb", "numeric", "real", "serial", "smallint", "text", "time",
+        "timestamp", "uuid", "varchar",
+    ],
+    literals: &["null", "true", "false"],
+    line_comments: &["--"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &["'", "\""],
+    multiline_quotes: &[],
+    hash_needs_space: false,
+    capitalized_types: false,
+    case_insensitive: true,
+};
+
+const PLAIN: Syntax = Syntax {
+    keywords: &[],
+    types: &[],
+    literals: &[],
+    line_comments: &["//", "#"],
+    block_comment: None,
+    quotes: &["\"", "'", "`"],
+    multiline_quotes: &[],
+    hash_needs_space: true,
+    capitalized_types: false,
+    case_insensitive: false,
+};
+
+// ── Highlighting ───────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Plain,
+    Keyword,
+    Type,
+    Function,
+    String,
+    Number,
+    Comment,
+    Variable,
+    Key,
+    Added,
+    Removed,
+    Hunk,
+    Heading,
+}
+
+impl Kind {
+    fn style(self, theme: &Theme) -> Style {
+        let rgb = |c: crate::config::themes::Color| Color::Rgb(c.r, c.g, c.b);
+        match self {
+            Kind::Plain => Style::default().fg(rgb(theme.text)),
+            Kind::Keyword => Style::default().fg(rgb(theme.purple)),
+            Kind::Type | Kind::Number => Style::default().fg(rgb(theme.warning)),
+            Kind::Function | Kind::Key | Kind::Variable => Style::default().fg(rgb(theme.accent)),
+            Kind::String | Kind::Added => Style::default().fg(rgb(theme.success)),
+            Kind::Removed => Style::default().fg(rgb(theme.error)),
+            Kind::Comment => Style::default().fg(rgb(theme.dim_text)).italic(),
+            Kind::Hunk => Style::default().fg(rgb(theme.purple)),
+            Kind::Heading => Style::default().fg(rgb(theme.text)).bold(),
+        }
+    }
+}
+
+/// A comment or string left open at the end of a line.
+#[derive(Debug, Clone)]
+struct Open {
+    kind: Kind,
+    end: String,
+    escapes: bool,
+}
+
+/// Highlights code one line at a time, carrying block comments and
+/// multi-line strings over to the next line.
+#[derive(Debug, Clone)]
+pub struct Highlighter {
+    language: Language,
+    open: Option<Open>,
+}
+
+impl Highlighter {
+    pub fn new(language: Language) -> Self {
+        Self { language, open: None }
+    }
+
+    /// Styled spans for the next line of code.
+    pub fn line(&mut self, line: &str, theme: &Theme) -> Vec<Span<'static>> {
+        self.tokens(line)
+            .into_iter()
+            .map(|(kind, text)| Span::styled(text, kind.style(theme)))
+            .collect()
+    }
+
+    fn tokens(&mut self, line: &str) -> Vec<(Kind, String)> {
+        let mut out = Vec::new();
+        if self.language == Language::Diff {
+            push(&mut out, diff_kind(line), line);
+            return out;
+        }
+        let syntax = self.language.syntax();
+        let mut i = 0;
+
+        if let Some(open) = self.open.take() {
+            match find_end(line, &open.end, open.escapes) {
+                Some(end) => {
+                    push(&mut out, open.kind, &line[..end]);
+                    i = end;
+                }
+                None => {
+                    push(&mut out, open.kind, line);
+                    self.open = Some(open);
+                    return out;
+                }
+            }
+        }
+
+        if self.language == Language::Yaml && i == 0 {
+            if let Some(caps) = YAML_KEY.captures(line) {
+                push(&mut out, Kind::Plain, &caps[1]);
+                push(&mut out, Kind::Key, &caps[2]);
+                push(&mut out, Kind::Plain, &caps[3]);
+                i = caps.get(3).map_or(0, |m| m.end());
+            }
+        }
+
+        // Shell: the first word of each command is the program
+        let mut command_position = true;
+        while i < line.len() {
+            let rest = &line[i..];
+            let c = rest.chars().next().unwrap_or(' ');
+            let prev = line[..i].chars().next_back();
+
+            // Comments
+            let line_comment = syntax.line_comments.iter().any(|m| {
+                rest.starts_with(m)
+                    && (*m != "#" || !syntax.hash_needs_space || prev.is_none_or(char::is_whitespace))
+            });
+            if line_comment {
+                push(&mut out, Kind::Comment, rest);
+                break;
+            }
+            if let Some((start, end)) = syntax.block_comment.filter(|(s, _)| rest.starts_with(s)) {
+                match rest[start.len()..].find(end) {
+                    Some(p) => {
+                        let stop = i + start.len() + p + end.len();
+                        push(&mut out, Kind::Comment, &line[i..stop]);
+                        i = stop;
+                        continue;
+                    }
+                    None => {
+                        push(&mut out, Kind::Comment, rest);
+                        self.open = Some(Open { kind: Kind::Comment, end: end.to_string(), escapes: false });
+                        break;
+                    }
+                }
+            }
+
+            // Strings
+            if let Some(quote) = syntax.quotes.iter().find(|q| rest.starts_with(**q)) {
+                // An apostrophe inside a word is not a quote
+                if !(*quote == "'" && prev.is_some_and(char::is_alphanumeric)) {
+                    i = self.string(line, i, i, quote, true, &mut out);
+                    command_position = false;
+                    continue;
+                }
+            }
+
+            // Rust char literals and lifetimes
+            if self.language == Language::Rust && c == '\'' {
+                let len = char_literal_len(rest);
+                if len > 0 {
+                    push(&mut out, Kind::String, &rest[..len]);
+                    i += len;
+                } else {
+                    let len = 1 + ident_len(&rest[1..]);
+                    push(&mut out, Kind::Type, &rest[..len]);
+                    i += len;
+                }
+                continue;
+            }
+
+            // Shell variables
+            if self.language == Language::Shell && c == '$' {
+                let len = variable_len(rest);
+                if len > 0 {
+                    push(&mut out, Kind::Variable, &rest[..len]);
+                    i += len;
+                    command_position = false;
+                    continue;
+                }
+            }
+
+            // Numbers
+            let starts_number = c.is_ascii_digit()
+                || (c == '-' && self.language == Language::Json && rest[1..].starts_with(|d: char| d.is_ascii_digit()));
+            if starts_number && !prev.is_some_and(is_ident_char) {
+                let len = number_len(rest);
+                push(&mut out, Kind::Number, &rest[..len]);
+                i += len;
+                command_position = false;
+                continue;
+            }
+
+            // Identifiers
+            if c.is_alphabetic() || c == '_' {
+                let mut len = ident_len(rest);
+                if self.language == Language::Shell {
+                    len += rest[len..].find(|ch: char| !(is_ident_char(ch) || ch == '-' || ch == '.' || ch == '/')).unwrap_or(rest.len() - len);
+                }
+                let word = &rest[..len];
+                let after = &rest[len..];
+
+                // String prefixes: r"raw", b"bytes", f"python"
+                if let Some((quote, escapes)) = self.prefixed_string(word, after) {
+                    i = self.string(line, i, i + len, &quote, escapes, &mut out);
+                    continue;
+                }
+
+                let kind = self.classify(word, after, syntax, command_position);
+                if self.language == Language::Rust && kind == Kind::Function && after.starts_with('!') {
+                    len += 1;
+                }
+                push(&mut out, kind, &rest[..len]);
+                i += len;
+                command_position = kind == Kind::Keyword && self.language == Language::Shell;
+                continue;
+            }
+
+            push(&mut out, Kind::Plain, &rest[..c.len_utf8()]);
+            i += c.len_utf8();
+            if matches!(c, '|' | ';' | '&' | '(' | '`') {
+                command_position = true;
+            } else if !c.is_whitespace() {
+                command_position = false;
+            }
+        }
+        out
+    }
+
+    /// Push the string whose token starts at `start` and whose opening
+    /// `quote` is at `quote_at`, and return where lexing continues.
+    fn string(
+        &mut self,
+        line: &str,
+        start: usize,
+        quote_at: usize,
+        quote: &str,
+        escapes: bool,
+        out: &mut Vec<(Kind, String)>,
+    ) -> usize {
+        let body = quote_at + quote.len();
+        // Rust raw strings close with `"` plus the same number of `#`
+        let end_delim = quote.trim_start_matches('#').to_string() + &"#".repeat(quote.matches('#').count());
+        match find_end(&line[body..], &end_delim, escapes) {
+            Some(e) => {
+                let end = body + e;
+                let is_key = self.language == Language::Json && line[end..].trim_start().starts_with(':');
+                push(out, if is_key { Kind::Key } else { Kind::String }, &line[start..end]);
+                end
+            }
+            None => {
+                push(out, Kind::String, &line[start..]);
+                if self.language.syntax().multiline_quotes.contains(&quote.trim_start_matches('#')) {
+                    self.open = Some(Open { kind: Kind::String, end: end_delim, escapes });
+                }
+                line.len()
+            }
+        }
+    }
+
+    /// Opening delimiter of a string that `word` prefixes, as in `r#"..."#`,
+    /// `b"..."` or `f'...'`, and whether backslash escapes apply.
+    fn prefixed_string(&self, word: &str, after: &str) -> Option<(String, bool)> {
+        match self.language {
+            Language::Rust if matches!(word, "r" | "br" | "b" | "c" | "cr") => {
+                let raw = word.ends_with('r');
+                let hashes = after.len() - after.trim_start_matches('#').len();
+                (after[hashes..].starts_with('"') && (hashes == 0 || raw))
+                    .then(|| ("#".repeat(hashes) + "\"", !raw))
+            }
+            Language::Python
+                if word.len() <= 2 && word.chars().all(|c| "rRbBfFuU".contains(c)) =>
+            {
+                let raw = word.contains(['r', 'R']);
+                PYTHON.quotes.iter().find(|q| after.starts_with(**q)).map(|q| (q.to_string(), !raw))
+            }
+            _ => None,
+        }
+    }
+
+    fn classify(&self, word: &str, after: &str, syntax: &Syntax, command_position: bool) -> Kind {
+        let matches = |list: &[&str]| {
+            if syntax.case_insensitive {
+                list.iter().any(|k| k.eq_ignore_ascii_case(word))
+            } else {
+                list.contains(&word)
+            }
+        };
+        if matches(syntax.keywords) {
+            Kind::Keyword
+        } else if matches(syntax.literals) {
+            Kind::Number
+        } else if matches(syntax.types) {
+            Kind::Type
+        } else if after.starts_with('(')
+            || (self.language == Language::Rust && after.starts_with('!') && !after.starts_with("!="))
+            || (self.language == Language::Shell && command_position)
+        {
+            Kind::Function
+        } else if syntax.capitalized_types && word.starts_with(|c: char| c.is_uppercase()) {
+            if word.len() > 1 && !word.chars().any(|c| c.is_lowercase()) {
+                Kind::Number
+            } else {
+                Kind::Type
+            }
+        } else {
+            Kind::Plain
+        }
+    }
+}
+
+fn diff_kind(line: &str) -> Kind {
+    if line.starts_with("+++") || line.starts_with("---") || line.starts_with("diff ") || line.starts_with("index ") {
+        Kind::Heading
+    } else if line.starts_with('+') {
+        Kind::Added
+    } else if line.starts_with('-') {
+        Kind::Removed
+    } else if line.starts_with("@@") {
+        Kind::Hunk
+    } else {
+        Kind::Plain
+    }
+}
+
+/// Append `text`, merging it into the previous token of the same kind.
+fn push(out: &mut Vec<(Kind, String)>, kind: Kind, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match out.last_mut() {
+        Some((last, s)) if *last == kind => s.push_str(text),
+        _ => out.push((kind, text.to_string())),
+    }
+}
+
+/// Byte offset just past the first unescaped `delim` in `s`.
+fn find_end(s: &str, delim: &str, escapes: bool) -> Option<usize> {
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if escapes && c == '\\' {
+            chars.next();
+        } else if s[i..].starts_with(delim) {
+            return Some(i + delim.len());
+        }
+    }
+    None
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn ident_len(s: &str) -> usize {
+    s.find(|c: char| !is_ident_char(c)).unwrap_or(s.len())
+}
+
+fn number_len(s: &str) -> usize {
+    let mut len = s.chars().next().map_or(0, char::len_utf8);
+    let bytes = s.as_bytes();
+    while len < s.len() {
+        let b = bytes[len];
+        let fraction = b == b'.' && bytes.get(len + 1).is_some_and(u8::is_ascii_digit);
+        if b.is_ascii_alphanumeric() || b == b'_' || fraction {
+            len += 1;
+        } else {
+            break;
+        }
+    }
+    len
+}
+
+/// Length of a Rust char literal at the start of `s` (`'a'`, `'\n'`,
+/// `'\u{1F600}'`), or 0 for a lifetime.
+fn char_literal_len(s: &str) -> usize {
+    let mut chars = s.char_indices().skip(1);
+    match chars.next() {
+        Some((_, '\\')) => s[2..].find('\'').map_or(0, |p| p + 3),
+        Some((_, c)) if c != '\'' => match chars.next() {
+            Some((i, '\'')) => i + 1,
+            _ => 0,
+        },
+        _ => 0,
+    }
+}
+
+/// Length of a shell variable at the start of `s`: `$NAME`, `${...}`, `$1`, `$?`.
+fn variable_len(s: &str) -> usize {
+    let rest = &s[1..];
+    if rest.starts_with('{') {
+        return rest.find('}').map_or(0, |p| p + 2);
+    }
+    match rest.chars().next() {
+        Some(c) if c.is_alphabetic() || c == '_' => 1 + ident_len(rest),
+        Some(c) if c.is_ascii_digit() || "?#@*!$-".contains(c) => 2,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(language: Language, lines: &[&str]) -> Vec<Vec<(Kind, String)>> {
+        let mut h = Highlighter::new(language);
+        lines.iter().map(|l| h.tokens(l)).collect()
+    }
+
+    fn kind_of(tokens: &[(Kind, String)], text: &str) -> Option<Kind> {
+        tokens.iter().find(|(_, t)| t.trim() == text).map(|(k, _)| *k)
+    }
+
+    #[test]
+    fn rust_tokens() {
+        let out = kinds(Language::Rust, &[r#"pub fn main(s: &'a str) { let x: Vec<u8> = vec![1, 2]; println!("{}", 'c'); } // done"#]);
+        let t = &out[0];
+        assert_eq!(kind_of(t, "pub"), Some(Kind::Keyword));
+        assert_eq!(kind_of(t, "main"), Some(Kind::Function));
+        assert_eq!(kind_of(t, "'a"), Some(Kind::Type));
+        assert_eq!(kind_of(t, "Vec"), Some(Kind::Type));
+        assert_eq!(kind_of(t, "u8"), Some(Kind::Type));
+        assert_eq!(kind_of(t, "vec!"), Some(Kind::Function));
+        assert_eq!(kind_of(t, "1"), Some(Kind::Number));
+        assert_eq!(kind_of(t, "\"{}\""), Some(Kind::String));
+        assert_eq!(kind_of(t, "'c'"), Some(Kind::String));
+        assert_eq!(t.last().unwrap(), &(Kind::Comment, "// done".to_string()));
+        // Tokens cover the line exactly
+        let joined: String = t.iter().map(|(_, s)| s.as_str()).collect();
+        assert!(joined.starts_with("pub fn main"));
+    }
+
+    #[test]
+    fn block_comments_and_strings_span_lines() {
+        let out = kinds(Language::Rust, &["let a = 1; /* start", "still comment", "end */ let b = r#\"raw", "\"# ;"]);
+        assert_eq!(out[1], vec![(Kind::Comment, "still comment".to_string())]);
+        assert_eq!(out[2][0], (Kind::Comment, "end */".to_string()));
+        assert_eq!(out[2].last().unwrap(), &(Kind::String, "r#\"raw".to_string()));
+        assert_eq!(out[3][0], (Kind::String, "\"#".to_string()));
+
+        let out = kinds(Language::Python, &["x = f'{y}'  # note", "'''doc", "more'''"]);
+        assert_eq!(kind_of(&out[0], "f'{y}'"), Some(Kind::String));
+        assert_eq!(kind_of(&out[0], "# note"), Some(Kind::Comment));
+        assert_eq!(out[1], vec![(Kind::String, "'''doc".to_string())]);
+        assert_eq!(out[2], vec![(Kind::String, "more'''".to_string())]);
+    }
+
+    #[test]
+    fn data_formats() {
+        let out = kinds(Language::Json, &[r#"{"name": "x", "n": -1.5, "ok": true}"#]);
+        assert_eq!(kind_of(&out[0], "\"name\""), Some(Kind::Key));
+        assert_eq!(kind_of(&out[0], "\"x\""), Some(Kind::String));
+        assert_eq!(kind_of(&out[0], "-1.5"), Some(Kind::Number));
+        assert_eq!(kind_of(&out[0], "true"), Some(Kind::Number));
+
+        let out = kinds(Language::Yaml, &["  - name: it's fine # comment", "on: push"]);
+        assert_eq!(kind_of(&out[0], "name"), Some(Kind::Key));
+        assert_eq!(kind_of(&out[0], "# comment"), Some(Kind::Comment));
+        assert!(out[0].iter().all(|(k, _)| *k != Kind::String));
+        assert_eq!(kind_of(&out[1], "on"), Some(Kind::Key));
+
+        let out = kinds(Language::Sql, &["SELECT id FROM users WHERE name = 'a' -- c"]);
+        assert_eq!(kind_of(&out[0], "SELECT"), Some(Kind::Keyword));
+        assert_eq!(kind_of(&out[0], "'a'"), Some(Kind::String));
+        assert_eq!(kind_of(&out[0], "-- c"), Some(Kind::Comment));
+    }
+
+    #[test]
+    fn shell_and_diff() {
+        let out = kinds(Language::Shell, &["if [ -n \"$HOME\" ]; then cargo test $1 | grep ok; fi # x"]);
+        let t = &out[0];
+        assert_eq!(kind_of(t, "if"), Some(Kind::Keyword));
+        assert_eq!(kind_of(t, "cargo"), Some(Kind::Function));
+        assert_eq!(kind_of(t, "test"), Some(Kind::Plain));
+        assert_eq!(kind_of(t, "$1"), Some(Kind::Variable));
+        assert_eq!(kind_of(t, "grep"), Some(Kind::Function));
+        assert_eq!(kind_of(t, "# x"), Some(Kind::Comment));
+
+        let out = kinds(Language::Diff, &["--- a/x", "@@ -1 +1 @@", "-old", "+new", " same"]);
+        let k: Vec<Kind> = out.iter().map(|t| t[0].0).collect();
+        assert_eq!(k, [Kind::Heading, Kind::Hunk, Kind::Removed, Kind::Added, Kind::Plain]);
+    }
+
+    #[test]
+    fn language_detection() {
+        assert_eq!(Language::from_tag("rust,ignore"), Language::Rust);
+        assert_eq!(Language::from_tag("TSX"), Language::TypeScript);
+        assert_eq!(Language::from_tag("brainfuck"), Language::Plain);
+        assert_eq!(Language::from_path("src/app.test.ts"), Language::TypeScript);
+        assert_eq!(Language::from_path("deploy/ci.yml"), Language::Yaml);
+        assert_eq!(Language::from_path("./Makefile"), Language::Plain);
+
+        let out = kinds(Language::Plain, &["run \"quoted\" 42 # note"]);
+        assert_eq!(kind_of(&out[0], "\"quoted\""), Some(Kind::String));
+        assert_eq!(kind_of(&out[0], "42"), Some(Kind::Number));
+        assert_eq!(kind_of(&out[0], "# note"), Some(Kind::Comment));
+    }
+}
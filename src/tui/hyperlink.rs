@@ -0,0 +1,180 @@
+use crossterm::style::{Attribute, Color as CColor, Print, SetAttribute, SetBackgroundColor, SetForegroundColor};
+use crossterm::{cursor, queue};
+use ratatui::buffer::Buffer;
+use ratatui::prelude::*;
+use ratatui::text::{Line as TuiLine, Span};
+use std::io::{self, Write};
+
+// OSC 8 hyperlinks. ratatui counts escape bytes as cell width, so links
+// cannot go through the buffer: rendered lines carry the sequences as
+// marker spans, `extract` strips them before layout, and `print` writes the
+// linked cells again with the sequences around them after each draw.
+
+const OPEN_PREFIX: &str = "\x1b]8;;";
+const TERMINATOR: &str = "\x1b\\";
+
+/// A link on screen: `width` cells from (`x`, `y`), showing `text`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hyperlink {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub url: String,
+    pub text: String,
+}
+
+impl Hyperlink {
+    /// The link at `x`..`x + width` of row `y`, with the text the
+    /// buffer holds there.
+    pub fn at(buffer: &Buffer, x: u16, y: u16, width: u16, url: String) -> Self {
+        Self { x, y, width, url, text: cells_text(buffer, x, y, width).unwrap_or_default() }
+    }
+}
+
+fn cells_text(buffer: &Buffer, x: u16, y: u16, width: u16) -> Option<String> {
+    (x..x.saturating_add(width))
+        .map(|x| buffer.cell(Position::new(x, y)).map(|c| c.symbol().to_string()))
+        .collect()
+}
+
+/// Marker span that starts a link to `url`.
+pub fn open(url: &str) -> Span<'static> {
+    Span::raw(format!("{}{}{}", OPEN_PREFIX, url, TERMINATOR))
+}
+
+/// Marker span that ends the current link.
+pub fn close() -> Span<'static> {
+    Span::raw(format!("{}{}", OPEN_PREFIX, TERMINATOR))
+}
+
+fn marker_url(span: &Span) -> Option<String> {
+    span.content
+        .strip_prefix(OPEN_PREFIX)
+        .and_then(|rest| rest.strip_suffix(TERMINATOR))
+        .map(str::to_string)
+}
+
+/// Remove link markers from `line`, returning the plain line and the links
+/// it contained as (column, width, url).
+pub fn extract<'a>(line: TuiLine<'a>) -> (TuiLine<'a>, Vec<(u16, u16, String)>) {
+    if !line.spans.iter().any(|s| s.content.starts_with(OPEN_PREFIX)) {
+        return (line, Vec::new());
+    }
+    let mut spans = Vec::new();
+    let mut links = Vec::new();
+    let mut col = 0u16;
+    let mut current: Option<(u16, String)> = None;
+    let style = line.style;
+    let alignment = line.alignment;
+    for span in line.spans {
+        match marker_url(&span) {
+            Some(url) => {
+                if let Some((start, url)) = current.take() {
+                    links.push((start, col - start, url));
+                }
+                if !url.is_empty() {
+                    current = Some((col, url));
+                }
+            }
+            None => {
+                col = col.saturating_add(span.width() as u16);
+                spans.push(span);
+            }
+        }
+    }
+    if let Some((start, url)) = current {
+        links.push((start, col - start, url));
+    }
+    let mut plain = TuiLine::from(spans).style(style);
+    plain.alignment = alignment;
+    (plain, links)
+}
+
+/// Links whose cells still show their text, i.e. are not covered by a
+/// popup drawn after the chat.
+pub fn visible(links: &[Hyperlink], buffer: &Buffer) -> Vec<Hyperlink> {
+    links
+        .iter()
+        .filter(|link| cells_text(buffer, link.x, link.y, link.width).as_ref() == Some(&link.text))
+        .cloned()
+        .collect()
+}
+
+/// Write the cells of each link again, wrapped in OSC 8 sequences, leaving
+/// the cursor where it was.
+pub fn print(links: &[Hyperlink], buffer: &Buffer) -> io::Result<()> {
+    if links.is_empty() {
+        return Ok(());
+    }
+    let mut out = io::stdout();
+    queue!(out, cursor::SavePosition)?;
+    for link in links {
+        queue!(
+            out,
+            cursor::MoveTo(link.x, link.y),
+            Print(format!("{}{}{}", OPEN_PREFIX, link.url, TERMINATOR))
+        )?;
+        let mut x = link.x;
+        while x < link.x + link.width {
+            let cell = &buffer[(x, link.y)];
+            let style = cell.style();
+            queue!(out, SetAttribute(Attribute::Reset))?;
+            if let Some(fg) = style.fg {
+                queue!(out, SetForegroundColor(CColor::from(fg)))?;
+            }
+            if let Some(bg) = style.bg {
+                queue!(out, SetBackgroundColor(CColor::from(bg)))?;
+            }
+            for (modifier, attribute) in [
+                (Modifier::BOLD, Attribute::Bold),
+                (Modifier::DIM, Attribute::Dim),
+                (Modifier::ITALIC, Attribute::Italic),
+                (Modifier::UNDERLINED, Attribute::Underlined),
+                (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+            ] {
+                if style.add_modifier.contains(modifier) {
+                    queue!(out, SetAttribute(attribute))?;
+                }
+            }
+            queue!(out, Print(cell.symbol()))?;
+            // Cells hidden behind a wide character are not printed
+            x += Span::raw(cell.symbol()).width().max(1) as u16;
+        }
+        queue!(out, Print(format!("{}{}", OPEN_PREFIX, TERMINATOR)), SetAttribute(Attribute::Reset))?;
+    }
+    queue!(out, cursor::RestorePosition)?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_strips_markers_and_reports_columns() {
+        let line = TuiLine::from(vec![
+            Span::raw("  see "),
+            open("https://example.com"),
+            Span::raw("the docs"),
+            close(),
+            Span::raw("."),
+        ]);
+        let (plain, links) = extract(line);
+        assert_eq!(plain.to_string(), "  see the docs.");
+        assert_eq!(links, vec![(6, 8, "https://example.com".to_string())]);
+
+        let (_, none) = extract(TuiLine::from("no links"));
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn covered_links_are_not_visible() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 10, 1));
+        buffer.set_string(0, 0, "link", Style::default());
+        let link = Hyperlink::at(&buffer, 0, 0, 4, "u".to_string());
+        assert_eq!(link.text, "link");
+        assert_eq!(visible(std::slice::from_ref(&link), &buffer), vec![link.clone()]);
+        buffer.set_string(2, 0, "XX", Style::default());
+        assert!(visible(&[link], &buffer).is_empty());
+    }
+}
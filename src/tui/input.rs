@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 
@@ -15,7 +17,85 @@ fn get_prompt(mode: &Mode) -> &'static str {
     }
 }
 
-/// Calculate the required height for the input widget based on text length and available width.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cell {
+    Prompt,
+    Text,
+    Paste,
+}
+
+/// Input laid out into wrapped rows, with the cursor's (row, column).
+/// The prompt sits on the first row; continuation rows are indented to
+/// line up with it.
+struct Layout {
+    rows: Vec<Vec<(char, Cell)>>,
+    cursor: (usize, usize),
+}
+
+fn layout(prompt: &str, text: &str, cursor: usize, pastes: &[Range<usize>], width: usize) -> Layout {
+    let width = width.max(1);
+    let indent = prompt.chars().count().min(width.saturating_sub(1));
+    let mut rows: Vec<Vec<(char, Cell)>> = vec![prompt.chars().map(|c| (c, Cell::Prompt)).collect()];
+    let mut cursor_pos = None;
+    let new_row = |rows: &mut Vec<Vec<(char, Cell)>>| rows.push(vec![(' ', Cell::Prompt); indent]);
+
+    while rows.last().is_some_and(|r| r.len() > width) {
+        let last = rows.last_mut().unwrap();
+        let rest = last.split_off(width);
+        rows.push(rest);
+    }
+    for (i, c) in text.char_indices() {
+        if c != '\n' && rows.last().is_some_and(|r| r.len() >= width) {
+            new_row(&mut rows);
+        }
+        if i == cursor {
+            cursor_pos = Some((rows.len() - 1, rows.last().map_or(0, |r| r.len())));
+        }
+        if c == '\n' {
+            new_row(&mut rows);
+            continue;
+        }
+        let cell = if pastes.iter().any(|r| r.contains(&i)) { Cell::Paste } else { Cell::Text };
+        rows.last_mut().unwrap().push((c, cell));
+    }
+    let cursor = cursor_pos.unwrap_or_else(|| {
+        let col = rows.last().map_or(0, |r| r.len());
+        if col >= width {
+            new_row(&mut rows);
+            (rows.len() - 1, indent)
+        } else {
+            (rows.len() - 1, col)
+        }
+    });
+    Layout { rows, cursor }
+}
+
+/// Prompt, text and cursor to show: the editor, the Ctrl+R search or the
+/// Ctrl+F transcript search.
+fn content(app: &App) -> (String, String, usize) {
+    if let Some(search) = &app.transcript_search {
+        let prompt = match search.current {
+            Some(current) => format!("find '{}' ({}/{})> ", search.query, current + 1, search.matches.len()),
+            None if search.query.is_empty() => "find> ".to_string(),
+            None => format!("failing find '{}'> ", search.query),
+        };
+        return (prompt, String::new(), 0);
+    }
+    match app.input.search() {
+        Some(search) => {
+            let label = if search.query.is_empty() || search.matched.is_some() {
+                "search"
+            } else {
+                "failing search"
+            };
+            let prompt = format!("{} '{}'> ", label, search.query);
+            (prompt, app.input.search_match().unwrap_or_default().to_string(), 0)
+        }
+        None => (get_prompt(&app.mode).to_string(), app.input.text.clone(), app.input.cursor),
+    }
+}
+
+/// Calculate the required height for the input widget based on its text and available width.
 /// Returns a value between MIN_HEIGHT (3) and MAX_HEIGHT (10), including borders.
 pub fn calculate_height(app: &App, available_width: u16) -> u16 {
     // Inner width = total width minus 2 for borders
@@ -24,18 +104,8 @@ pub fn calculate_height(app: &App, available_width: u16) -> u16 {
         return MIN_HEIGHT;
     }
 
-    let prompt = get_prompt(&app.mode);
-    let prompt_chars = prompt.chars().count();
-    let text_chars = app.input_text.chars().count();
-    let total_chars = prompt_chars + text_chars;
-
-    if total_chars == 0 {
-        return MIN_HEIGHT;
-    }
-
-    // Number of wrapped lines = ceil(total_chars / inner_width)
-    let lines = (total_chars + inner_width - 1) / inner_width;
-    let lines = lines.max(1) as u16;
+    let (prompt, text, cursor) = content(app);
+    let lines = layout(&prompt, &text, cursor, &[], inner_width).rows.len() as u16;
 
     // Add 2 for borders
     (lines + 2).clamp(MIN_HEIGHT, MAX_HEIGHT)
@@ -50,112 +120,113 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App, theme: &Theme) {
             theme.builder_badge.b,
         ),
     };
+    let dim = Color::Rgb(theme.dim_text.r, theme.dim_text.g, theme.dim_text.b);
 
     let border_style = Style::default().fg(mode_color);
-    let block = Block::default()
+    let mut block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(border_style);
 
-    let prompt = get_prompt(&app.mode);
+    let (prompt, text, cursor) = content(app);
+    let pastes = if app.input.search().is_some() { Vec::new() } else { app.input.paste_ranges() };
+    if !pastes.is_empty() {
+        block = block.title_bottom(Line::from(Span::styled(" Ctrl+O: expand paste ", Style::default().fg(dim))));
+    }
+    if app.transcript_search.is_some() {
+        let hint = " ↑↓ older/newer match · Enter: stay here · Esc: cancel ";
+        block = block.title_bottom(Line::from(Span::styled(hint, Style::default().fg(dim))));
+    }
     let inner_width = area.width.saturating_sub(2) as usize;
+    if inner_width == 0 {
+        frame.render_widget(block, area);
+        return;
+    }
 
-    let is_placeholder = app.input_text.is_empty();
-    let display_text = if is_placeholder {
+    let is_placeholder = text.is_empty() && app.input.search().is_none() && app.transcript_search.is_none();
+    let shown = if is_placeholder {
         "Type a message, / for commands, Tab to toggle mode..."
     } else {
-        &app.input_text
+        &text
     };
+    let Layout { rows, cursor: (cursor_row, cursor_col) } =
+        layout(&prompt, shown, cursor, &pastes, inner_width);
 
     let prompt_style = Style::default().fg(mode_color).bold();
     let text_style = if is_placeholder {
-        Style::default().fg(Color::Rgb(
-            theme.dim_text.r,
-            theme.dim_text.g,
-            theme.dim_text.b,
-        ))
+        Style::default().fg(dim)
     } else {
         Style::default().fg(Color::Rgb(theme.text.r, theme.text.g, theme.text.b))
     };
+    let paste_style = Style::default().fg(Color::Rgb(theme.accent.r, theme.accent.g, theme.accent.b));
+
+    // Calculate visible lines based on inner height (area height - 2 for borders),
+    // scrolling to keep the cursor visible
+    let inner_height = (area.height.saturating_sub(2) as usize).max(1);
+    let scroll_start = (cursor_row + 1).saturating_sub(inner_height);
+
+    let lines: Vec<Line> = rows
+        .iter()
+        .skip(scroll_start)
+        .take(inner_height)
+        .map(|row| {
+            Line::from(
+                row.iter()
+                    .map(|&(ch, cell)| {
+                        let style = match cell {
+                            Cell::Prompt => prompt_style,
+                            Cell::Text => text_style,
+                            Cell::Paste => paste_style,
+                        };
+                        Span::styled(String::from(ch), style)
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+
+    let content = Paragraph::new(Text::from(lines)).block(block);
+    frame.render_widget(content, area);
+
+    // Position cursor
+    if !app.is_streaming {
+        let cursor_x = area.x + 1 + cursor_col as u16;
+        let cursor_y = area.y + 1 + (cursor_row - scroll_start) as u16;
 
-    // Build full display string: prompt + text, then wrap manually into Line objects
-    let full_text: String = format!("{}{}", prompt, display_text);
-    let full_chars: Vec<char> = full_text.chars().collect();
-    let prompt_char_count = prompt.chars().count();
-
-    let mut lines: Vec<Line> = Vec::new();
-    if inner_width > 0 {
-        for chunk in full_chars.chunks(inner_width) {
-            let mut spans: Vec<Span> = Vec::new();
-            // Track the global char offset for this chunk
-            let chunk_start = lines.len() * inner_width;
-
-            for (i, &ch) in chunk.iter().enumerate() {
-                let global_idx = chunk_start + i;
-                let style = if global_idx < prompt_char_count {
-                    prompt_style
-                } else {
-                    text_style
-                };
-                spans.push(Span::styled(String::from(ch), style));
-            }
-            lines.push(Line::from(spans));
+        // Only set cursor if it's within the widget bounds
+        if cursor_x < area.x + area.width - 1 && cursor_y < area.y + area.height - 1 {
+            frame.set_cursor_position(Position::new(cursor_x, cursor_y));
         }
     }
-    if lines.is_empty() {
-        lines.push(Line::from(Span::styled("", text_style)));
-    }
-
-    // Calculate visible lines based on inner height (area height - 2 for borders)
-    let inner_height = area.height.saturating_sub(2) as usize;
-
-    // If there are more lines than visible, scroll to keep cursor visible
-    let visible_lines = if lines.len() > inner_height && inner_height > 0 {
-        // Find which line the cursor is on
-        let cursor_char_pos = prompt_char_count
-            + app.input_text[..app.input_cursor].chars().count();
-        let cursor_line = if inner_width > 0 {
-            cursor_char_pos / inner_width
-        } else {
-            0
-        };
-        let scroll_start = if cursor_line >= inner_height {
-            cursor_line - inner_height + 1
-        } else {
-            0
-        };
-        lines[scroll_start..lines.len().min(scroll_start + inner_height)].to_vec()
-    } else {
-        lines.clone()
-    };
-
-    let content = Paragraph::new(Text::from(visible_lines)).block(block);
-    frame.render_widget(content, area);
+}
 
-    // Position cursor with wrapping
-    if !app.is_streaming {
-        let cursor_char_pos = prompt_char_count
-            + app.input_text[..app.input_cursor].chars().count();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if inner_width > 0 {
-            let cursor_line = cursor_char_pos / inner_width;
-            let cursor_col = cursor_char_pos % inner_width;
+    fn row_text(row: &[(char, Cell)]) -> String {
+        row.iter().map(|(c, _)| c).collect()
+    }
 
-            // Adjust for scrolling
-            let scroll_start = if lines.len() > inner_height && cursor_line >= inner_height {
-                cursor_line - inner_height + 1
-            } else {
-                0
-            };
-            let visible_cursor_line = cursor_line.saturating_sub(scroll_start);
+    #[test]
+    fn layout_wraps_and_indents_continuation_lines() {
+        let text = "one\ntwo three";
+        let out = layout("> ", text, text.len(), &[], 8);
+        let rows: Vec<String> = out.rows.iter().map(|r| row_text(r)).collect();
+        assert_eq!(rows, ["> one", "  two th", "  ree"]);
+        assert_eq!(out.cursor, (2, 5));
 
-            let cursor_x = area.x + 1 + cursor_col as u16;
-            let cursor_y = area.y + 1 + visible_cursor_line as u16;
+        let out = layout("> ", text, 4, &[], 8);
+        assert_eq!(out.cursor, (1, 2));
+    }
 
-            // Only set cursor if it's within the widget bounds
-            if cursor_x < area.x + area.width - 1 && cursor_y < area.y + area.height - 1 {
-                frame.set_cursor_position(Position::new(cursor_x, cursor_y));
-            }
-        }
+    #[test]
+    fn layout_marks_paste_placeholders() {
+        let text = "a [Pasted text #1 +20 lines]";
+        let paste = 2..text.len();
+        let out = layout("> ", text, 0, &[paste], 80);
+        assert_eq!(out.rows[0][2].1, Cell::Text);
+        assert_eq!(out.rows[0][4].1, Cell::Paste);
+        assert_eq!(out.cursor, (0, 2));
     }
 }
@@ -8,19 +8,25 @@ use crate::tui::app::{App, AppScreen, Overlay, SystemMessageType};
 use crate::tui::chat_view;
 use crate::tui::command_palette;
 use crate::tui::config_menu;
+use crate::tui::confirm;
 use crate::tui::file_picker;
 use crate::tui::header;
+use crate::tui::hyperlink::Hyperlink;
 use crate::tui::input;
+use crate::tui::pager;
+use crate::tui::session_list;
 use crate::tui::status_bar;
 use crate::tui::todo_panel;
 
-/// Main draw function that renders the entire layout.
-pub fn draw(frame: &mut Frame, app: &App) {
+/// Main draw function that renders the entire layout. Returns the links in
+/// the chat, for `hyperlink::print`.
+pub fn draw(frame: &mut Frame, app: &App) -> Vec<Hyperlink> {
     let theme = get_theme(app.theme_name());
 
     match &app.screen {
         AppScreen::ApiKeyPrompt => {
             api_key_prompt::render(frame, frame.area(), &app.api_key_state, theme);
+            Vec::new()
         }
         AppScreen::ConfigMenu => {
             config_menu::render(
@@ -32,16 +38,23 @@ pub fn draw(frame: &mut Frame, app: &App) {
                 &app.config.theme,
                 &app.config.model,
             );
+            Vec::new()
         }
-        AppScreen::Chat => {
-            draw_chat_screen(frame, app, theme);
-        }
+        AppScreen::Chat => draw_chat_screen(frame, app, theme),
     }
 }
 
-fn draw_chat_screen(frame: &mut Frame, app: &App, theme: &crate::config::themes::Theme) {
-    let area = frame.area();
+/// Areas of the chat screen; optional sections are `None` when hidden.
+struct ChatAreas {
+    header: Rect,
+    todo: Option<Rect>,
+    chat: Rect,
+    system: Option<Rect>,
+    input: Rect,
+    status: Rect,
+}
 
+fn chat_areas(app: &App, area: Rect) -> ChatAreas {
     // Layout: Header(3) | TodoPanel?(N) | Chat(flex) | SystemMsg?(1) | Input(3-10) | StatusBar(1)
     let has_system_msg = app.system_message.is_some();
     let has_todos = !app.todo_items.is_empty();
@@ -93,6 +106,32 @@ fn draw_chat_screen(frame: &mut Frame, app: &App, theme: &crate::config::themes:
     idx += 1;
     let status_area = chunks[idx];
 
+    ChatAreas {
+        header: header_area,
+        todo: todo_area,
+        chat: chat_area,
+        system: system_area,
+        input: input_area,
+        status: status_area,
+    }
+}
+
+/// The chat message area for a screen of the given size.
+pub fn chat_area(app: &App, area: Rect) -> Rect {
+    chat_areas(app, area).chat
+}
+
+fn draw_chat_screen(frame: &mut Frame, app: &App, theme: &crate::config::themes::Theme) -> Vec<Hyperlink> {
+    let area = frame.area();
+    let ChatAreas {
+        header: header_area,
+        todo: todo_area,
+        chat: chat_area,
+        system: system_area,
+        input: input_area,
+        status: status_area,
+    } = chat_areas(app, area);
+
     // Draw header
     header::render(frame, header_area, app, theme);
 
@@ -102,7 +141,7 @@ fn draw_chat_screen(frame: &mut Frame, app: &App, theme: &crate::config::themes:
     }
 
     // Draw chat messages
-    chat_view::render(frame, chat_area, app, theme);
+    let links = chat_view::render(frame, chat_area, app, theme);
 
     // Draw system message if present
     if let (Some(area), Some(msg)) = (system_area, &app.system_message) {
@@ -136,113 +175,25 @@ fn draw_chat_screen(frame: &mut Frame, app: &App, theme: &crate::config::themes:
         Overlay::FilePicker => {
             file_picker::render(frame, area, &app.file_picker_state, theme);
         }
-        Overlay::SessionList { selected } => {
-            draw_session_list(frame, area, app, *selected, theme);
+        Overlay::SessionList => {
+            session_list::render(frame, area, &app.session_list_state, theme);
         }
         Overlay::AgentQuestion => {
             if let Some(ref state) = app.agent_question_state {
                 agent_question::render(frame, area, state, theme);
             }
         }
+        Overlay::Confirm(_) => {
+            if let Some(ref state) = app.confirm_state {
+                confirm::render(frame, area, state, theme);
+            }
+        }
+        Overlay::Pager => {
+            if let Some(ref state) = app.pager_state {
+                pager::render(frame, area, state, theme);
+            }
+        }
         Overlay::None => {}
     }
-}
-
-fn draw_session_list(
-    frame: &mut Frame,
-    area: Rect,
-    app: &App,
-    selected: usize,
-    theme: &crate::config::themes::Theme,
-) {
-    let sessions = app.list_sessions();
-    if sessions.is_empty() {
-        let h = 5u16;
-        let w = 40u16.min(area.width.saturating_sub(4));
-        let x = (area.width.saturating_sub(w)) / 2;
-        let y = (area.height.saturating_sub(h)) / 2;
-        let overlay_area = Rect::new(x, y, w, h);
-        frame.render_widget(Clear, overlay_area);
-        let block = Block::default()
-            .title(" Sessions ")
-            .borders(Borders::ALL)
-            .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(Color::Rgb(
-                theme.accent.r,
-                theme.accent.g,
-                theme.accent.b,
-            )))
-            .style(Style::default().bg(Color::Rgb(
-                theme.surface.r,
-                theme.surface.g,
-                theme.surface.b,
-            )));
-        let para = Paragraph::new(" No sessions yet. Press Esc to go back.")
-            .style(Style::default().fg(Color::Rgb(
-                theme.dim_text.r,
-                theme.dim_text.g,
-                theme.dim_text.b,
-            )))
-            .block(block);
-        frame.render_widget(para, overlay_area);
-        return;
-    }
-
-    let list_height = (sessions.len() as u16 + 3).min(area.height.saturating_sub(4));
-    let list_width = 60u16.min(area.width.saturating_sub(4));
-    let x = (area.width.saturating_sub(list_width)) / 2;
-    let y = (area.height.saturating_sub(list_height)) / 2;
-    let overlay_area = Rect::new(x, y, list_width, list_height);
-
-    frame.render_widget(Clear, overlay_area);
-
-    let list_items: Vec<ListItem> = sessions
-        .iter()
-        .enumerate()
-        .map(|(i, (_, name, model))| {
-            let style = if i == selected {
-                Style::default()
-                    .fg(Color::Rgb(theme.bg.r, theme.bg.g, theme.bg.b))
-                    .bg(Color::Rgb(theme.accent.r, theme.accent.g, theme.accent.b))
-                    .bold()
-            } else {
-                Style::default().fg(Color::Rgb(theme.text.r, theme.text.g, theme.text.b))
-            };
-            let indicator = if i == selected { "\u{25b8} " } else { "  " };
-            ListItem::new(Line::from(vec![
-                Span::styled(indicator, style),
-                Span::styled(format!("{:<30}", name), style),
-                Span::styled(
-                    format!(" {}", model),
-                    if i == selected {
-                        style
-                    } else {
-                        Style::default().fg(Color::Rgb(
-                            theme.dim_text.r,
-                            theme.dim_text.g,
-                            theme.dim_text.b,
-                        ))
-                    },
-                ),
-            ]))
-        })
-        .collect();
-
-    let block = Block::default()
-        .title(format!(" Sessions ({}) ", sessions.len()))
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(Color::Rgb(
-            theme.accent.r,
-            theme.accent.g,
-            theme.accent.b,
-        )))
-        .style(Style::default().bg(Color::Rgb(
-            theme.surface.r,
-            theme.surface.g,
-            theme.surface.b,
-        )));
-
-    let list = List::new(list_items).block(block);
-    frame.render_widget(list, overlay_area);
+    links
 }
@@ -1,404 +1,757 @@
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
 use ratatui::prelude::*;
 use ratatui::text::{Line as TuiLine, Span};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::config::themes::Theme;
+use crate::tui::highlight::{Highlighter, Language};
+use crate::tui::hyperlink;
 
-/// Convert a markdown string into styled ratatui Lines.
-pub fn markdown_to_lines<'a>(text: &str, theme: &Theme, width: u16) -> Vec<TuiLine<'a>> {
-    let accent = Color::Rgb(theme.accent.r, theme.accent.g, theme.accent.b);
-    let text_color = Color::Rgb(theme.text.r, theme.text.g, theme.text.b);
-    let dim = Color::Rgb(theme.dim_text.r, theme.dim_text.g, theme.dim_text.b);
+/// Bullets for unordered lists, by nesting depth.
+const BULLETS: [&str; 3] = ["• ", "◦ ", "▪ "];
+/// Narrowest a table column is squeezed to.
+const MIN_COLUMN_WIDTH: usize = 3;
 
-    let mut lines: Vec<TuiLine<'a>> = Vec::new();
-    let mut in_code_block = false;
+const OPTIONS: Options = Options::ENABLE_TABLES
+    .union(Options::ENABLE_STRIKETHROUGH)
+    .union(Options::ENABLE_TASKLISTS);
+
+/// Convert a markdown string into styled ratatui Lines. Code blocks are
+/// numbered from `first_code_block`, for `/yank`.
+pub fn markdown_to_lines<'a>(text: &str, theme: &Theme, width: u16, first_code_block: usize) -> Vec<TuiLine<'a>> {
     let content_width = width.saturating_sub(4) as usize; // 2 indent + some margin
+    let mut renderer = Renderer::new(theme, content_width, first_code_block);
+    for event in Parser::new_ext(text, OPTIONS) {
+        renderer.event(event);
+    }
+    renderer.finish()
+}
 
-    for raw_line in text.lines() {
-        // Fenced code block toggle
-        if raw_line.trim_start().starts_with("```") {
-            in_code_block = !in_code_block;
-            if in_code_block {
-                // Show the language tag dimmed
-                let lang = raw_line.trim_start().trim_start_matches('`').trim();
-                if !lang.is_empty() {
-                    lines.push(TuiLine::from(vec![
-                        Span::raw("  "),
-                        Span::styled(format!("─ {} ", lang), Style::default().fg(dim)),
-                        Span::styled(
-                            "─".repeat(content_width.saturating_sub(lang.len() + 3)),
-                            Style::default().fg(dim),
-                        ),
-                    ]));
+/// Contents of the code blocks in a markdown string, in order.
+pub fn code_blocks(text: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Option<String> = None;
+    for event in Parser::new_ext(text, OPTIONS) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => current = Some(String::new()),
+            Event::Text(text) => {
+                if let Some(code) = &mut current {
+                    code.push_str(&text);
                 }
-            } else {
-                // End of code block — add a thin separator
-                lines.push(TuiLine::from(Span::styled(
-                    format!("  {}", "─".repeat(content_width)),
-                    Style::default().fg(dim),
-                )));
             }
-            continue;
-        }
-
-        if in_code_block {
-            // Code lines: indented, accent color
-            lines.push(TuiLine::from(vec![
-                Span::raw("  "),
-                Span::styled(raw_line.to_string(), Style::default().fg(accent)),
-            ]));
-            continue;
+            Event::End(TagEnd::CodeBlock) => blocks.extend(current.take()),
+            _ => {}
         }
+    }
+    blocks
+}
 
-        let trimmed = raw_line.trim();
+// ── Inline text ────────────────────────────────────────────────────────
 
-        // Empty line
-        if trimmed.is_empty() {
-            lines.push(TuiLine::from(""));
-            continue;
-        }
+/// A run of inline text with one style, possibly inside a link.
+#[derive(Debug, Clone, PartialEq)]
+struct Piece {
+    text: String,
+    style: Style,
+    link: Option<String>,
+}
 
-        // Headers (# ## ###)
-        if trimmed.starts_with('#') {
-            let content = trimmed.trim_start_matches('#').trim();
-            lines.push(TuiLine::from(vec![Span::styled(
-                format!("  {}", content),
-                Style::default().fg(accent).bold(),
-            )]));
-            continue;
-        }
+impl Piece {
+    fn width(&self) -> usize {
+        self.text.width()
+    }
+}
 
-        // Blockquotes (> )
-        if trimmed.starts_with("> ") || trimmed == ">" {
-            let content = trimmed.strip_prefix("> ").unwrap_or("").trim();
-            let wrapped = wrap_text(content, content_width.saturating_sub(4));
-            for w in wrapped {
-                lines.push(TuiLine::from(vec![
-                    Span::raw("  "),
-                    Span::styled("│ ", Style::default().fg(dim)),
-                    Span::styled(w, Style::default().fg(dim).italic()),
-                ]));
-            }
-            continue;
-        }
+enum Token {
+    Word(Vec<Piece>),
+    Space(Piece),
+    Break,
+}
 
-        // Unordered list items (- or * )
-        if (trimmed.starts_with("- ") || trimmed.starts_with("* ")) && trimmed.len() > 2 {
-            let content = &trimmed[2..];
-            let wrapped = wrap_text(content, content_width.saturating_sub(4));
-            for (i, w) in wrapped.iter().enumerate() {
-                if i == 0 {
-                    lines.push(TuiLine::from(vec![
-                        Span::raw("  "),
-                        Span::styled("• ", Style::default().fg(dim)),
-                        Span::styled(w.clone(), Style::default().fg(text_color)),
-                    ]));
-                } else {
-                    lines.push(TuiLine::from(vec![
-                        Span::raw("    "),
-                        Span::styled(w.clone(), Style::default().fg(text_color)),
-                    ]));
+/// Split pieces into words, spaces and hard breaks. A word may span several
+/// pieces, as in `**bold**text`.
+fn tokenize(pieces: &[Piece]) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word: Vec<Piece> = Vec::new();
+    for piece in pieces {
+        let mut run = String::new();
+        let mut run_is_space = false;
+        let flush_run = |run: &mut String, is_space: bool, word: &mut Vec<Piece>, tokens: &mut Vec<Token>| {
+            if run.is_empty() {
+                return;
+            }
+            let p = Piece { text: std::mem::take(run), style: piece.style, link: piece.link.clone() };
+            if is_space {
+                if !word.is_empty() {
+                    tokens.push(Token::Word(std::mem::take(word)));
                 }
+                tokens.push(Token::Space(p));
+            } else {
+                word.push(p);
             }
-            continue;
-        }
-
-        // Ordered list items (1. 2. etc.)
-        if let Some(rest) = strip_ordered_list_prefix(trimmed) {
-            let wrapped = wrap_text(rest, content_width.saturating_sub(5));
-            for (i, w) in wrapped.iter().enumerate() {
-                if i == 0 {
-                    let prefix = &trimmed[..trimmed.len() - rest.len()];
-                    lines.push(TuiLine::from(vec![
-                        Span::raw("  "),
-                        Span::styled(prefix.to_string(), Style::default().fg(dim)),
-                        Span::styled(w.clone(), Style::default().fg(text_color)),
-                    ]));
-                } else {
-                    lines.push(TuiLine::from(vec![
-                        Span::raw("     "),
-                        Span::styled(w.clone(), Style::default().fg(text_color)),
-                    ]));
+        };
+        for c in piece.text.chars() {
+            if c == '\n' {
+                flush_run(&mut run, run_is_space, &mut word, &mut tokens);
+                if !word.is_empty() {
+                    tokens.push(Token::Word(std::mem::take(&mut word)));
                 }
+                tokens.push(Token::Break);
+                continue;
             }
-            continue;
-        }
-
-        // Regular paragraph — apply inline formatting and wrap
-        let wrapped = wrap_text(trimmed, content_width);
-        for w in wrapped {
-            let spans = parse_inline_formatting(&w, text_color, accent, dim);
-            let mut final_spans = vec![Span::raw("  ")];
-            final_spans.extend(spans);
-            lines.push(TuiLine::from(final_spans));
+            let is_space = c == ' ';
+            if is_space != run_is_space {
+                flush_run(&mut run, run_is_space, &mut word, &mut tokens);
+                run_is_space = is_space;
+            }
+            run.push(c);
         }
+        flush_run(&mut run, run_is_space, &mut word, &mut tokens);
     }
-
-    // If we ended inside a code block, close it
-    if in_code_block {
-        lines.push(TuiLine::from(Span::styled(
-            format!("  {}", "─".repeat(content_width)),
-            Style::default().fg(dim),
-        )));
+    if !word.is_empty() {
+        tokens.push(Token::Word(word));
     }
-
-    lines
+    tokens
 }
 
-/// Parse inline markdown formatting: **bold**, *italic*, `code`, [link](url)
-fn parse_inline_formatting(text: &str, text_color: Color, accent: Color, _dim: Color) -> Vec<Span<'static>> {
-    let mut spans = Vec::new();
-    let mut chars = text.chars().peekable();
-    let mut current = String::new();
-
-    while let Some(&ch) = chars.peek() {
-        match ch {
-            '`' => {
-                // Inline code
-                if !current.is_empty() {
-                    spans.push(Span::styled(
-                        std::mem::take(&mut current),
-                        Style::default().fg(text_color),
-                    ));
+/// Wrap styled pieces at `width` display columns, breaking on spaces and
+/// splitting words longer than a line.
+fn wrap_pieces(pieces: &[Piece], width: usize) -> Vec<Vec<Piece>> {
+    let width = width.max(1);
+    let mut lines: Vec<Vec<Piece>> = Vec::new();
+    let mut line: Vec<Piece> = Vec::new();
+    let mut line_width = 0;
+    let mut space: Option<Piece> = None;
+
+    for token in tokenize(pieces) {
+        match token {
+            Token::Break => {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0;
+                space = None;
+            }
+            Token::Space(piece) => {
+                if !line.is_empty() {
+                    space = Some(piece);
                 }
-                chars.next(); // consume `
-                let mut code = String::new();
-                while let Some(&c) = chars.peek() {
-                    if c == '`' {
-                        chars.next();
-                        break;
-                    }
-                    code.push(c);
-                    chars.next();
+            }
+            Token::Word(word) => {
+                let word_width: usize = word.iter().map(Piece::width).sum();
+                let space_width = space.as_ref().map_or(0, Piece::width);
+                if !line.is_empty() && line_width + space_width + word_width > width {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                    space = None;
+                }
+                if let Some(space) = space.take() {
+                    line_width += space.width();
+                    line.push(space);
                 }
-                spans.push(Span::styled(code, Style::default().fg(accent)));
-            }
-            '*' => {
-                chars.next();
-                if chars.peek() == Some(&'*') {
-                    // **bold**
-                    chars.next();
-                    if !current.is_empty() {
-                        spans.push(Span::styled(
-                            std::mem::take(&mut current),
-                            Style::default().fg(text_color),
-                        ));
+                for piece in word {
+                    if line_width + piece.width() <= width {
+                        line_width += piece.width();
+                        line.push(piece);
+                        continue;
                     }
-                    let mut bold_text = String::new();
-                    while let Some(&c) = chars.peek() {
-                        if c == '*' {
-                            chars.next();
-                            if chars.peek() == Some(&'*') {
-                                chars.next();
-                                break;
+                    // Longer than a line: break at character boundaries
+                    let mut chunk = String::new();
+                    for c in piece.text.chars() {
+                        let w = c.width().unwrap_or(0);
+                        if line_width + w > width && line_width > 0 {
+                            if !chunk.is_empty() {
+                                line.push(Piece { text: std::mem::take(&mut chunk), ..piece.clone() });
                             }
-                            bold_text.push('*');
-                            continue;
+                            lines.push(std::mem::take(&mut line));
+                            line_width = 0;
                         }
-                        bold_text.push(c);
-                        chars.next();
+                        chunk.push(c);
+                        line_width += w;
                     }
-                    spans.push(Span::styled(
-                        bold_text,
-                        Style::default().fg(text_color).bold(),
-                    ));
-                } else {
-                    // *italic*
-                    if !current.is_empty() {
-                        spans.push(Span::styled(
-                            std::mem::take(&mut current),
-                            Style::default().fg(text_color),
-                        ));
+                    if !chunk.is_empty() {
+                        line.push(Piece { text: chunk, ..piece.clone() });
                     }
-                    let mut italic_text = String::new();
-                    while let Some(&c) = chars.peek() {
-                        if c == '*' {
-                            chars.next();
-                            break;
-                        }
-                        italic_text.push(c);
-                        chars.next();
-                    }
-                    spans.push(Span::styled(
-                        italic_text,
-                        Style::default().fg(text_color).italic(),
-                    ));
                 }
             }
-            '[' => {
-                // [link text](url)
-                chars.next();
-                let mut link_text = String::new();
-                let mut found_close = false;
-                while let Some(&c) = chars.peek() {
-                    if c == ']' {
-                        chars.next();
-                        found_close = true;
-                        break;
-                    }
-                    link_text.push(c);
-                    chars.next();
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Spans for a wrapped line, with hyperlink markers around linked text.
+fn to_spans(pieces: Vec<Piece>) -> Vec<Span<'static>> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut open: Option<String> = None;
+    for piece in pieces {
+        if piece.link != open {
+            if open.is_some() {
+                spans.push(hyperlink::close());
+            }
+            if let Some(url) = &piece.link {
+                spans.push(hyperlink::open(url));
+            }
+            open = piece.link.clone();
+        }
+        match spans.last_mut() {
+            Some(last) if last.style == piece.style && !last.content.starts_with('\x1b') => {
+                last.content = format!("{}{}", last.content, piece.text).into();
+            }
+            _ => spans.push(Span::styled(piece.text, piece.style)),
+        }
+    }
+    if open.is_some() {
+        spans.push(hyperlink::close());
+    }
+    spans
+}
+
+fn pieces_width(pieces: &[Piece]) -> usize {
+    pieces.iter().map(Piece::width).sum()
+}
+
+// ── Blocks ─────────────────────────────────────────────────────────────
+
+enum Container {
+    Quote,
+    /// A list item; the marker shows on its first line only.
+    Item { marker: String, first_line: bool },
+}
+
+struct List {
+    next_number: Option<u64>,
+}
+
+struct Table {
+    alignments: Vec<Alignment>,
+    rows: Vec<Vec<Vec<Piece>>>,
+    row: Vec<Vec<Piece>>,
+    header_rows: usize,
+}
+
+struct Renderer<'t> {
+    theme: &'t Theme,
+    width: usize,
+    lines: Vec<TuiLine<'static>>,
+    /// Inline content of the current paragraph, heading or table cell.
+    inline: Vec<Piece>,
+    styles: Vec<Style>,
+    links: Vec<String>,
+    containers: Vec<Container>,
+    lists: Vec<List>,
+    code: Option<(Highlighter, String)>,
+    /// Number shown on the next code block.
+    code_number: usize,
+    table: Option<Table>,
+}
+
+impl<'t> Renderer<'t> {
+    fn new(theme: &'t Theme, width: usize, first_code_block: usize) -> Self {
+        Self {
+            theme,
+            width,
+            lines: Vec::new(),
+            inline: Vec::new(),
+            styles: Vec::new(),
+            links: Vec::new(),
+            containers: Vec::new(),
+            lists: Vec::new(),
+            code: None,
+            code_number: first_code_block,
+            table: None,
+        }
+    }
+
+    fn color(&self, c: crate::config::themes::Color) -> Color {
+        Color::Rgb(c.r, c.g, c.b)
+    }
+
+    fn text_style(&self) -> Style {
+        Style::default().fg(self.color(self.theme.text))
+    }
+
+    fn dim_style(&self) -> Style {
+        Style::default().fg(self.color(self.theme.dim_text))
+    }
+
+    fn accent_style(&self) -> Style {
+        Style::default().fg(self.color(self.theme.accent))
+    }
+
+    fn style(&self) -> Style {
+        self.styles.last().copied().unwrap_or_else(|| self.text_style())
+    }
+
+    fn push_style(&mut self, f: impl FnOnce(Style) -> Style) {
+        let style = f(self.style());
+        self.styles.push(style);
+    }
+
+    fn text(&mut self, text: &str, style: Style) {
+        if let Some((_, code)) = &mut self.code {
+            code.push_str(text);
+            return;
+        }
+        self.inline.push(Piece { text: text.to_string(), style, link: self.links.last().cloned() });
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => self.text(&text, self.style()),
+            Event::Code(code) => {
+                let style = self.style().patch(self.accent_style());
+                self.text(&code, style);
+            }
+            Event::InlineMath(math) | Event::DisplayMath(math) => {
+                let style = self.style().patch(self.accent_style());
+                self.text(&math, style);
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                let style = self.dim_style();
+                self.text(html.trim_end_matches('\n'), style);
+            }
+            Event::FootnoteReference(name) => {
+                let style = self.dim_style();
+                self.text(&format!("[^{}]", name), style);
+            }
+            Event::SoftBreak => self.text(" ", self.style()),
+            Event::HardBreak => self.text("\n", self.style()),
+            Event::Rule => {
+                self.start_block();
+                let width = self.width.saturating_sub(self.prefix_width());
+                let rule = Span::styled("─".repeat(width), self.dim_style());
+                self.push_line(vec![rule]);
+            }
+            Event::TaskListMarker(done) => {
+                let marker = if done { "[x] " } else { "[ ] " };
+                let style = self.dim_style();
+                self.text(marker, style);
+            }
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.start_block(),
+            Tag::Heading { level, .. } => {
+                self.start_block();
+                let accent = self.color(self.theme.accent);
+                let style = match level {
+                    HeadingLevel::H1 => Style::default().fg(accent).bold().underlined(),
+                    HeadingLevel::H2 => Style::default().fg(accent).bold(),
+                    _ => self.text_style().bold(),
+                };
+                self.styles.push(style);
+            }
+            Tag::BlockQuote(_) => {
+                self.flush();
+                self.start_block();
+                self.containers.push(Container::Quote);
+                self.push_style(|s| s.italic());
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush();
+                self.start_block();
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => info.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                let lang = lang.trim();
+                // Number and language tag, dimmed
+                let label = if lang.is_empty() {
+                    format!("[{}]", self.code_number)
+                } else {
+                    format!("[{}] {}", self.code_number, lang)
+                };
+                self.code_number += 1;
+                let rest = self.width.saturating_sub(self.prefix_width() + label.width() + 3);
+                let header = Span::styled(format!("─ {} {}", label, "─".repeat(rest)), self.dim_style());
+                self.push_line(vec![header]);
+                self.code = Some((Highlighter::new(Language::from_tag(lang)), String::new()));
+            }
+            Tag::List(start) => {
+                self.flush();
+                if !self.in_item() {
+                    self.start_block();
                 }
-                if found_close && chars.peek() == Some(&'(') {
-                    chars.next(); // consume (
-                    let mut url = String::new();
-                    while let Some(&c) = chars.peek() {
-                        if c == ')' {
-                            chars.next();
-                            break;
-                        }
-                        url.push(c);
-                        chars.next();
+                self.lists.push(List { next_number: start });
+            }
+            Tag::Item => {
+                self.flush();
+                let depth = self.lists.len().saturating_sub(1);
+                let marker = match self.lists.last_mut().and_then(|l| l.next_number.as_mut()) {
+                    Some(n) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
                     }
-                    if !current.is_empty() {
-                        spans.push(Span::styled(
-                            std::mem::take(&mut current),
-                            Style::default().fg(text_color),
-                        ));
+                    None => BULLETS[depth % BULLETS.len()].to_string(),
+                };
+                self.containers.push(Container::Item { marker, first_line: true });
+            }
+            Tag::Table(alignments) => {
+                self.flush();
+                self.start_block();
+                self.table = Some(Table { alignments, rows: Vec::new(), row: Vec::new(), header_rows: 0 });
+            }
+            Tag::TableCell => self.inline.clear(),
+            Tag::Emphasis => self.push_style(|s| s.italic()),
+            Tag::Strong => self.push_style(|s| s.bold()),
+            Tag::Strikethrough => self.push_style(|s| s.crossed_out()),
+            Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => {
+                let accent = self.color(self.theme.accent);
+                self.push_style(|s| s.fg(accent).underlined());
+                self.links.push(link_target(&dest_url));
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.flush(),
+            TagEnd::Heading(_) => {
+                self.flush();
+                self.styles.pop();
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush();
+                self.styles.pop();
+                self.containers.pop();
+            }
+            TagEnd::CodeBlock => {
+                if let Some((mut highlighter, code)) = self.code.take() {
+                    for line in code.trim_end_matches('\n').split('\n') {
+                        let spans = highlighter.line(&line.replace('\t', "    "), self.theme);
+                        self.push_line(spans);
                     }
-                    spans.push(Span::styled(
-                        link_text,
-                        Style::default().fg(accent).underlined(),
-                    ));
-                } else {
-                    // Not a link, treat as regular text
-                    current.push('[');
-                    current.push_str(&link_text);
-                    if found_close {
-                        current.push(']');
+                }
+                // End of code block — add a thin separator
+                let width = self.width.saturating_sub(self.prefix_width());
+                let separator = Span::styled("─".repeat(width), self.dim_style());
+                self.push_line(vec![separator]);
+            }
+            TagEnd::List(_) => {
+                self.flush();
+                self.lists.pop();
+            }
+            TagEnd::Item => {
+                self.flush();
+                self.containers.pop();
+            }
+            TagEnd::TableCell => {
+                let cell = std::mem::take(&mut self.inline);
+                if let Some(table) = &mut self.table {
+                    table.row.push(cell);
+                }
+            }
+            TagEnd::TableHead | TagEnd::TableRow => {
+                if let Some(table) = &mut self.table {
+                    let row = std::mem::take(&mut table.row);
+                    table.rows.push(row);
+                    if tag == TagEnd::TableHead {
+                        table.header_rows = table.rows.len();
                     }
                 }
             }
-            _ => {
-                current.push(ch);
-                chars.next();
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    self.render_table(table);
+                }
+            }
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough => {
+                self.styles.pop();
             }
+            TagEnd::Link | TagEnd::Image => {
+                self.styles.pop();
+                self.links.pop();
+            }
+            _ => {}
         }
     }
 
-    if !current.is_empty() {
-        spans.push(Span::styled(current, Style::default().fg(text_color)));
+    fn in_item(&self) -> bool {
+        self.containers.iter().any(|c| matches!(c, Container::Item { .. }))
     }
 
-    if spans.is_empty() {
-        spans.push(Span::raw(""));
+    /// Separate a new block from the previous one with a blank line, except
+    /// at the start of a list item.
+    fn start_block(&mut self) {
+        self.flush();
+        let item_start = matches!(self.containers.last(), Some(Container::Item { first_line: true, .. }));
+        let after_blank = self.lines.last().is_none_or(|l| l.spans.iter().all(|s| s.content.trim().is_empty() || s.content.trim() == "│"));
+        if !item_start && !after_blank {
+            self.push_line(Vec::new());
+        }
     }
 
-    spans
-}
+    fn prefix_width(&self) -> usize {
+        self.containers
+            .iter()
+            .map(|c| match c {
+                Container::Quote => 2,
+                Container::Item { marker, .. } => marker.width(),
+            })
+            .sum()
+    }
 
-/// Wrap text at the given width, breaking on whitespace.
-fn wrap_text(text: &str, max_width: usize) -> Vec<String> {
-    if max_width == 0 {
-        return vec![text.to_string()];
-    }
-
-    let mut lines = Vec::new();
-    let mut current_line = String::new();
-
-    for word in text.split_whitespace() {
-        let word_chars = word.chars().count();
-        let line_chars = current_line.chars().count();
-        if current_line.is_empty() {
-            if word_chars > max_width {
-                // Break long word at char boundaries
-                let mut chars = word.chars();
-                let mut chunk = String::new();
-                while let Some(c) = chars.next() {
-                    chunk.push(c);
-                    if chunk.chars().count() >= max_width {
-                        lines.push(chunk);
-                        chunk = String::new();
+    /// Indent, quote bars and list markers for the next line.
+    fn prefix(&mut self) -> Vec<Span<'static>> {
+        let dim = self.dim_style();
+        let mut spans = vec![Span::raw("  ")];
+        let innermost_item = self.containers.iter().rposition(|c| matches!(c, Container::Item { .. }));
+        for (i, container) in self.containers.iter_mut().enumerate() {
+            match container {
+                Container::Quote => spans.push(Span::styled("│ ", dim)),
+                Container::Item { marker, first_line } => {
+                    if *first_line && Some(i) == innermost_item {
+                        spans.push(Span::styled(marker.clone(), dim));
+                    } else {
+                        spans.push(Span::raw(" ".repeat(marker.width())));
                     }
+                    *first_line = false;
                 }
-                current_line = chunk;
-            } else {
-                current_line = word.to_string();
             }
-        } else if line_chars + 1 + word_chars > max_width {
-            lines.push(current_line);
-            current_line = word.to_string();
-        } else {
-            current_line.push(' ');
-            current_line.push_str(word);
         }
+        spans
     }
 
-    if !current_line.is_empty() {
-        lines.push(current_line);
+    fn push_line(&mut self, content: Vec<Span<'static>>) {
+        let mut spans = self.prefix();
+        spans.extend(content);
+        self.lines.push(TuiLine::from(spans));
     }
 
-    if lines.is_empty() {
-        lines.push(String::new());
+    /// Wrap and emit the pending inline content.
+    fn flush(&mut self) {
+        if self.inline.is_empty() || self.table.is_some() {
+            return;
+        }
+        let pieces = std::mem::take(&mut self.inline);
+        let width = self.width.saturating_sub(self.prefix_width());
+        for line in wrap_pieces(&pieces, width) {
+            self.push_line(to_spans(line));
+        }
     }
 
-    lines
+    fn render_table(&mut self, table: Table) {
+        let columns = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        let natural: Vec<usize> = (0..columns)
+            .map(|c| {
+                table
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(c))
+                    .map(|cell| pieces_width(cell))
+                    .max()
+                    .unwrap_or(0)
+                    .max(1)
+            })
+            .collect();
+        // Borders and padding take 3 columns per cell plus one
+        let available = self.width.saturating_sub(self.prefix_width() + 3 * columns + 1);
+        let widths = fit_columns(&natural, available);
+
+        let border = self.dim_style();
+        let rule = |left: &str, mid: &str, right: &str| {
+            let parts: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+            Span::styled(format!("{}{}{}", left, parts.join(mid), right), border)
+        };
+        self.push_line(vec![rule("┌", "┬", "┐")]);
+        for (r, row) in table.rows.iter().enumerate() {
+            let header = r < table.header_rows;
+            let cells: Vec<Vec<Vec<Piece>>> = (0..columns)
+                .map(|c| {
+                    let mut cell = row.get(c).cloned().unwrap_or_default();
+                    if header {
+                        for piece in &mut cell {
+                            piece.style = piece.style.bold();
+                        }
+                    }
+                    wrap_pieces(&cell, widths[c])
+                })
+                .collect();
+            let height = cells.iter().map(Vec::len).max().unwrap_or(1);
+            for l in 0..height {
+                let mut spans = vec![Span::styled("│", border)];
+                for (c, cell) in cells.iter().enumerate() {
+                    let pieces = cell.get(l).cloned().unwrap_or_default();
+                    let pad = widths[c].saturating_sub(pieces_width(&pieces));
+                    let (left, right) = match table.alignments.get(c) {
+                        Some(Alignment::Right) => (pad, 0),
+                        Some(Alignment::Center) => (pad / 2, pad - pad / 2),
+                        _ => (0, pad),
+                    };
+                    spans.push(Span::raw(" ".repeat(left + 1)));
+                    spans.extend(to_spans(pieces));
+                    spans.push(Span::raw(" ".repeat(right + 1)));
+                    spans.push(Span::styled("│", border));
+                }
+                self.push_line(spans);
+            }
+            if header && r + 1 == table.header_rows && r + 1 < table.rows.len() {
+                self.push_line(vec![rule("├", "┼", "┤")]);
+            }
+        }
+        self.push_line(vec![rule("└", "┴", "┘")]);
+    }
+
+    fn finish(mut self) -> Vec<TuiLine<'static>> {
+        self.flush();
+        // A code block still open at the end (e.g. while streaming)
+        if self.code.is_some() {
+            self.end(TagEnd::CodeBlock);
+        }
+        self.lines
+    }
+}
+
+/// Column widths that fit in `available`: columns that fit in an even
+/// share keep their natural width, the rest split what is left.
+fn fit_columns(natural: &[usize], available: usize) -> Vec<usize> {
+    if natural.iter().sum::<usize>() <= available {
+        return natural.to_vec();
+    }
+    let mut widths = vec![0; natural.len()];
+    let mut order: Vec<usize> = (0..natural.len()).collect();
+    order.sort_by_key(|&c| natural[c]);
+    let mut remaining = available;
+    for (i, &c) in order.iter().enumerate() {
+        let share = remaining / (natural.len() - i);
+        widths[c] = natural[c].min(share).max(MIN_COLUMN_WIDTH);
+        remaining = remaining.saturating_sub(widths[c]);
+    }
+    widths
 }
 
-/// Strip an ordered list prefix like "1. ", "2. ", etc. and return the rest.
-fn strip_ordered_list_prefix(text: &str) -> Option<&str> {
-    let mut chars = text.chars();
-    let first = chars.next()?;
-    if !first.is_ascii_digit() {
-        return None;
-    }
-    // Consume remaining digits
-    let rest = chars.as_str();
-    let dot_pos = rest.find(". ")?;
-    // Verify all chars before dot are digits
-    if rest[..dot_pos].chars().all(|c| c.is_ascii_digit()) {
-        Some(&rest[dot_pos + 2..])
-    } else {
-        None
+/// `url` safe to put in an OSC 8 sequence: control characters (C0, DEL and
+/// C1), which could end the sequence early and inject others, are
+/// percent-encoded.
+fn link_target(url: &str) -> String {
+    let mut out = String::with_capacity(url.len());
+    for c in url.chars() {
+        if c.is_control() {
+            let mut buf = [0; 4];
+            for byte in c.encode_utf8(&mut buf).bytes() {
+                out.push_str(&format!("%{:02X}", byte));
+            }
+        } else {
+            out.push(c);
+        }
     }
+    out
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::themes::get_theme;
+
+    fn render(text: &str, width: u16) -> Vec<String> {
+        markdown_to_lines(text, get_theme("tokyo-night"), width, 1)
+            .into_iter()
+            .map(|l| hyperlink::extract(l).0.to_string().trim_end().to_string())
+            .collect()
+    }
+
+    fn piece(text: &str, style: Style) -> Piece {
+        Piece { text: text.to_string(), style, link: None }
+    }
+
+    #[test]
+    fn wrap_keeps_styles_across_lines() {
+        let bold = Style::default().bold();
+        let pieces = [piece("plain ", Style::default()), piece("bold words here", bold)];
+        let lines = wrap_pieces(&pieces, 11);
+        let text: Vec<String> = lines.iter().map(|l| l.iter().map(|p| p.text.as_str()).collect()).collect();
+        assert_eq!(text, ["plain bold", "words here"]);
+        assert!(lines[1].iter().all(|p| p.style == bold));
+
+        let lines = wrap_pieces(&[piece("abcdefgh", Style::default())], 3);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(wrap_pieces(&[], 10), vec![Vec::<Piece>::new()]);
+    }
+
+    #[test]
+    fn paragraphs_headings_and_rules() {
+        let lines = render("# Title\n\nSome **bold** and `code`.\n\n---\n\nEnd", 40);
+        assert_eq!(lines, ["  Title", "", "  Some bold and code.", "", format!("  {}", "─".repeat(36)).as_str(), "", "  End"]);
+    }
 
     #[test]
-    fn wrap_text_short() {
-        let result = wrap_text("hello world", 80);
-        assert_eq!(result, vec!["hello world"]);
+    fn nested_lists_and_quotes() {
+        let lines = render("- one\n  - nested item\n- two\n\n3. three\n4. four\n\n> quoted\n> text", 40);
+        assert_eq!(
+            lines,
+            ["  • one", "    ◦ nested item", "  • two", "", "  3. three", "  4. four", "", "  │ quoted text"]
+        );
+        let lines = render("- a long item that wraps", 18);
+        assert_eq!(lines, ["  • a long item", "    that wraps"]);
     }
 
     #[test]
-    fn wrap_text_long() {
-        let result = wrap_text("hello world foo bar", 12);
-        assert_eq!(result, vec!["hello world", "foo bar"]);
+    fn tables_fit_the_width() {
+        let md = "| Name | Value |\n|:-----|------:|\n| a | 1 |\n| b | 22 |";
+        let lines = render(md, 40);
+        assert_eq!(
+            lines,
+            [
+                "  ┌──────┬───────┐",
+                "  │ Name │ Value │",
+                "  ├──────┼───────┤",
+                "  │ a    │     1 │",
+                "  │ b    │    22 │",
+                "  └──────┴───────┘",
+            ]
+        );
+        let md = "| A | B |\n|---|---|\n| short | a much longer cell text |";
+        let lines = render(md, 24);
+        assert!(lines.iter().all(|l| l.width() <= 22));
+        assert!(lines.len() > 5);
+        assert_eq!(fit_columns(&[5, 30], 20), vec![5, 15]);
     }
 
     #[test]
-    fn wrap_text_empty() {
-        let result = wrap_text("", 80);
-        assert_eq!(result, vec![""]);
+    fn links_carry_hyperlink_markers() {
+        let lines = markdown_to_lines("see [the docs](https://example.com)", get_theme("tokyo-night"), 40, 1);
+        let (plain, links) = hyperlink::extract(lines[0].clone());
+        assert_eq!(plain.to_string(), "  see the docs");
+        assert_eq!(links, vec![(6, 8, "https://example.com".to_string())]);
     }
 
     #[test]
-    fn strip_ordered_list() {
-        assert_eq!(strip_ordered_list_prefix("1. Hello"), Some("Hello"));
-        assert_eq!(strip_ordered_list_prefix("12. Test"), Some("Test"));
-        assert_eq!(strip_ordered_list_prefix("not a list"), None);
-        assert_eq!(strip_ordered_list_prefix("a. nope"), None);
+    fn link_urls_cannot_carry_escape_sequences() {
+        let theme = get_theme("tokyo-night");
+        let url = |md: &str| {
+            let lines = markdown_to_lines(md, theme, 80, 1);
+            let (_, links) = hyperlink::extract(lines[0].clone());
+            links.into_iter().map(|l| l.2).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            url("[x](<https://a.test/\x1b]8;;https://evil.test\x07\u{9b}2J>)"),
+            ["https://a.test/%1B]8;;https://evil.test%07%C2%9B2J"]
+        );
+        assert_eq!(url("[x](https://a.test/&#27;&#x9c;)"), ["https://a.test/%1B%C2%9C"]);
     }
 
     #[test]
-    fn inline_formatting_bold() {
-        let accent = Color::Cyan;
-        let text_color = Color::White;
-        let dim = Color::Gray;
-        let spans = parse_inline_formatting("hello **world**", text_color, accent, dim);
-        assert_eq!(spans.len(), 2);
-        assert!(spans[1].style.add_modifier.contains(Modifier::BOLD));
+    fn code_blocks_are_highlighted() {
+        let lines = markdown_to_lines("```rust\nfn main() {}\n```", get_theme("tokyo-night"), 30, 4);
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].to_string().contains("─ [4] rust "));
+        assert_eq!(lines[1].to_string(), "  fn main() {}");
+        assert!(lines[1].spans.len() > 2);
     }
 
     #[test]
-    fn inline_formatting_code() {
-        let accent = Color::Cyan;
-        let text_color = Color::White;
-        let dim = Color::Gray;
-        let spans = parse_inline_formatting("use `println!`", text_color, accent, dim);
-        assert_eq!(spans.len(), 2);
-        assert_eq!(spans[1].style.fg, Some(accent));
+    fn code_blocks_in_order() {
+        let text = "Run:\n\n```sh\ncargo test\n```\n\nthen\n\n    indented\n";
+        assert_eq!(code_blocks(text), ["cargo test\n", "indented\n"]);
+        assert!(code_blocks("no code").is_empty());
     }
 }
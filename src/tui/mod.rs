@@ -2,13 +2,22 @@ pub mod agent_question;
 pub mod api_key_prompt;
 pub mod app;
 pub mod chat_view;
+pub mod clipboard;
 pub mod command_palette;
 pub mod config_menu;
+pub mod confirm;
+pub mod editor;
+pub mod external_editor;
 pub mod file_picker;
 pub mod header;
+pub mod highlight;
+pub mod hyperlink;
 pub mod input;
 pub mod layout;
 pub mod markdown;
+pub mod pager;
+pub mod session_list;
 pub mod status_bar;
 pub mod todo_panel;
 pub mod tool_view;
+pub mod transcript_search;
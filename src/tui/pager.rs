@@ -0,0 +1,140 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::text::Line as TuiLine;
+use ratatui::widgets::*;
+use std::cell::Cell;
+
+use crate::config::themes::Theme;
+
+/// Columns moved per Left/Right press.
+const HORIZONTAL_STEP: usize = 8;
+
+// ── State ──────────────────────────────────────────────────────────────
+
+/// Full-screen, scrollable view of a long text, such as a tool result.
+#[derive(Debug, Clone)]
+pub struct PagerState {
+    pub title: String,
+    pub lines: Vec<TuiLine<'static>>,
+    /// First line shown.
+    pub scroll: usize,
+    /// First column shown; lines are not wrapped.
+    pub hscroll: usize,
+    /// Lines that fit on screen, recorded by the last render.
+    viewport: Cell<usize>,
+}
+
+impl PagerState {
+    pub fn new(title: impl Into<String>, lines: Vec<TuiLine<'static>>) -> Self {
+        Self {
+            title: title.into(),
+            lines,
+            scroll: 0,
+            hscroll: 0,
+            viewport: Cell::new(20),
+        }
+    }
+
+    fn page(&self) -> usize {
+        self.viewport.get().max(1)
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.lines.len().saturating_sub(self.page())
+    }
+
+    fn scroll_by(&mut self, delta: isize) {
+        self.scroll = self.scroll.saturating_add_signed(delta).min(self.max_scroll());
+    }
+}
+
+// ── Action result ──────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PagerAction {
+    None,
+    Close,
+}
+
+// ── Key handling ───────────────────────────────────────────────────────
+
+pub fn handle_key(state: &mut PagerState, key: KeyEvent) -> PagerAction {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let page = state.page() as isize;
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => return PagerAction::Close,
+        KeyCode::Up | KeyCode::Char('k') => state.scroll_by(-1),
+        KeyCode::Down | KeyCode::Char('j') => state.scroll_by(1),
+        KeyCode::Char('u') if ctrl => state.scroll_by(-page / 2),
+        KeyCode::Char('d') if ctrl => state.scroll_by(page / 2),
+        KeyCode::PageUp | KeyCode::Char('b') => state.scroll_by(-page),
+        KeyCode::PageDown | KeyCode::Char(' ') => state.scroll_by(page),
+        KeyCode::Home | KeyCode::Char('g') => state.scroll = 0,
+        KeyCode::End | KeyCode::Char('G') => state.scroll = state.max_scroll(),
+        KeyCode::Left | KeyCode::Char('h') => state.hscroll = state.hscroll.saturating_sub(HORIZONTAL_STEP),
+        KeyCode::Right | KeyCode::Char('l') => state.hscroll += HORIZONTAL_STEP,
+        _ => {}
+    }
+    PagerAction::None
+}
+
+// ── Rendering ──────────────────────────────────────────────────────────
+
+pub fn render(frame: &mut Frame, area: Rect, state: &PagerState, theme: &Theme) {
+    let accent = Color::Rgb(theme.accent.r, theme.accent.g, theme.accent.b);
+    let bg = Color::Rgb(theme.bg.r, theme.bg.g, theme.bg.b);
+    let dim = Color::Rgb(theme.dim_text.r, theme.dim_text.g, theme.dim_text.b);
+
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" {} ", state.title))
+        .title_style(Style::default().fg(accent).bold())
+        .title_bottom(TuiLine::from(vec![Span::styled(
+            " ↑↓/jk scroll · PgUp/PgDn page · g/G top/end · ←→ pan · q close ",
+            Style::default().fg(dim),
+        )]))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(dim))
+        .style(Style::default().bg(bg));
+    let inner = block.inner(area);
+    state.viewport.set(inner.height as usize);
+
+    let scroll = state.scroll.min(state.max_scroll());
+    let last = (scroll + inner.height as usize).min(state.lines.len());
+    let position = format!(" {}-{}/{} ", (scroll + 1).min(last), last, state.lines.len());
+    let block = block.title(TuiLine::from(Span::styled(position, Style::default().fg(dim))).right_aligned());
+
+    let visible: Vec<TuiLine> = state.lines[scroll..last].to_vec();
+    let paragraph = Paragraph::new(visible)
+        .block(block)
+        .scroll((0, state.hscroll.min(u16::MAX as usize) as u16));
+    frame.render_widget(paragraph, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn scrolling_stays_in_range() {
+        let lines = (0..50).map(|i| TuiLine::from(format!("line {}", i))).collect();
+        let mut state = PagerState::new("out", lines);
+        state.viewport.set(10);
+        handle_key(&mut state, key(KeyCode::Up));
+        assert_eq!(state.scroll, 0);
+        handle_key(&mut state, key(KeyCode::PageDown));
+        assert_eq!(state.scroll, 10);
+        handle_key(&mut state, key(KeyCode::Char('G')));
+        assert_eq!(state.scroll, 40);
+        handle_key(&mut state, key(KeyCode::Down));
+        assert_eq!(state.scroll, 40);
+        handle_key(&mut state, key(KeyCode::Char('k')));
+        assert_eq!(state.scroll, 39);
+        assert_eq!(handle_key(&mut state, key(KeyCode::Char('q'))), PagerAction::Close);
+    }
+}
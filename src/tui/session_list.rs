@@ -0,0 +1,572 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{HashMap, HashSet};
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+use crate::config::themes::Theme;
+use crate::core::session::{SearchHit, Session, MATCH_END, MATCH_START};
+use crate::core::usage::format_cost;
+
+// ── State ──────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+pub struct SessionListState {
+    pub query: String,
+    pub selected: usize,
+    /// Sessions in tree order: each fork follows its parent.
+    pub sessions: Vec<Session>,
+    /// Fork depth of each entry in `sessions` (0 for top-level sessions).
+    depths: Vec<usize>,
+    pub hits: Vec<SearchHit>,
+    /// Show sessions from every project instead of only the current one.
+    pub all_projects: bool,
+    /// Name of the current project, for the title.
+    pub project_name: String,
+    /// New name being typed for the selected session.
+    pub rename: Option<String>,
+    /// Cost in USD of sessions that used a priced model.
+    pub costs: HashMap<String, f64>,
+}
+
+impl SessionListState {
+    /// Build the list from sessions sorted most recent first.
+    pub fn new(sessions: Vec<Session>) -> Self {
+        let (sessions, depths) = tree_order(sessions).into_iter().unzip();
+        Self {
+            query: String::new(),
+            selected: 0,
+            sessions,
+            depths,
+            hits: Vec::new(),
+            all_projects: false,
+            project_name: String::new(),
+            rename: None,
+            costs: HashMap::new(),
+        }
+    }
+
+    /// Whether the list shows search results instead of sessions.
+    pub fn is_searching(&self) -> bool {
+        !self.query.trim().is_empty()
+    }
+
+    fn len(&self) -> usize {
+        if self.is_searching() {
+            self.hits.len()
+        } else {
+            self.sessions.len()
+        }
+    }
+
+    /// Replace the listed sessions, e.g. after switching project scope.
+    /// The selection follows the selected session if it is still listed.
+    pub fn set_sessions(&mut self, sessions: Vec<Session>) {
+        let selected_id = self.sessions.get(self.selected).map(|s| s.id.clone());
+        let (sessions, depths): (Vec<Session>, Vec<usize>) = tree_order(sessions).into_iter().unzip();
+        self.selected = selected_id
+            .and_then(|id| sessions.iter().position(|s| s.id == id))
+            .unwrap_or(0);
+        self.sessions = sessions;
+        self.depths = depths;
+    }
+
+    /// Replace the search results after the query changed.
+    pub fn set_hits(&mut self, hits: Vec<SearchHit>) {
+        self.hits = hits;
+        self.selected = 0;
+    }
+}
+
+/// Order sessions so forks are listed under their parent, keeping the input
+/// order among siblings. Forks whose parent is gone become top-level.
+fn tree_order(sessions: Vec<Session>) -> Vec<(Session, usize)> {
+    let ids: HashSet<String> = sessions.iter().map(|s| s.id.clone()).collect();
+    let mut children: HashMap<String, Vec<Session>> = HashMap::new();
+    let mut roots = Vec::new();
+    for session in sessions {
+        match session.parent_id.clone().filter(|p| ids.contains(p) && *p != session.id) {
+            Some(parent) => children.entry(parent).or_default().push(session),
+            None => roots.push(session),
+        }
+    }
+
+    fn visit(
+        session: Session,
+        depth: usize,
+        children: &mut HashMap<String, Vec<Session>>,
+        out: &mut Vec<(Session, usize)>,
+    ) {
+        let kids = children.remove(&session.id).unwrap_or_default();
+        out.push((session, depth));
+        for kid in kids {
+            visit(kid, depth + 1, children, out);
+        }
+    }
+
+    let mut out = Vec::new();
+    for root in roots {
+        visit(root, 0, &mut children, &mut out);
+    }
+    // Parent cycles never reach a root; list them rather than dropping them
+    for (_, rest) in children.drain() {
+        out.extend(rest.into_iter().map(|s| (s, 0)));
+    }
+    out
+}
+
+// ── Action result ──────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionListAction {
+    None,
+    Close,
+    Open(String),
+    OpenAt { session_id: String, message_id: i64 },
+    QueryChanged,
+    /// Switch between this project's sessions and all projects.
+    ToggleScope,
+    Rename { session_id: String, name: String },
+    TogglePin(String),
+}
+
+// ── Key handling ───────────────────────────────────────────────────────
+
+pub fn handle_key(state: &mut SessionListState, key: KeyEvent) -> SessionListAction {
+    if state.rename.is_some() {
+        return handle_rename_key(state, key);
+    }
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => {
+            if state.query.is_empty() {
+                SessionListAction::Close
+            } else {
+                state.query.clear();
+                SessionListAction::QueryChanged
+            }
+        }
+        KeyCode::Up => {
+            if state.selected > 0 {
+                state.selected -= 1;
+            }
+            SessionListAction::None
+        }
+        KeyCode::Down => {
+            if state.selected < state.len().saturating_sub(1) {
+                state.selected += 1;
+            }
+            SessionListAction::None
+        }
+        KeyCode::Tab => {
+            state.all_projects = !state.all_projects;
+            SessionListAction::ToggleScope
+        }
+        KeyCode::Enter => {
+            if state.is_searching() {
+                match state.hits.get(state.selected) {
+                    Some(hit) => SessionListAction::OpenAt {
+                        session_id: hit.session_id.clone(),
+                        message_id: hit.message_id,
+                    },
+                    None => SessionListAction::None,
+                }
+            } else {
+                match state.sessions.get(state.selected) {
+                    Some(session) => SessionListAction::Open(session.id.clone()),
+                    None => SessionListAction::Close,
+                }
+            }
+        }
+        KeyCode::Backspace => {
+            if state.query.pop().is_some() {
+                SessionListAction::QueryChanged
+            } else {
+                SessionListAction::None
+            }
+        }
+        KeyCode::Char('r') if ctrl && !state.is_searching() => {
+            if let Some(session) = state.sessions.get(state.selected) {
+                state.rename = Some(session.name.clone());
+            }
+            SessionListAction::None
+        }
+        KeyCode::Char('p') if ctrl && !state.is_searching() => {
+            match state.sessions.get(state.selected) {
+                Some(session) => SessionListAction::TogglePin(session.id.clone()),
+                None => SessionListAction::None,
+            }
+        }
+        KeyCode::Char(c) if !ctrl => {
+            state.query.push(c);
+            SessionListAction::QueryChanged
+        }
+        _ => SessionListAction::None,
+    }
+}
+
+/// Keys while editing the selected session's name.
+fn handle_rename_key(state: &mut SessionListState, key: KeyEvent) -> SessionListAction {
+    let Some(name) = state.rename.as_mut() else {
+        return SessionListAction::None;
+    };
+    match key.code {
+        KeyCode::Esc => {
+            state.rename = None;
+            SessionListAction::None
+        }
+        KeyCode::Enter => {
+            let name = name.trim().to_string();
+            state.rename = None;
+            match state.sessions.get(state.selected) {
+                Some(session) if !name.is_empty() && name != session.name => SessionListAction::Rename {
+                    session_id: session.id.clone(),
+                    name,
+                },
+                _ => SessionListAction::None,
+            }
+        }
+        KeyCode::Backspace => {
+            name.pop();
+            SessionListAction::None
+        }
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            name.push(c);
+            SessionListAction::None
+        }
+        _ => SessionListAction::None,
+    }
+}
+
+// ── Rendering ──────────────────────────────────────────────────────────
+
+pub fn render(frame: &mut Frame, area: Rect, state: &SessionListState, theme: &Theme) {
+    let searching = state.is_searching();
+    let rows_per_item = if searching { 2 } else { 1 };
+    let total = state.len();
+
+    let max_visible = ((area.height as usize).saturating_sub(8) / rows_per_item).clamp(3, 12);
+    let body_rows = total.clamp(1, max_visible) * rows_per_item;
+    let list_height = (body_rows as u16 + 6).min(area.height.saturating_sub(4));
+    let list_width = 72u16.min(area.width.saturating_sub(4));
+    let x = (area.width.saturating_sub(list_width)) / 2;
+    let y = (area.height.saturating_sub(list_height)) / 2;
+    let overlay_area = Rect::new(x, y, list_width, list_height);
+
+    frame.render_widget(Clear, overlay_area);
+
+    let accent = Color::Rgb(theme.accent.r, theme.accent.g, theme.accent.b);
+    let bg = Color::Rgb(theme.bg.r, theme.bg.g, theme.bg.b);
+    let surface = Color::Rgb(theme.surface.r, theme.surface.g, theme.surface.b);
+    let text_color = Color::Rgb(theme.text.r, theme.text.g, theme.text.b);
+    let dim = Color::Rgb(theme.dim_text.r, theme.dim_text.g, theme.dim_text.b);
+    let warning = Color::Rgb(theme.warning.r, theme.warning.g, theme.warning.b);
+
+    // Visible window that keeps the selection on screen
+    let start = state.selected.saturating_sub(max_visible.saturating_sub(1));
+    let end = (start + max_visible).min(total);
+
+    let mut lines: Vec<Line> = Vec::new();
+
+    // Search input line, or the new name while renaming
+    if let Some(name) = &state.rename {
+        lines.push(Line::from(vec![
+            Span::styled(" Rename: ", Style::default().fg(warning).bold()),
+            Span::styled(format!("{}▏", name), Style::default().fg(text_color)),
+        ]));
+    } else {
+        let (query_display, query_style) = if state.query.is_empty() {
+            ("Type to search all sessions...".to_string(), Style::default().fg(dim))
+        } else {
+            (format!("{}▏", state.query), Style::default().fg(text_color))
+        };
+        lines.push(Line::from(vec![
+            Span::styled(" / ", Style::default().fg(accent).bold()),
+            Span::styled(query_display, query_style),
+        ]));
+    }
+    lines.push(Line::from(""));
+
+    if start > 0 {
+        lines.push(Line::from(Span::styled(
+            format!("  ↑ {} more", start),
+            Style::default().fg(dim).italic(),
+        )));
+    }
+
+    for i in start..end {
+        let is_selected = i == state.selected;
+        let style = if is_selected {
+            Style::default().fg(bg).bg(accent).bold()
+        } else {
+            Style::default().fg(text_color)
+        };
+        let secondary = if is_selected { style } else { Style::default().fg(dim) };
+        let indicator = if is_selected { "▸ " } else { "  " };
+
+        if searching {
+            let hit = &state.hits[i];
+            lines.push(Line::from(vec![
+                Span::styled(indicator, style),
+                Span::styled(hit.session_name.clone(), style),
+                Span::styled(
+                    format!("  {} · {}", hit.created_at.get(..16).unwrap_or(&hit.created_at), hit.role),
+                    secondary,
+                ),
+            ]));
+            let mut snippet = vec![Span::raw("    ")];
+            snippet.extend(snippet_spans(
+                &hit.snippet,
+                Style::default().fg(dim),
+                Style::default().fg(warning).bold(),
+            ));
+            lines.push(Line::from(snippet));
+        } else {
+            let session = &state.sessions[i];
+            let depth = state.depths.get(i).copied().unwrap_or(0);
+            let branch = if depth == 0 {
+                String::new()
+            } else {
+                format!("{}└ ", "  ".repeat(depth - 1))
+            };
+            let pin = if session.pinned { "★ " } else { "" };
+            let name_width = 30usize.saturating_sub(branch.chars().count() + pin.chars().count());
+            // Which project in the global view; which branch within a project
+            let location = if state.all_projects {
+                session.project_name()
+            } else {
+                session.git_branch.clone()
+            };
+            let location = location.map(|l| format!(" · {}", l)).unwrap_or_default();
+            let cost = state
+                .costs
+                .get(&session.id)
+                .map(|c| format!(" · {}", format_cost(*c)))
+                .unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::styled(indicator, style),
+                Span::styled(branch, secondary),
+                Span::styled(pin, if is_selected { style } else { Style::default().fg(warning) }),
+                Span::styled(format!("{:<width$}", session.name, width = name_width), style),
+                Span::styled(format!(" {}{}{}", session.model, location, cost), secondary),
+            ]));
+        }
+    }
+
+    if end < total {
+        lines.push(Line::from(Span::styled(
+            format!("  ↓ {} more", total - end),
+            Style::default().fg(dim).italic(),
+        )));
+    }
+
+    if total == 0 {
+        let empty = if searching {
+            "  No matching messages"
+        } else if state.all_projects {
+            "  No sessions yet"
+        } else {
+            "  No sessions in this project (Tab: all projects)"
+        };
+        lines.push(Line::from(Span::styled(empty, Style::default().fg(dim).italic())));
+    }
+
+    let title = if searching {
+        format!(" Search ({} matches) ", total)
+    } else if state.all_projects {
+        format!(" Sessions · all projects ({}) · Tab: this project ", total)
+    } else {
+        format!(" Sessions · {} ({}) · Tab: all projects ", state.project_name, total)
+    };
+    let hint = if state.rename.is_some() {
+        " Enter: save · Esc: cancel "
+    } else if searching {
+        " Enter: open · Esc: clear "
+    } else {
+        " Ctrl+R: rename · Ctrl+P: pin "
+    };
+    let block = Block::default()
+        .title(title)
+        .title_bottom(Line::from(Span::styled(hint, Style::default().fg(dim))).right_aligned())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(accent))
+        .style(Style::default().bg(surface));
+
+    let paragraph = Paragraph::new(lines).block(block);
+    frame.render_widget(paragraph, overlay_area);
+}
+
+/// Split a search snippet into spans, highlighting the matched terms.
+fn snippet_spans(snippet: &str, normal: Style, highlight: Style) -> Vec<Span<'static>> {
+    let flat: String = snippet
+        .chars()
+        .map(|c| if c == '\n' || c == '\r' { ' ' } else { c })
+        .collect();
+    let mut spans = Vec::new();
+    let mut rest = flat.as_str();
+    while let Some(open) = rest.find(MATCH_START) {
+        if open > 0 {
+            spans.push(Span::styled(rest[..open].to_string(), normal));
+        }
+        rest = &rest[open + MATCH_START.len_utf8()..];
+        let close = rest.find(MATCH_END).unwrap_or(rest.len());
+        spans.push(Span::styled(rest[..close].to_string(), highlight));
+        rest = rest.get(close + MATCH_END.len_utf8()..).unwrap_or("");
+    }
+    if !rest.is_empty() {
+        spans.push(Span::styled(rest.to_string(), normal));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str) -> Session {
+        Session {
+            id: id.to_string(),
+            name: format!("Session {}", id),
+            model: "MiniMax-M2.5".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn fork(id: &str, parent: &str) -> Session {
+        Session {
+            parent_id: Some(parent.to_string()),
+            ..session(id)
+        }
+    }
+
+    #[test]
+    fn forks_follow_their_parent() {
+        // Most recent first, as returned by the store
+        let state = SessionListState::new(vec![
+            fork("c", "a"),
+            session("b"),
+            fork("d", "c"),
+            session("a"),
+            fork("e", "gone"),
+        ]);
+        let ids: Vec<&str> = state.sessions.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "a", "c", "d", "e"]);
+        assert_eq!(state.depths, vec![0, 0, 1, 2, 0]);
+    }
+
+    fn hit(session_id: &str, message_id: i64) -> SearchHit {
+        SearchHit {
+            message_id,
+            session_id: session_id.to_string(),
+            session_name: "Session".to_string(),
+            role: "user".to_string(),
+            snippet: format!("the {}migration{} failed", MATCH_START, MATCH_END),
+            created_at: "2026-01-01 10:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn enter_opens_selected_session() {
+        let mut state = SessionListState::new(vec![session("a"), session("b")]);
+        handle_key(&mut state, KeyEvent::from(KeyCode::Down));
+        let action = handle_key(&mut state, KeyEvent::from(KeyCode::Enter));
+        assert_eq!(action, SessionListAction::Open("b".to_string()));
+    }
+
+    #[test]
+    fn typing_updates_query() {
+        let mut state = SessionListState::new(vec![session("a")]);
+        let action = handle_key(&mut state, KeyEvent::from(KeyCode::Char('m')));
+        assert_eq!(action, SessionListAction::QueryChanged);
+        assert_eq!(state.query, "m");
+        assert!(state.is_searching());
+
+        let action = handle_key(&mut state, KeyEvent::from(KeyCode::Backspace));
+        assert_eq!(action, SessionListAction::QueryChanged);
+        assert!(!state.is_searching());
+    }
+
+    #[test]
+    fn enter_on_hit_opens_at_message() {
+        let mut state = SessionListState::new(vec![session("a")]);
+        state.query = "migration".to_string();
+        state.set_hits(vec![hit("a", 7), hit("a", 9)]);
+        handle_key(&mut state, KeyEvent::from(KeyCode::Down));
+        let action = handle_key(&mut state, KeyEvent::from(KeyCode::Enter));
+        assert_eq!(
+            action,
+            SessionListAction::OpenAt { session_id: "a".to_string(), message_id: 9 }
+        );
+    }
+
+    #[test]
+    fn tab_toggles_project_scope() {
+        let mut state = SessionListState::new(Vec::new());
+        assert!(!state.all_projects);
+        assert_eq!(handle_key(&mut state, KeyEvent::from(KeyCode::Tab)), SessionListAction::ToggleScope);
+        assert!(state.all_projects);
+    }
+
+    #[test]
+    fn rename_edits_selected_session() {
+        let mut state = SessionListState::new(vec![session("a"), session("b")]);
+        handle_key(&mut state, KeyEvent::from(KeyCode::Down));
+        handle_key(&mut state, KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(state.rename.as_deref(), Some("Session b"));
+
+        // Typing edits the name, not the search query
+        for _ in 0.."Session b".len() {
+            handle_key(&mut state, KeyEvent::from(KeyCode::Backspace));
+        }
+        for c in "Parser fix".chars() {
+            handle_key(&mut state, KeyEvent::from(KeyCode::Char(c)));
+        }
+        assert!(state.query.is_empty());
+        assert_eq!(
+            handle_key(&mut state, KeyEvent::from(KeyCode::Enter)),
+            SessionListAction::Rename { session_id: "b".to_string(), name: "Parser fix".to_string() }
+        );
+        assert!(state.rename.is_none());
+
+        // Esc cancels without closing the list
+        handle_key(&mut state, KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(handle_key(&mut state, KeyEvent::from(KeyCode::Esc)), SessionListAction::None);
+        assert!(state.rename.is_none());
+    }
+
+    #[test]
+    fn pin_toggles_selected_session_and_selection_follows() {
+        let mut state = SessionListState::new(vec![session("a"), session("b")]);
+        handle_key(&mut state, KeyEvent::from(KeyCode::Down));
+        assert_eq!(
+            handle_key(&mut state, KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL)),
+            SessionListAction::TogglePin("b".to_string())
+        );
+
+        // The store lists the pinned session first after reloading
+        let pinned = Session { pinned: true, ..session("b") };
+        state.set_sessions(vec![pinned, session("a")]);
+        assert_eq!(state.selected, 0);
+        assert_eq!(state.sessions[0].id, "b");
+    }
+
+    #[test]
+    fn escape_clears_query_before_closing() {
+        let mut state = SessionListState::new(Vec::new());
+        state.query = "x".to_string();
+        assert_eq!(handle_key(&mut state, KeyEvent::from(KeyCode::Esc)), SessionListAction::QueryChanged);
+        assert_eq!(handle_key(&mut state, KeyEvent::from(KeyCode::Esc)), SessionListAction::Close);
+    }
+
+    #[test]
+    fn snippet_highlights_matches() {
+        let spans = snippet_spans(
+            &format!("a {}b{} c", MATCH_START, MATCH_END),
+            Style::default(),
+            Style::default().bold(),
+        );
+        let texts: Vec<&str> = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(texts, vec!["a ", "b", " c"]);
+        assert!(spans[1].style.add_modifier.contains(Modifier::BOLD));
+    }
+}
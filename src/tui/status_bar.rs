@@ -2,8 +2,14 @@ use ratatui::prelude::*;
 use ratatui::widgets::*;
 
 use crate::config::themes::Theme;
+use crate::core::usage::format_tokens;
 use crate::tui::app::App;
 
+/// Cells in the context usage bar.
+const GAUGE_WIDTH: usize = 10;
+/// Share of the context window above which the gauge turns red.
+const GAUGE_CRITICAL: f64 = 0.9;
+
 pub fn render(frame: &mut Frame, area: Rect, app: &App, theme: &Theme) {
     let dim = Style::default().fg(Color::Rgb(
         theme.dim_text.r,
@@ -44,20 +50,35 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App, theme: &Theme) {
         "0".to_string()
     };
     let version = env!("CARGO_PKG_VERSION");
-    let right_text = format!(
-        "v{} | Tokens: {} | /: cmds | Tab: mode ",
-        version, token_str
-    );
+    let version_text = format!("v{} | ", version);
+    let window = app.config.model_info().context_window;
+    let ratio = context_ratio(app.context_tokens, window);
+    let gauge_text = format!("Context {}", context_gauge(ratio));
+    let gauge_color = if ratio >= GAUGE_CRITICAL {
+        theme.error
+    } else if ratio >= app.config.context.compact_at {
+        theme.warning
+    } else {
+        theme.dim_text
+    };
+    let right_text = format!(" | Tokens: {} | /: cmds | Tab: mode ", token_str);
 
     // Calculate spacing
     let total_width = area.width as usize;
-    let left_len = left_text.len();
-    let right_len = right_text.len();
+    let left_len = left_text.chars().count();
+    let right_len = version_text.chars().count()
+        + gauge_text.chars().count()
+        + right_text.chars().count();
     let padding = total_width.saturating_sub(left_len + right_len);
 
     let line = Line::from(vec![
         Span::styled(left_text, dim),
         Span::styled(" ".repeat(padding), dim),
+        Span::styled(version_text, dim),
+        Span::styled(
+            gauge_text,
+            Style::default().fg(Color::Rgb(gauge_color.r, gauge_color.g, gauge_color.b)),
+        ),
         Span::styled(right_text, dim),
     ]);
 
@@ -65,14 +86,23 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App, theme: &Theme) {
     frame.render_widget(bar, area);
 }
 
-fn format_tokens(tokens: u64) -> String {
-    if tokens >= 1_000_000 {
-        format!("{:.1}M", tokens as f64 / 1_000_000.0)
-    } else if tokens >= 1_000 {
-        format!("{:.1}k", tokens as f64 / 1_000.0)
-    } else {
-        format!("{}", tokens)
+/// Share of the context window in use, capped at 1.
+fn context_ratio(tokens: u64, window: u64) -> f64 {
+    if window == 0 {
+        return 0.0;
     }
+    (tokens as f64 / window as f64).min(1.0)
+}
+
+/// A bar such as `[█████░░░░░] 52%`.
+fn context_gauge(ratio: f64) -> String {
+    let filled = ((ratio * GAUGE_WIDTH as f64).round() as usize).min(GAUGE_WIDTH);
+    format!(
+        "[{}{}] {}%",
+        "█".repeat(filled),
+        "░".repeat(GAUGE_WIDTH - filled),
+        (ratio * 100.0).round() as u64
+    )
 }
 
 fn format_reset(minutes: u64) -> String {
@@ -89,23 +119,6 @@ fn format_reset(minutes: u64) -> String {
 mod tests {
     use super::*;
 
-    #[test]
-    fn format_tokens_small() {
-        assert_eq!(format_tokens(0), "0");
-        assert_eq!(format_tokens(500), "500");
-    }
-
-    #[test]
-    fn format_tokens_thousands() {
-        assert_eq!(format_tokens(1200), "1.2k");
-        assert_eq!(format_tokens(15000), "15.0k");
-    }
-
-    #[test]
-    fn format_tokens_millions() {
-        assert_eq!(format_tokens(1_500_000), "1.5M");
-    }
-
     #[test]
     fn format_reset_minutes_only() {
         assert_eq!(format_reset(9), "9m");
@@ -115,4 +128,12 @@ mod tests {
     fn format_reset_hours_and_minutes() {
         assert_eq!(format_reset(249), "4h 9m");
     }
+
+    #[test]
+    fn context_gauge_fills_by_ratio() {
+        assert_eq!(context_gauge(context_ratio(0, 200_000)), "[░░░░░░░░░░] 0%");
+        assert_eq!(context_gauge(context_ratio(104_000, 200_000)), "[█████░░░░░] 52%");
+        assert_eq!(context_gauge(context_ratio(300_000, 200_000)), "[██████████] 100%");
+        assert_eq!(context_ratio(10, 0), 0.0);
+    }
 }
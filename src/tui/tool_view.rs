@@ -3,7 +3,14 @@ use ratatui::text::{Line as TuiLine, Span};
 
 use crate::config::themes::Theme;
 use crate::core::api::AccumulatedToolCall;
+use crate::tools::ToolResultMeta;
 use crate::tui::app::{DisplayMessage, ToolStatus};
+use crate::tui::highlight::{Highlighter, Language};
+
+/// Most output lines shown under a tool result until it is expanded.
+const MAX_PREVIEW_LINES: usize = 8;
+/// Most diff lines shown under an edit/write result.
+const MAX_DIFF_LINES: usize = 8;
 
 /// Render a tool call reference line (shown in assistant messages).
 /// Format: → tool_name(args_preview)
@@ -24,11 +31,29 @@ pub fn render_tool_call_line<'a>(tc: &AccumulatedToolCall, theme: &Theme) -> Tui
     ])
 }
 
+/// How much of a tool result the transcript shows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToolDetail {
+    /// The header line only.
+    Collapsed,
+    /// The first lines of output and of the diff.
+    Preview,
+    /// The call's arguments and all output.
+    Full,
+}
+
 /// Render tool result message lines.
 /// Format:
 ///   ⚡ tool_name ✓/✗/...
 ///     preview lines
-pub fn render_tool_result_lines<'a>(msg: &DisplayMessage, theme: &Theme, width: u16) -> Vec<TuiLine<'a>> {
+pub fn render_tool_result_lines<'a>(
+    msg: &DisplayMessage,
+    args: Option<&str>,
+    theme: &Theme,
+    width: u16,
+    detail: ToolDetail,
+    is_selected: bool,
+) -> Vec<TuiLine<'a>> {
     let warning = Color::Rgb(theme.warning.r, theme.warning.g, theme.warning.b);
     let success = Color::Rgb(theme.success.r, theme.success.g, theme.success.b);
     let error = Color::Rgb(theme.error.r, theme.error.g, theme.error.b);
@@ -45,21 +70,73 @@ pub fn render_tool_result_lines<'a>(msg: &DisplayMessage, theme: &Theme, width:
         None => ("", dim),
     };
 
-    lines.push(TuiLine::from(vec![
-        Span::raw("  "),
-        Span::styled("⚡ ", Style::default().fg(warning)),
-        Span::styled(tool_name.to_string(), Style::default().fg(warning).bold()),
-        Span::raw(" "),
-        Span::styled(status_icon.to_string(), Style::default().fg(status_color)),
-    ]));
+    let mut header = if is_selected {
+        // Highlighted while choosing tool output to expand
+        let bg = Color::Rgb(theme.bg.r, theme.bg.g, theme.bg.b);
+        vec![Span::styled(format!("  ▸ ⚡ {} ", tool_name), Style::default().fg(bg).bg(warning).bold())]
+    } else {
+        vec![
+            Span::raw("  "),
+            Span::styled("⚡ ", Style::default().fg(warning)),
+            Span::styled(tool_name.to_string(), Style::default().fg(warning).bold()),
+        ]
+    };
+    header.push(Span::raw(" "));
+    header.push(Span::styled(status_icon.to_string(), Style::default().fg(status_color)));
+    let total_lines = msg.content.lines().count();
+    if detail == ToolDetail::Collapsed && total_lines > 0 {
+        header.push(Span::styled(
+            format!(" ({} lines)", total_lines),
+            Style::default().fg(dim).italic(),
+        ));
+    }
+    if is_selected {
+        let hint = match detail {
+            ToolDetail::Full => "Enter: collapse",
+            _ => "Enter: expand",
+        };
+        header.push(Span::styled(
+            format!("  {} · o: pager · y: copy · c: collapse all · ↑↓ choose · Esc", hint),
+            Style::default().fg(dim),
+        ));
+    }
+    lines.push(TuiLine::from(header));
+
+    let limit = match detail {
+        ToolDetail::Collapsed => return lines,
+        ToolDetail::Preview => Some(MAX_PREVIEW_LINES),
+        ToolDetail::Full => None,
+    };
+
+    // Arguments of the call, pretty-printed
+    if detail == ToolDetail::Full {
+        if let Some(args) = args.filter(|a| !a.trim().is_empty()) {
+            let content_width = (width.saturating_sub(6)) as usize;
+            for line in pretty_args(args).lines() {
+                lines.push(TuiLine::from(vec![
+                    Span::raw("    "),
+                    Span::styled(truncate_chars(&strip_ansi_and_tabs(line), content_width.saturating_sub(1)), Style::default().fg(warning)),
+                ]));
+            }
+        }
+    }
 
     // Content preview (truncated)
     if !msg.content.is_empty() {
-        let max_preview_lines = 8;
+        let max_preview_lines = limit.unwrap_or(usize::MAX);
         let content_width = (width.saturating_sub(6)) as usize;
         let preview_lines: Vec<&str> = msg.content.lines().take(max_preview_lines).collect();
+        // File previews are highlighted for the file's language
+        let mut highlighter = match &msg.tool_meta {
+            Some(ToolResultMeta::ReadFile { path }) => Some(Highlighter::new(Language::from_path(path))),
+            _ => None,
+        };
 
         for line in &preview_lines {
+            if let Some(highlighter) = &mut highlighter {
+                lines.push(read_file_line(line, highlighter, content_width, theme));
+                continue;
+            }
             // Strip ANSI escape codes and replace tabs with spaces
             let clean = strip_ansi_and_tabs(line);
             let truncated = if clean.chars().count() > content_width {
@@ -73,12 +150,39 @@ pub fn render_tool_result_lines<'a>(msg: &DisplayMessage, theme: &Theme, width:
             ]));
         }
 
-        let total_lines = msg.content.lines().count();
         if total_lines > max_preview_lines {
             lines.push(TuiLine::from(vec![
                 Span::raw("    "),
                 Span::styled(
-                    format!("... ({} more lines)", total_lines - max_preview_lines),
+                    format!("... ({} more lines, Ctrl+T to expand)", total_lines - max_preview_lines),
+                    Style::default().fg(dim).italic(),
+                ),
+            ]));
+        }
+    }
+
+    if let Some(meta) = &msg.tool_meta {
+        let max_diff_lines = limit.map_or(usize::MAX, |_| MAX_DIFF_LINES);
+        let content_width = (width.saturating_sub(8)) as usize;
+        let diff = diff_lines(meta);
+        for (sign, text) in diff.iter().take(max_diff_lines) {
+            let color = if *sign == '-' { error } else { success };
+            let clean = strip_ansi_and_tabs(text);
+            let truncated = if clean.chars().count() > content_width {
+                truncate_chars(&clean, content_width.saturating_sub(1))
+            } else {
+                clean
+            };
+            lines.push(TuiLine::from(vec![
+                Span::raw("    "),
+                Span::styled(format!("{} {}", sign, truncated), Style::default().fg(color)),
+            ]));
+        }
+        if diff.len() > max_diff_lines {
+            lines.push(TuiLine::from(vec![
+                Span::raw("    "),
+                Span::styled(
+                    format!("... ({} more diff lines)", diff.len() - max_diff_lines),
                     Style::default().fg(dim).italic(),
                 ),
             ]));
@@ -88,6 +192,57 @@ pub fn render_tool_result_lines<'a>(msg: &DisplayMessage, theme: &Theme, width:
     lines
 }
 
+/// Arguments of the call that produced the tool result at `index`, found by
+/// its call id in the assistant messages before it.
+pub fn call_arguments(messages: &[DisplayMessage], index: usize) -> Option<&str> {
+    let id = messages.get(index)?.tool_call_id.as_deref()?;
+    messages[..index]
+        .iter()
+        .rev()
+        .flat_map(|m| m.tool_calls.iter())
+        .find(|tc| tc.id == id)
+        .map(|tc| tc.function.arguments.as_str())
+}
+
+/// JSON arguments indented for reading; other text as is.
+fn pretty_args(args: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(args)
+        .and_then(|v| serde_json::to_string_pretty(&v))
+        .unwrap_or_else(|_| args.to_string())
+}
+
+/// One `N<tab>code` line of `read_file` output, with a dim line number.
+fn read_file_line<'a>(line: &str, highlighter: &mut Highlighter, width: usize, theme: &Theme) -> TuiLine<'a> {
+    let dim = Color::Rgb(theme.dim_text.r, theme.dim_text.g, theme.dim_text.b);
+    let Some((number, code)) = line.split_once('\t').filter(|(n, _)| n.parse::<usize>().is_ok()) else {
+        return TuiLine::from(vec![Span::raw("    "), Span::styled(line.to_string(), Style::default().fg(dim))]);
+    };
+    let gutter = format!("{:>4} ", number);
+    let clean = strip_ansi_and_tabs(code);
+    let code_width = width.saturating_sub(gutter.chars().count());
+    let truncated = if clean.chars().count() > code_width {
+        truncate_chars(&clean, code_width.saturating_sub(1))
+    } else {
+        clean
+    };
+    let mut spans = vec![Span::raw("    "), Span::styled(gutter, Style::default().fg(dim))];
+    spans.extend(highlighter.line(&truncated, theme));
+    TuiLine::from(spans)
+}
+
+/// Removed (`-`) and added (`+`) lines of an edit or write.
+fn diff_lines(meta: &ToolResultMeta) -> Vec<(char, &str)> {
+    match meta {
+        ToolResultMeta::EditFile { old_str, new_str, .. } => old_str
+            .lines()
+            .map(|l| ('-', l))
+            .chain(new_str.lines().map(|l| ('+', l)))
+            .collect(),
+        ToolResultMeta::WriteFile { content, .. } => content.lines().map(|l| ('+', l)).collect(),
+        ToolResultMeta::ReadFile { .. } => Vec::new(),
+    }
+}
+
 /// Strip ANSI escape sequences and replace tabs with spaces.
 fn strip_ansi_and_tabs(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
@@ -172,6 +327,7 @@ fn abbreviate_args(args_json: &str, max_len: usize) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tui::app::MessageRole;
 
     #[test]
     fn abbreviate_short_args() {
@@ -189,4 +345,71 @@ mod tests {
         // or the whole result truncated to max_len)
         assert!(result.len() <= 60 || result.contains('…'));
     }
+
+    #[test]
+    fn edit_diff_lists_removed_then_added() {
+        let meta = ToolResultMeta::EditFile {
+            path: "main.rs".to_string(),
+            old_str: "a\nb".to_string(),
+            new_str: "c".to_string(),
+        };
+        assert_eq!(diff_lines(&meta), vec![('-', "a"), ('-', "b"), ('+', "c")]);
+    }
+
+    #[test]
+    fn read_file_preview_has_gutter_and_highlighting() {
+        let theme = crate::config::themes::get_theme("tokyo-night");
+        let mut highlighter = Highlighter::new(Language::Rust);
+        let line = read_file_line("12\tfn main() {}", &mut highlighter, 80, theme);
+        let text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(text, "      12 fn main() {}");
+        let styles: std::collections::HashSet<_> = line.spans.iter().skip(2).map(|s| s.style.fg).collect();
+        assert!(styles.len() > 1);
+        assert_eq!(diff_lines(&ToolResultMeta::ReadFile { path: "a.rs".to_string() }), vec![]);
+    }
+
+    fn message(role: MessageRole, content: &str) -> DisplayMessage {
+        DisplayMessage {
+            role,
+            content: content.to_string(),
+            reasoning: None,
+            tool_calls: Vec::new(),
+            is_streaming: false,
+            tool_status: Some(ToolStatus::Done),
+            tool_name: Some("bash".to_string()),
+            tool_meta: None,
+            tool_call_id: None,
+            stored_id: None,
+        }
+    }
+
+    #[test]
+    fn detail_levels_and_arguments() {
+        let theme = crate::config::themes::get_theme("tokyo-night");
+        let mut call = message(MessageRole::Assistant, "");
+        call.tool_calls = vec![AccumulatedToolCall {
+            id: "tc_1".to_string(),
+            call_type: "function".to_string(),
+            function: crate::core::api::ToolCallFunction {
+                name: "bash".to_string(),
+                arguments: r#"{"command":"ls"}"#.to_string(),
+            },
+        }];
+        let output: Vec<String> = (1..=20).map(|i| format!("file{}", i)).collect();
+        let mut result = message(MessageRole::Tool, &output.join("\n"));
+        result.tool_call_id = Some("tc_1".to_string());
+        let messages = vec![call, result];
+        let args = call_arguments(&messages, 1);
+        assert_eq!(args, Some(r#"{"command":"ls"}"#));
+
+        let render = |detail| render_tool_result_lines(&messages[1], args, theme, 80, detail, false);
+        let collapsed = render(ToolDetail::Collapsed);
+        assert_eq!(collapsed.len(), 1);
+        assert!(collapsed[0].to_string().ends_with("(20 lines)"));
+        assert_eq!(render(ToolDetail::Preview).len(), 1 + MAX_PREVIEW_LINES + 1);
+        let full = render(ToolDetail::Full);
+        // Header, three lines of pretty JSON, all output
+        assert_eq!(full.len(), 1 + 3 + 20);
+        assert_eq!(full[2].to_string().trim(), r#""command": "ls""#);
+    }
 }
@@ -0,0 +1,180 @@
+use ratatui::prelude::*;
+use ratatui::text::{Line as TuiLine, Span};
+
+/// Incremental search over the transcript as drawn (Ctrl+F).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptSearch {
+    pub query: String,
+    /// Occurrences of the query as (line, column) in the rendered transcript,
+    /// top to bottom.
+    pub matches: Vec<(usize, usize)>,
+    /// Position in `matches` of the match shown.
+    pub current: Option<usize>,
+    /// Scroll offset before the search, restored when it is cancelled.
+    pub saved_offset: u16,
+    /// The query changed since `matches` were found.
+    pub stale: bool,
+    /// The current match has not been scrolled into view yet.
+    pub reveal: bool,
+}
+
+impl TranscriptSearch {
+    pub fn new(saved_offset: u16) -> Self {
+        Self {
+            query: String::new(),
+            matches: Vec::new(),
+            current: None,
+            saved_offset,
+            stale: false,
+            reveal: false,
+        }
+    }
+
+    /// Find the query in `lines`, the text of the rendered transcript,
+    /// selecting the last match.
+    pub fn update(&mut self, lines: &[String]) {
+        let ignore_case = ignores_case(&self.query);
+        self.matches = lines
+            .iter()
+            .enumerate()
+            .flat_map(|(i, line)| {
+                let found = find(line, &self.query, ignore_case);
+                found.into_iter().map(move |(col, _)| (i, col))
+            })
+            .collect();
+        self.current = self.matches.len().checked_sub(1);
+        self.stale = false;
+    }
+
+    /// Move to the previous match, wrapping around to the last.
+    pub fn older(&mut self) {
+        if let Some(current) = self.current {
+            self.current = Some(current.checked_sub(1).unwrap_or(self.matches.len() - 1));
+        }
+    }
+
+    /// Move to the next match, wrapping around to the first.
+    pub fn newer(&mut self) {
+        if let Some(current) = self.current {
+            self.current = Some((current + 1) % self.matches.len());
+        }
+    }
+
+    /// Line and column of the current match.
+    pub fn current_match(&self) -> Option<(usize, usize)> {
+        self.current.and_then(|c| self.matches.get(c).copied())
+    }
+}
+
+/// Smart case: a query without capitals matches any case.
+fn ignores_case(query: &str) -> bool {
+    !query.chars().any(char::is_uppercase)
+}
+
+fn chars_eq(a: char, b: char, ignore_case: bool) -> bool {
+    a == b || (ignore_case && a.to_lowercase().eq(b.to_lowercase()))
+}
+
+/// Char ranges of the non-overlapping occurrences of `query` in `text`.
+fn find(text: &str, query: &str, ignore_case: bool) -> Vec<(usize, usize)> {
+    let text: Vec<char> = text.chars().collect();
+    let query: Vec<char> = query.chars().collect();
+    let mut ranges = Vec::new();
+    if query.is_empty() {
+        return ranges;
+    }
+    let mut i = 0;
+    while i + query.len() <= text.len() {
+        if text[i..i + query.len()].iter().zip(&query).all(|(&a, &b)| chars_eq(a, b, ignore_case)) {
+            ranges.push((i, i + query.len()));
+            i += query.len();
+        } else {
+            i += 1;
+        }
+    }
+    ranges
+}
+
+/// `line` with each occurrence of `query` drawn in `style`.
+pub fn highlight<'a>(line: TuiLine<'a>, query: &str, style: Style) -> TuiLine<'a> {
+    let text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+    let ranges = find(&text, query, ignores_case(query));
+    if ranges.is_empty() {
+        return line;
+    }
+    let mut spans = Vec::with_capacity(line.spans.len() + ranges.len() * 2);
+    let mut offset = 0;
+    for span in &line.spans {
+        let chars: Vec<char> = span.content.chars().collect();
+        let (start, end) = (offset, offset + chars.len());
+        offset = end;
+        // Split the span at match boundaries inside it
+        let mut cuts = vec![start, end];
+        for &(a, b) in &ranges {
+            cuts.extend([a, b].into_iter().filter(|&c| c > start && c < end));
+        }
+        cuts.sort_unstable();
+        cuts.dedup();
+        for pair in cuts.windows(2) {
+            let piece: String = chars[pair[0] - start..pair[1] - start].iter().collect();
+            let matched = ranges.iter().any(|&(a, b)| pair[0] >= a && pair[1] <= b);
+            let piece_style = if matched { span.style.patch(style) } else { span.style };
+            spans.push(Span::styled(piece, piece_style));
+        }
+    }
+    let mut highlighted = TuiLine::from(spans).style(line.style);
+    highlighted.alignment = line.alignment;
+    highlighted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &[&str]) -> Vec<String> {
+        text.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn matches_with_smart_case_and_wraps_around() {
+        let transcript = lines(&["  Fix the parser", "  ok", "  parse args, parse more"]);
+        let mut search = TranscriptSearch::new(0);
+        search.query = "pars".to_string();
+        search.update(&transcript);
+        assert_eq!(search.matches, vec![(0, 10), (2, 2), (2, 14)]);
+        assert_eq!(search.current_match(), Some((2, 14)));
+        search.older();
+        assert_eq!(search.current_match(), Some((2, 2)));
+        search.older();
+        search.older();
+        assert_eq!(search.current_match(), Some((2, 14)));
+        search.newer();
+        assert_eq!(search.current_match(), Some((0, 10)));
+
+        search.query = "Fix".to_string();
+        search.update(&transcript);
+        assert_eq!(search.matches, vec![(0, 2)]);
+        search.query = "FIX".to_string();
+        search.update(&transcript);
+        assert!(search.matches.is_empty());
+        assert_eq!(search.current_match(), None);
+    }
+
+    #[test]
+    fn highlight_splits_spans_at_matches() {
+        let bold = Style::default().bold();
+        let line = TuiLine::from(vec![Span::raw("  foo b"), Span::styled("ar foo", bold)]);
+        let mark = Style::default().bg(Color::Yellow);
+        let out = highlight(line, "bar", mark);
+        assert_eq!(out.to_string(), "  foo bar foo");
+        let marked: Vec<&str> = out
+            .spans
+            .iter()
+            .filter(|s| s.style.bg == Some(Color::Yellow))
+            .map(|s| s.content.as_ref())
+            .collect();
+        assert_eq!(marked, ["b", "ar"]);
+        assert_eq!(out.spans[2].style, bold.patch(mark));
+        assert_eq!(find("aaaa", "aa", true), vec![(0, 2), (2, 4)]);
+    }
+}
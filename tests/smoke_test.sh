@@ -137,6 +137,25 @@ if [ -f "$BINARY" ]; then
   fi
 fi
 
+# ── Test 8: MCP server mode ─────────────────────────────────────────────
+
+echo "Test 8: mcp-serve"
+MCP_OUTPUT=$(printf '%s\n' \
+  '{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}' \
+  '{"jsonrpc":"2.0","id":2,"method":"tools/list"}' \
+  | "$BINARY" mcp-serve --plan 2>&1) || true
+if echo "$MCP_OUTPUT" | grep -q '"read_file"'; then
+  pass "mcp-serve lists read_file"
+else
+  fail "mcp-serve" "tools/list missing read_file"
+fi
+
+if echo "$MCP_OUTPUT" | grep -q '"name":"bash"'; then
+  fail "mcp-serve --plan" "bash should not be listed in PLAN mode"
+else
+  pass "mcp-serve --plan hides bash"
+fi
+
 # ── Summary ─────────────────────────────────────────────────────────────
 
 echo ""